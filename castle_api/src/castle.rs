@@ -1,39 +1,68 @@
 use std::collections::HashMap;
 
 use castle_error::CastleError;
-use castle_query_parser::{parse_message, Message};
-use castle_schema_parser::{parsers::parse_schema::parse_schema, types::SchemaDefinition};
+use castle_query_parser::{parse_message, Inputs, Message};
+use castle_schema_parser::{parsers::parse_schema::parse_schema, types::{EnumDefinition, InputTypeDefinition, SchemaDefinition, TypeDefinition}};
 
 use crate::{
-    executor::execute_message,
-    types::result::CastleResult,
+    executor::{apply_input_defaults::apply_input_defaults, execute_message, substitute_variables::substitute_variables},
+    types::{definitions::{AddedDefinitions, CastleEnum, CastleInput, CastleType}, result::CastleResult},
     validation::{
-        validate_directives_exist::validate_directives_exist,
-        validate_projection::validate_projection,
+        check_message, validate_directives_exist::validate_directives_exist,
         validate_resolvers_exist::validate_resolvers_exist, validate_schema::validate_schema,
+        validate_variables::validate_variable_values, Diagnostics,
     },
     Directive, Resolver,
 };
+
+/// Field resolvers of a single type, keyed by field name
+pub type FieldResolvers<Ctx, E> = HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>;
+
+/// Field resolvers keyed by type name, then field name
+pub type TypeResolvers<Ctx, E> = HashMap<Box<str>, FieldResolvers<Ctx, E>>;
+
+/// How the values returned by resolvers are checked against the return kinds of their fields.
+///
+/// The checking modes take a function to turn a [CastleError::ReturnValue] into the error
+/// type of the resolvers, so mismatches can be reported in [CastleResult::errors].
+pub enum ReturnValueCheck<E> {
+    /// Values aren't checked
+    Off,
+    /// Mismatches are reported, but the value is still returned
+    Warn(fn(CastleError) -> E),
+    /// Mismatches are reported and the field is returned as [Value::Failed](crate::Value::Failed)
+    Strict(fn(CastleError) -> E),
+}
+
 #[derive(derivative::Derivative)]
 #[derivative(Debug)]
 pub struct Castle<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> {
     pub parsed_schema: SchemaDefinition,
     #[derivative(Debug = "ignore")]
-    pub field_resolvers: HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>,
+    pub field_resolvers: TypeResolvers<Ctx, E>,
     #[derivative(Debug = "ignore")]
     pub directives: HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
+    /// Maximum number of resolvers that run concurrently while executing a single message,
+    /// `None` means sibling fields are all resolved at the same time
+    pub max_concurrency: Option<usize>,
+    #[derivative(Debug = "ignore")]
+    pub return_value_check: ReturnValueCheck<E>,
 }
 
 impl<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> Castle<Ctx, E> {
     pub(crate) fn build_and_validate(
-        field_resolvers: HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>,
+        field_resolvers: TypeResolvers<Ctx, E>,
         directives: HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
         parsed_schema: SchemaDefinition,
+        max_concurrency: Option<usize>,
+        return_value_check: ReturnValueCheck<E>,
     ) -> Result<Castle<Ctx, E>, CastleError> {
         let castle = Castle {
             field_resolvers,
             parsed_schema,
             directives,
+            max_concurrency,
+            return_value_check,
         };
         castle.validate()?;
         Ok(castle)
@@ -44,16 +73,21 @@ impl<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> Castle<Ctx, E> {
     /// - Self validate schema
     ///     - all schema_types and enums used as types have been defined in the schema
     /// - Validate schema resolvers & directives (functions) match the ones we've built in Rust
+    ///
+    /// Every problem found is returned at once as [CastleError::Multiple]
     fn validate(&self) -> Result<(), CastleError> {
-        validate_schema(&self.parsed_schema)?;
-        validate_resolvers_exist(&self.parsed_schema, &self.field_resolvers)?;
-        validate_directives_exist(&self.parsed_schema, &self.directives)?;
-        return Ok(());
+        let mut diagnostics = Diagnostics::new();
+        validate_schema(&self.parsed_schema, &mut diagnostics);
+        validate_resolvers_exist(&self.parsed_schema, &self.field_resolvers, &mut diagnostics);
+        validate_directives_exist(&self.parsed_schema, &self.directives, &mut diagnostics);
+        diagnostics.into_result()
     }
 
+    /// Parses and validates a message against the schema.
+    /// Every problem found is returned at once as [CastleError::Multiple]
     pub fn validate_message(&self, query: &str) -> Result<Message, CastleError> {
         let parsed_message = parse_message(query)?;
-        validate_projection(&self.parsed_schema, &parsed_message.projection)?;
+        check_message(&self.parsed_schema, &parsed_message)?;
         Ok(parsed_message)
     }
 
@@ -68,14 +102,40 @@ impl<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> Castle<Ctx, E> {
     ) -> Result<CastleResult<Ctx, E>, CastleError> where 
         Ctx: Send + Sync + 'static,
         E: Send + Sync + 'static
+    {
+        self.run_message_with_variables(query, &HashMap::new(), ctx).await
+    }
+
+    /// Runs a query that declares variables, eg: `message GetUser($id: Uuid) { ... }`
+    /// - Validates query against the schema for validity and type correctness
+    /// - Validates the provided variables against the variables the query declares
+    /// - Substitutes the variables (or their defaults) into the inputs of the query
+    /// - Fills in the default values of any omitted inputs
+    /// - Runs the query using the resolvers
+    /// - Returns the result
+    pub async fn run_message_with_variables(
+        &self,
+        query: &str,
+        variables: &Inputs,
+        ctx: &Ctx,
+    ) -> Result<CastleResult<Ctx, E>, CastleError> where
+        Ctx: Send + Sync + 'static,
+        E: Send + Sync + 'static
     {
         let mut parsed_message = self.validate_message(query)?;
+        let mut diagnostics = Diagnostics::new();
+        validate_variable_values(&self.parsed_schema, &parsed_message.variables, variables, &mut diagnostics);
+        diagnostics.into_result()?;
+        substitute_variables(&mut parsed_message.projection, &parsed_message.variables, variables);
+        apply_input_defaults(&self.parsed_schema, &self.parsed_schema.types["Root"], &mut parsed_message.projection);
         execute_message(
             &mut parsed_message,
             &self.field_resolvers,
             &self.directives,
             &self.parsed_schema,
             ctx,
+            self.max_concurrency,
+            &self.return_value_check,
         )
         .await
     }
@@ -85,10 +145,14 @@ impl<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> Castle<Ctx, E> {
 #[derivative(Debug)]
 pub struct CastleBuilder<Ctx, E> {
     #[derivative(Debug = "ignore")]
-    resolver_map: HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>,
+    resolver_map: TypeResolvers<Ctx, E>,
     #[derivative(Debug = "ignore")]
     directives: HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
     schema: String,
+    max_concurrency: Option<usize>,
+    #[derivative(Debug = "ignore")]
+    return_value_check: ReturnValueCheck<E>,
+    definitions: AddedDefinitions,
 }
 
 impl<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> CastleBuilder<Ctx, E> {
@@ -97,24 +161,97 @@ impl<Ctx: Send + Sync + 'static, E: Send + Sync + 'static> CastleBuilder<Ctx, E>
             resolver_map: HashMap::new(),
             schema: schema.into(),
             directives: HashMap::new(),
+            max_concurrency: None,
+            return_value_check: ReturnValueCheck::Off,
+            definitions: AddedDefinitions::default(),
         }
     }
 
     pub fn build(&mut self) -> Result<Castle<Ctx, E>, CastleError> {
+        let mut parsed_schema = parse_schema(&self.schema)?;
+        std::mem::take(&mut self.definitions).merge_into(&mut parsed_schema)?;
         Castle::build_and_validate(
             self.resolver_map.drain().collect(),
             self.directives.drain().collect(),
-            parse_schema(&self.schema)?,
+            parsed_schema,
+            self.max_concurrency,
+            std::mem::replace(&mut self.return_value_check, ReturnValueCheck::Off),
         )
     }
 
+    /// Limits how many resolvers may run concurrently while executing a single message
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) -> &mut Self {
+        self.max_concurrency = Some(max_concurrency);
+        self
+    }
+
+    /// Sets how the values returned by resolvers are checked against the schema, checks are off by default
+    pub fn set_return_value_check(&mut self, return_value_check: ReturnValueCheck<E>) -> &mut Self {
+        self.return_value_check = return_value_check;
+        self
+    }
+
+    /// Adds a field resolver.
+    ///
+    /// `resolver_name` is either a field of `type Root` (eg: `me`), or a field
+    /// of another type written as `Type.field` (eg: `User.avatar`).
     pub fn add_resolver(
         &mut self,
         resolver_name: &str,
         resolver: impl Resolver<Ctx, E> + 'static,
+    ) -> &mut Self {
+        match resolver_name.split_once('.') {
+            Some((type_name, field_name)) => self.add_type_resolver(type_name, field_name, resolver),
+            None => self.add_type_resolver("Root", resolver_name, resolver),
+        }
+    }
+
+    /// Adds a resolver for the field `field_name` of the type `type_name`
+    ///
+    /// Fields without a resolver are read from the object returned by the parent field's resolver.
+    pub fn add_type_resolver(
+        &mut self,
+        type_name: &str,
+        field_name: &str,
+        resolver: impl Resolver<Ctx, E> + 'static,
     ) -> &mut Self {
         self.resolver_map
-            .insert(resolver_name.into(), Box::new(resolver));
+            .entry(type_name.into())
+            .or_default()
+            .insert(field_name.into(), Box::new(resolver));
+        self
+    }
+
+    /// Adds a `type` defined in Rust (eg: with `#[derive(CastleType)]`) to the schema.
+    ///
+    /// Types, inputs and enums added to the builder are merged into the parsed schema when it's
+    /// built, and can't have the same name as a definition in the schema string.
+    pub fn add_type<T: CastleType>(&mut self) -> &mut Self {
+        self.add_type_definition(T::type_definition())
+    }
+
+    /// Adds an `input` defined in Rust (eg: with `#[derive(CastleInput)]`) to the schema
+    pub fn add_input_type<T: CastleInput>(&mut self) -> &mut Self {
+        self.add_input_type_definition(T::input_type_definition())
+    }
+
+    /// Adds an `enum` defined in Rust (eg: with `#[derive(CastleEnum)]`) to the schema
+    pub fn add_enum<T: CastleEnum>(&mut self) -> &mut Self {
+        self.add_enum_definition(T::enum_definition())
+    }
+
+    pub fn add_type_definition(&mut self, type_definition: TypeDefinition) -> &mut Self {
+        self.definitions.types.push(type_definition);
+        self
+    }
+
+    pub fn add_input_type_definition(&mut self, input_type_definition: InputTypeDefinition) -> &mut Self {
+        self.definitions.input_types.push(input_type_definition);
+        self
+    }
+
+    pub fn add_enum_definition(&mut self, enum_definition: EnumDefinition) -> &mut Self {
+        self.definitions.enums.push(enum_definition);
         self
     }
 
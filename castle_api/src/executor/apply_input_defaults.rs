@@ -0,0 +1,70 @@
+use castle_query_parser::{FieldKind, Input, Inputs, Projection};
+use castle_schema_parser::types::{InputDefinitions, Kind, SchemaDefinition, TypeDefinition};
+
+use super::{matched_enum, projected_type};
+
+/// Fills in the default value of each input omitted from the projection, so resolvers
+/// always see every input that has a default. Omitted `Option<T>` inputs without a default
+/// are filled in as [Input::None], so they look the same as an explicit `None`. This includes
+/// the fields of input types nested inside the inputs.
+///
+/// The projection is expected to have been validated against `type_def`.
+pub(crate) fn apply_input_defaults(schema: &SchemaDefinition, type_def: &TypeDefinition, projection: &mut Projection) {
+    for field in projection.values_mut() {
+        let field_def = match type_def.fields.get(&field.name) {
+            Some(field_def) => field_def,
+            None => continue,
+        };
+        apply_defaults_to_inputs(schema, &field_def.input_definitions, &mut field.inputs);
+
+        match &mut field.kind {
+            FieldKind::Field => {},
+            FieldKind::Object(projection) => if let Ok(type_def) = projected_type(schema, &field_def.return_kind) {
+                apply_input_defaults(schema, type_def, projection);
+            },
+            FieldKind::List(projection) => if let Ok(type_def) = projected_type(schema, &field_def.return_kind.generics[0]) {
+                apply_input_defaults(schema, type_def, projection);
+            },
+            FieldKind::Match(arms) => if let Ok(enum_def) = matched_enum(schema, &field_def.return_kind) {
+                for (variant_name, projection) in arms.iter_mut() {
+                    let type_def = enum_def.variants
+                        .get(variant_name)
+                        .and_then(|variant_def| variant_def.map_type_definition(&enum_def.ident));
+                    if let Some(type_def) = type_def {
+                        apply_input_defaults(schema, &type_def, projection);
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn apply_defaults_to_inputs(schema: &SchemaDefinition, input_defs: &InputDefinitions, inputs: &mut Inputs) {
+    for (ident, input_def) in input_defs {
+        match inputs.get_mut(ident) {
+            Some(input) => apply_defaults_to_input(schema, &input_def.input_kind, input),
+            None => match &input_def.default {
+                Some(default) => {
+                    inputs.insert(ident.clone(), default.clone());
+                },
+                None if &*input_def.input_kind.ident == "Option" => {
+                    inputs.insert(ident.clone(), Input::None);
+                },
+                None => {},
+            },
+        }
+    }
+}
+
+fn apply_defaults_to_input(schema: &SchemaDefinition, kind: &Kind, input: &mut Input) {
+    match (&*kind.ident, input) {
+        ("Option", input) => apply_defaults_to_input(schema, &kind.generics[0], input),
+        ("Vec", Input::List(items)) => for item in items {
+            apply_defaults_to_input(schema, &kind.generics[0], item);
+        },
+        (ident, Input::Map(map)) => if let Some(input_type_def) = schema.input_types.get(ident) {
+            apply_defaults_to_inputs(schema, &input_type_def.input_definitions, map);
+        },
+        _ => {},
+    }
+}
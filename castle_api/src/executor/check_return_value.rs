@@ -0,0 +1,60 @@
+use castle_schema_parser::types::{Kind, SchemaDefinition, VariantKindDefinition};
+
+use crate::Value;
+
+/// Checks that the value a resolver returned matches the return kind of its field, returns the
+/// reason it doesn't if there is a mismatch.
+/// - scalars (and lists or options of them) are checked all the way down
+/// - objects are only checked to be objects, their fields are checked as they are evaluated
+/// - enum variants must be a variant of the enum, with a payload of the right shape
+pub(crate) fn check_return_value<Ctx, E>(schema: &SchemaDefinition, kind: &Kind, value: &Value<Ctx, E>) -> Result<(), String> {
+    match (&*kind.ident, value) {
+        ("Option", Value::None | Value::Void) => Ok(()),
+        ("Option", value) => check_return_value(schema, &kind.generics[0], value),
+        ("String", Value::String(_))
+        | ("number", Value::Number(_))
+        | ("bool", Value::Bool(_))
+        | ("void", Value::Void) => Ok(()),
+        ("Uuid", Value::String(uuid)) => uuid::Uuid::parse_str(uuid)
+            .map(|_| ())
+            .map_err(|e| format!("expected a Uuid but got \"{}\": {}", uuid, e)),
+        ("Vec", Value::Vec(items)) => {
+            for (index, item) in items.iter().enumerate() {
+                check_return_value(schema, &kind.generics[0], item).map_err(|reason| format!("[{}] {}", index, reason))?;
+            }
+            Ok(())
+        },
+        (ident, Value::Object(_)) if schema.types.contains_key(ident) => Ok(()),
+        (ident, Value::Variant(variant_name, payload)) if let Some(enum_def) = schema.enums.get(ident) => {
+            let variant_def = enum_def.variants.get(variant_name)
+                .ok_or_else(|| format!("expected a variant of {} but got unknown variant {}", enum_def.ident, variant_name))?;
+            match (&variant_def.kind, &**payload) {
+                (VariantKindDefinition::Unit, Value::Void) => Ok(()),
+                (VariantKindDefinition::Map(_), Value::Object(_)) => Ok(()),
+                (VariantKindDefinition::Tuple(kinds), Value::Vec(items)) if kinds.len() == items.len() => {
+                    for (index, (kind, item)) in kinds.iter().zip(items).enumerate() {
+                        check_return_value(schema, kind, item).map_err(|reason| format!("{}[{}] {}", variant_name, index, reason))?;
+                    }
+                    Ok(())
+                },
+                (_, payload) => Err(format!("{}.{} has a payload of the wrong shape, got {}", enum_def.ident, variant_name, describe(payload))),
+            }
+        },
+        (_, value) => Err(format!("expected {} but got {}", kind, describe(value))),
+    }
+}
+
+fn describe<Ctx, E>(value: &Value<Ctx, E>) -> String {
+    match value {
+        Value::Bool(_) => "a bool".into(),
+        Value::Number(_) => "a number".into(),
+        Value::String(_) => "a String".into(),
+        Value::Vec(_) => "a list".into(),
+        Value::Object(_) => "an object".into(),
+        Value::Variant(name, _) => format!("variant {}", name),
+        Value::None => "None".into(),
+        Value::Void => "nothing".into(),
+        Value::Failed => "a failed value".into(),
+        Value::Resolver(_) => "a resolver".into(),
+    }
+}
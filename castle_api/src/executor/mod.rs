@@ -1,57 +1,355 @@
 use std::collections::HashMap;
 
-use crate::{types::result::CastleResult, Directive, Next, Resolver, Value};
+pub(crate) mod apply_input_defaults;
+pub(crate) mod check_return_value;
+pub(crate) mod substitute_variables;
+
+use crate::{castle::{ReturnValueCheck, TypeResolvers}, types::result::{CastleResult, FieldError, PathSegment}, Directive, Next, Resolver, Value};
 use async_recursion::async_recursion;
+use check_return_value::check_return_value;
+use futures::future::join_all;
+use tokio::sync::{Semaphore, SemaphorePermit};
 use castle_error::CastleError;
-use castle_query_parser::{Field, Message};
-use castle_schema_parser::types::{SchemaDefinition, FieldDefinition, AppliedDirective, TypeDefinition};
+use castle_query_parser::{Field, FieldKind, Message, Projection};
+use castle_schema_parser::types::{SchemaDefinition, FieldDefinition, AppliedDirective, TypeDefinition, Kind, EnumDefinition};
 
 pub async fn execute_message<Ctx: Send + Sync, E: Send + Sync + 'static>(
     message: &mut Message,
-    field_resolvers: &HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>,
+    field_resolvers: &TypeResolvers<Ctx, E>,
     directives: &HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
     schema: &SchemaDefinition,
     ctx: &Ctx,
+    max_concurrency: Option<usize>,
+    return_value_check: &ReturnValueCheck<E>,
 ) -> Result<CastleResult<Ctx, E>, CastleError> {
-    let mut result = CastleResult {
-        data: HashMap::new(),
-        errors: Vec::new(),
+    let exec = Execution {
+        field_resolvers,
+        directives,
+        schema,
+        ctx,
+        limit: max_concurrency.map(|max| Semaphore::new(max.max(1))),
+        return_value_check,
     };
-    result.data = evaluate_map(message, schema.types.get("Root").unwrap(), field_resolvers, directives, schema, ctx, &mut result.errors).await?;
-    Ok(result)
+    let mut errors = Errors::default();
+    let data = evaluate_map(&exec, &message.projection, schema.types.get("Root").unwrap(), None, &[], &mut errors).await?;
+    Ok(CastleResult {
+        data,
+        errors: errors.resolver,
+        castle_errors: errors.castle,
+    })
+}
+
+/// Everything that stays the same while a single message is being executed
+struct Execution<'a, Ctx, E> {
+    field_resolvers: &'a TypeResolvers<Ctx, E>,
+    directives: &'a HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
+    schema: &'a SchemaDefinition,
+    ctx: &'a Ctx,
+    /// Caps the number of resolvers running at the same time
+    limit: Option<Semaphore>,
+    return_value_check: &'a ReturnValueCheck<E>,
+}
+
+impl<Ctx, E> Execution<'_, Ctx, E> {
+    async fn acquire_permit(&self) -> Option<SemaphorePermit<'_>> {
+        match &self.limit {
+            Some(limit) => limit.acquire().await.ok(),
+            None => None,
+        }
+    }
+}
+
+/// The errors found while evaluating part of a message, sibling fields collect their own so
+/// they can be put in order once all of them are evaluated
+struct Errors<E> {
+    /// Errors returned by resolvers and directives, or found by the [ReturnValueCheck]
+    resolver: Vec<FieldError<E>>,
+    /// Errors Castle found in the values resolvers returned
+    castle: Vec<FieldError<CastleError>>,
+}
+
+impl<E> Default for Errors<E> {
+    fn default() -> Self {
+        Errors {
+            resolver: Vec::new(),
+            castle: Vec::new(),
+        }
+    }
+}
+
+impl<E> Errors<E> {
+    fn append(&mut self, mut other: Errors<E>) {
+        self.resolver.append(&mut other.resolver);
+        self.castle.append(&mut other.castle);
+    }
+}
+
+/// Where the value of a field comes from before its directives are applied.
+/// - Fields with a resolver registered for their type use that resolver
+/// - Other nested fields use the value the parent resolver returned under the field's name
+///   (`None` if the parent didn't return one)
+enum FieldSource<'a, Ctx, E> {
+    Resolver(&'a dyn Resolver<Ctx, E>),
+    Value(Option<Value<Ctx, E>>),
 }
 
-async fn evaluate_map<Ctx: Send + Sync, E: Send + Sync + 'static>(
-    message: &mut Message, 
+/// Evaluates each field of the projection against `type_def`.
+///
+/// `parent` is the object returned by the resolver of the enclosing field (`None` for `Root`).
+/// Each field is resolved by the resolver registered for `type_def` and the field's name,
+/// otherwise it is taken from the parent. The resulting value is then projected with the
+/// field's sub-projection (if any).
+///
+/// Results are keyed by each field's response key (its alias, if renamed), `path` is where
+/// the map is in the result and is used to locate errors.
+/// Sibling fields are evaluated concurrently, errors are collected in the order of the response keys.
+#[async_recursion]
+async fn evaluate_map<Ctx, E>(
+    exec: &Execution<'_, Ctx, E>,
+    projection: &Projection,
     type_def: &TypeDefinition,
-    field_resolvers: &HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>, 
-    directives: &HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>, 
-    schema: &SchemaDefinition, 
-    ctx: &Ctx,
-    errors: &mut Vec<E>,
-) -> Result<HashMap<Box<str>, Value<Ctx, E>>, CastleError> {
-    let mut map = HashMap::new();
+    mut parent: Option<HashMap<Box<str>, Value<Ctx, E>>>,
+    path: &[PathSegment],
+    errors: &mut Errors<E>,
+) -> Result<HashMap<Box<str>, Value<Ctx, E>>, CastleError>
+where
+    Ctx: Send + Sync,
+    E: Send + Sync + 'static,
+{
+    let mut fields: Vec<_> = projection.iter().collect();
+    fields.sort_by_key(|(response_key, _)| *response_key);
 
-    for (field_name, field) in message.projection.iter() {
+    // the same field can be projected more than once under different aliases,
+    // so values taken from the parent are only removed on their last use
+    let mut remaining_uses: HashMap<&str, usize> = HashMap::new();
+    for (_, field) in fields.iter() {
+        *remaining_uses.entry(&field.name).or_default() += 1;
+    }
+
+    let mut evaluations = Vec::with_capacity(fields.len());
+    for (response_key, field) in fields {
         let field_def = type_def
             .fields
-            .get(field_name)
+            .get(&field.name)
             .unwrap();
 
-        let resolver = field_resolvers
-            .get(field_name)
-            .unwrap();
-            
-        match evaluate_field(field, field_def, &field_def.directives[..], ctx, resolver, &directives).await? {
-            Ok(Value::Void) => {},
-            Ok(data) => { map.insert(field_name.clone(), data); },
-            Err(e) => { errors.push(e); }
+        let resolver = exec.field_resolvers
+            .get(&type_def.ident)
+            .and_then(|resolvers| resolvers.get(&field.name));
+
+        let uses = remaining_uses.get_mut(&*field.name).unwrap();
+        *uses -= 1;
+        let parent_value = match (parent.as_mut(), *uses) {
+            (Some(parent), 0) => parent.remove(&field.name),
+            (Some(parent), _) => parent.get(&field.name).cloned(),
+            (None, _) => None,
+        };
+
+        let source = match resolver {
+            Some(resolver) => FieldSource::Resolver(&**resolver),
+            None => FieldSource::Value(parent_value),
+        };
+
+        let field_path = [path, &[PathSegment::Key(response_key.clone())]].concat();
+        evaluations.push(async move {
+            let mut field_errors = Errors::default();
+            let value = evaluate_projected_field(exec, field, field_def, source, field_path, &mut field_errors).await?;
+            Ok::<_, CastleError>((response_key, value, field_errors))
+        });
+    }
+
+    let mut map = HashMap::new();
+    for evaluation in join_all(evaluations).await {
+        let (response_key, value, field_errors) = evaluation?;
+        if let Some(value) = value {
+            map.insert(response_key.clone(), value);
         }
+        errors.append(field_errors);
     }
     Ok(map)
 }
 
-/// evaluate_field(field, field_def, remaining_directives, field_resolvers, ctx) -> Result<Value<Ctx, E>, E>
+/// Resolves a single field (applying its directives) and projects the resolved value.
+/// Returns `None` if a `void` field resolved to [Value::Void], and [Value::Failed] if it failed with an error.
+///
+/// `Option<T>` fields that resolve to [Value::None] or [Value::Void] (including fields missing from
+/// the parent object) are returned as [Value::None], any other field resolving to nothing is an error.
+///
+/// Resolved values are checked against the field's return kind if [ReturnValueCheck] is on.
+async fn evaluate_projected_field<Ctx: Send + Sync, E: Send + Sync + 'static>(
+    exec: &Execution<'_, Ctx, E>,
+    field: &Field,
+    field_def: &FieldDefinition,
+    mut source: FieldSource<'_, Ctx, E>,
+    path: Vec<PathSegment>,
+    errors: &mut Errors<E>,
+) -> Result<Option<Value<Ctx, E>>, CastleError> {
+    match evaluate_field(exec, field, field_def, &field_def.directives[..], &mut source).await? {
+        Ok(Value::None | Value::Void) if is_option(&field_def.return_kind) => Ok(Some(Value::None)),
+        Ok(Value::Void) if &*field_def.return_kind.ident == "void" => Ok(None),
+        Ok(value @ (Value::None | Value::Void)) => Ok(Some(fail(field, &path, errors, CastleError::ReturnValue(format!(
+            "{} resolved to {}, but its type {} is not an Option",
+            field.name,
+            match value {
+                Value::None => "None",
+                _ => "nothing",
+            },
+            field_def.return_kind
+        ).into())))),
+        Ok(data) => {
+            let (into_error, strict) = match exec.return_value_check {
+                ReturnValueCheck::Off => (None, false),
+                ReturnValueCheck::Warn(into_error) => (Some(into_error), false),
+                ReturnValueCheck::Strict(into_error) => (Some(into_error), true),
+            };
+            if let Some(into_error) = into_error {
+                if let Err(reason) = check_return_value(exec.schema, &field_def.return_kind, &data) {
+                    errors.resolver.push(FieldError {
+                        path: path.clone(),
+                        field: field.name.clone(),
+                        span: Some(field.span),
+                        error: into_error(CastleError::ReturnValue(format!("{} {}", field.name, reason).into())),
+                    });
+                    if strict {
+                        return Ok(Some(Value::Failed))
+                    }
+                }
+            }
+            Ok(Some(project_value(exec, field, &field_def.return_kind, data, &path, errors).await?))
+        },
+        Err(error) => {
+            errors.resolver.push(FieldError {
+                path,
+                field: field.name.clone(),
+                span: Some(field.span),
+                error,
+            });
+            Ok(Some(Value::Failed))
+        }
+    }
+}
+
+/// Applies the sub-projection of a field to the value its resolver returned
+/// - [FieldKind::Field] values are returned as is
+/// - [FieldKind::Object] values must be objects, each projected field is evaluated against the return type
+/// - [FieldKind::List] values must be lists, each item is evaluated concurrently against the `Vec`'s generic type
+///   (items of a `Vec<Option<T>>` can be [Value::None])
+/// - [FieldKind::Match] values must be enum variants, map variants are evaluated against the arm matching their name
+///
+/// A value of the wrong shape is reported as a Castle error at `path` (or at the item's path, for
+/// an item of a list) and replaced with [Value::Failed], so the rest of the data is still returned.
+async fn project_value<Ctx: Send + Sync, E: Send + Sync + 'static>(
+    exec: &Execution<'_, Ctx, E>,
+    field: &Field,
+    return_kind: &Kind,
+    value: Value<Ctx, E>,
+    path: &[PathSegment],
+    errors: &mut Errors<E>,
+) -> Result<Value<Ctx, E>, CastleError> {
+    match &field.kind {
+        FieldKind::Field => Ok(value),
+        FieldKind::Object(projection) => {
+            let type_def = projected_type(exec.schema, return_kind)?;
+            match value {
+                Value::Object(object) => Ok(Value::Object(evaluate_map(exec, projection, type_def, Some(object), path, errors).await?)),
+                _ => Ok(fail(field, path, errors, expected(field, "an object"))),
+            }
+        },
+        FieldKind::List(projection) => {
+            let item_kind = &return_kind.generics[0];
+            let type_def = projected_type(exec.schema, item_kind)?;
+            let items = match value {
+                Value::Vec(items) => items,
+                _ => return Ok(fail(field, path, errors, expected(field, "a list"))),
+            };
+            let mut evaluations = Vec::with_capacity(items.len());
+            for (index, item) in items.into_iter().enumerate() {
+                let item_path = [path, &[PathSegment::Index(index)]].concat();
+                evaluations.push(async move {
+                    let mut item_errors = Errors::default();
+                    let item = match item {
+                        Value::None if is_option(item_kind) => Value::None,
+                        Value::Object(item) => Value::Object(evaluate_map(exec, projection, type_def, Some(item), &item_path, &mut item_errors).await?),
+                        _ => fail(field, &item_path, &mut item_errors, expected(field, "an object")),
+                    };
+                    Ok::<_, CastleError>((item, item_errors))
+                });
+            }
+            let mut list = Vec::with_capacity(evaluations.len());
+            for evaluation in join_all(evaluations).await {
+                let (item, item_errors) = evaluation?;
+                list.push(item);
+                errors.append(item_errors);
+            }
+            Ok(Value::Vec(list))
+        },
+        FieldKind::Match(arms) => {
+            let (variant_name, payload) = match value {
+                Value::Variant(variant_name, payload) => (variant_name, *payload),
+                _ => return Ok(fail(field, path, errors, expected(field, "an enum variant"))),
+            };
+            let enum_def = matched_enum(exec.schema, return_kind)?;
+            let variant_def = match enum_def.variants.get(&variant_name) {
+                Some(variant_def) => variant_def,
+                None => return Ok(fail(field, path, errors, CastleError::ReturnValue(
+                    format!("{} resolver returned unknown variant {}", field.name, variant_name).into(),
+                ))),
+            };
+
+            let payload = match (variant_def.map_type_definition(&enum_def.ident), payload) {
+                (Some(type_def), Value::Object(object)) => {
+                    let empty = Projection::new();
+                    let projection = arms.get(&variant_name).unwrap_or(&empty);
+                    let variant_path = [path, &[PathSegment::Key(variant_name.clone())]].concat();
+                    Value::Object(evaluate_map(exec, projection, &type_def, Some(object), &variant_path, errors).await?)
+                },
+                (Some(_), _) => return Ok(fail(field, path, errors, expected(field, "an object"))),
+                (None, payload) => payload,
+            };
+            Ok(Value::Variant(variant_name, Box::new(payload)))
+        },
+    }
+}
+
+/// Records an error Castle found in the value of a field at `path`, returning the [Value::Failed]
+/// that takes the field's place in the data
+fn fail<Ctx, E>(field: &Field, path: &[PathSegment], errors: &mut Errors<E>, error: CastleError) -> Value<Ctx, E> {
+    errors.castle.push(FieldError {
+        path: path.to_vec(),
+        field: field.name.clone(),
+        span: Some(field.span),
+        error,
+    });
+    Value::Failed
+}
+
+fn expected(field: &Field, shape: &str) -> CastleError {
+    CastleError::ReturnValue(format!("{} expected resolver to return {}", field.name, shape).into())
+}
+
+fn is_option(kind: &Kind) -> bool {
+    &*kind.ident == "Option"
+}
+
+/// Finds the [TypeDefinition] a projection is evaluated against, looking through `Option<T>`
+fn projected_type<'a>(schema: &'a SchemaDefinition, kind: &Kind) -> Result<&'a TypeDefinition, CastleError> {
+    match &*kind.ident {
+        "Option" => projected_type(schema, &kind.generics[0]),
+        ident => schema.types.get(ident)
+            .ok_or(CastleError::Validation("Validation did not catch error".into())),
+    }
+}
+
+/// Finds the [EnumDefinition] a match projection is evaluated against, looking through `Option<T>`
+fn matched_enum<'a>(schema: &'a SchemaDefinition, kind: &Kind) -> Result<&'a EnumDefinition, CastleError> {
+    match &*kind.ident {
+        "Option" => matched_enum(schema, &kind.generics[0]),
+        ident => schema.enums.get(ident)
+            .ok_or(CastleError::Validation("Validation did not catch error".into())),
+    }
+}
+
+/// evaluate_field(exec, field, field_def, remaining_directives, source) -> Result<Value<Ctx, E>, E>
 /// - match remaining_directives.get(i)
 ///     - Some(directive)
 ///         - let directive_args be the arguments of the directive
@@ -60,24 +358,27 @@ async fn evaluate_map<Ctx: Send + Sync, E: Send + Sync + 'static>(
 ///             - future select
 ///                 - sender = wait_next.recv()
 ///                     - let remaining_directives be a slice of remaining_directives[1..remaining_directives.len() - 1]
-///                     - sender.send(evaluate_field(field, field_def, remaining_directives, field_resolvers, ctx))
+///                     - sender.send(evaluate_field(exec, field, field_def, remaining_directives, source))
 ///                 - value = directive.field_resolver(field_def, directive_args, ctx, next)
 ///                     - return value
 ///    - None
-///       return resolver.resolve
-///             
+///       return resolver.resolve (or the value taken from the parent), holding a concurrency permit if limited
+///
 #[async_recursion]
-async fn evaluate_field<Ctx: Send + Sync, E: Send + Sync + 'static>(
+async fn evaluate_field<Ctx, E>(
+    exec: &Execution<'_, Ctx, E>,
     field: &Field,
     field_def: &FieldDefinition,
     remaining_directives: &[AppliedDirective],
-    ctx: &Ctx,
-    resolver: &Box<dyn Resolver<Ctx, E>>,
-    directives: &HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
-) -> Result<Result<Value<Ctx, E>, E>, CastleError> {
+    source: &mut FieldSource<'_, Ctx, E>,
+) -> Result<Result<Value<Ctx, E>, E>, CastleError>
+where
+    Ctx: Send + Sync,
+    E: Send + Sync + 'static,
+{
     match remaining_directives.get(0) {
         Some(applied_directive) => {
-            let directive = match directives.get(&applied_directive.ident) {
+            let directive = match exec.directives.get(&applied_directive.ident) {
                 Some(directive) => directive,
                 None => return Err(CastleError::Validation("Validation did not catch error".into())),
             };
@@ -86,17 +387,16 @@ async fn evaluate_field<Ctx: Send + Sync, E: Send + Sync + 'static>(
             let next = Next {
                 sender
             };
-            let mut value_fut = directive.field_visitor(field, &applied_directive.inputs, next, ctx);
+            let mut value_fut = directive.field_visitor(field, &applied_directive.inputs, next, exec.ctx);
             loop {
                 tokio::select! {
                     Some(sender) = wait_next.recv() => {
                         let _ = sender.send(evaluate_field(
+                            exec,
                             field,
                             field_def,
                             &remaining_directives[1..],
-                            ctx,
-                            resolver,
-                            directives
+                            source,
                         ).await?);
                         continue
                     }
@@ -104,6 +404,19 @@ async fn evaluate_field<Ctx: Send + Sync, E: Send + Sync + 'static>(
                 }
             }
         }
-        None => Ok(resolver.resolve_recursively(field, ctx).await)
+        None => match source {
+            FieldSource::Resolver(resolver) => {
+                let _permit = exec.acquire_permit().await;
+                Ok(resolver.resolve_recursively(field, exec.ctx).await)
+            },
+            FieldSource::Value(value) => match value.take() {
+                Some(Value::Resolver(resolver)) => {
+                    let _permit = exec.acquire_permit().await;
+                    Ok(resolver.resolve_recursively(field, exec.ctx).await)
+                },
+                Some(value) => Ok(Ok(value)),
+                None => Ok(Ok(Value::Void)),
+            },
+        }
     }
 }
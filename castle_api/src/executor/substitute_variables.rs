@@ -0,0 +1,27 @@
+use castle_query_parser::{FieldKind, Input, Inputs, Projection, VariableDefinitions};
+
+/// Replaces each variable used in the inputs of the projection with the value provided for it,
+/// or the variable's default value if none was provided. `Option<T>` variables without either are `None`.
+///
+/// The values are expected to have been validated against the variable definitions.
+pub(crate) fn substitute_variables(projection: &mut Projection, variables: &VariableDefinitions, values: &Inputs) {
+    for field in projection.values_mut() {
+        for input in field.inputs.values_mut() {
+            input.substitute_variables(&|name| values.get(name).or_else(|| {
+                let variable = variables.get(name)?;
+                match (&variable.default, &*variable.kind.ident) {
+                    (Some(default), _) => Some(default),
+                    (None, "Option") => Some(&Input::None),
+                    (None, _) => None,
+                }
+            }));
+        }
+        match &mut field.kind {
+            FieldKind::Field => {},
+            FieldKind::Object(projection) | FieldKind::List(projection) => substitute_variables(projection, variables, values),
+            FieldKind::Match(arms) => for projection in arms.values_mut() {
+                substitute_variables(projection, variables, values);
+            },
+        }
+    }
+}
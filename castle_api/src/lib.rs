@@ -5,11 +5,19 @@ use tokio::sync::{oneshot, mpsc};
 use std::fmt::Debug;
 use std::future::Future;
 pub use types::value::Value;
+pub use types::into_value::IntoValue;
+pub use types::from_inputs::{FromInput, FromInputs};
+pub use types::definitions::{CastleEnum, CastleInput, CastleKind, CastleType};
+pub use castle_derive::{CastleEnum, CastleInput, CastleType, FromInputs, IntoValue};
+pub use castle_error::CastleError;
+pub use validation::{check_message, check_schema};
 
 pub use crate::castle::Castle;
 pub use castle_tokenizer::{Number, Primitive};
 
 pub mod castle;
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
 pub(crate) mod executor;
 pub mod types;
 pub(crate) mod validation;
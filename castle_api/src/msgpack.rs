@@ -0,0 +1,127 @@
+//! MessagePack encoding for compact responses, enabled with the `msgpack` feature.
+//!
+//! Values are encoded the same way as they are serialized to JSON (see [Value]'s
+//! [Serialize] impl), except numbers keep whether they are unsigned, signed or floating point.
+
+use std::collections::HashMap;
+
+use castle_error::CastleError;
+use castle_shared_parser::Primitive;
+use serde::Serialize;
+
+use crate::{types::result::CastleResult, Input, Inputs, Number, Value};
+
+/// Encodes a [CastleResult] in the response envelope, using `map_error` to turn each error
+/// into something serializable (see [CastleResult::serialize_with])
+pub fn encode_result<'a, Ctx, E, T, F>(result: &'a CastleResult<Ctx, E>, map_error: F) -> Result<Vec<u8>, CastleError>
+where
+    T: Serialize,
+    F: Fn(&'a E) -> T,
+{
+    encode(&result.serialize_with(map_error))
+}
+
+pub fn encode_value<Ctx, E>(value: &Value<Ctx, E>) -> Result<Vec<u8>, CastleError> {
+    encode(value)
+}
+
+/// Decodes a [Value], variants are decoded as strings or objects (see [Value]'s `Deserialize` impl)
+pub fn decode_value<Ctx, E>(mut bytes: &[u8]) -> Result<Value<Ctx, E>, CastleError> {
+    let value = rmpv::decode::read_value(&mut bytes)
+        .map_err(|e| CastleError::Other(format!("Failed to decode MessagePack: {}", e).into()))?;
+    rmpv::ext::from_value(value)
+        .map_err(|e| CastleError::Other(format!("Failed to decode MessagePack value: {}", e).into()))
+}
+
+pub fn encode_variables(variables: &Inputs) -> Result<Vec<u8>, CastleError> {
+    let map = variables
+        .iter()
+        .map(|(ident, input)| Ok((rmpv::Value::from(&**ident), input_to_msgpack(ident, input)?)))
+        .collect::<Result<_, CastleError>>()?;
+    write(&rmpv::Value::Map(map))
+}
+
+/// Decodes the variables of a request, which must be a map of variable names to values.
+/// `nil` is decoded as [Input::None], enum variants can't be sent as variables.
+pub fn decode_variables(mut bytes: &[u8]) -> Result<Inputs, CastleError> {
+    let value = rmpv::decode::read_value(&mut bytes)
+        .map_err(|e| CastleError::Other(format!("Failed to decode MessagePack: {}", e).into()))?;
+    match value {
+        rmpv::Value::Map(map) => map_to_inputs("variables", map),
+        _ => Err(CastleError::Other("Expected variables to be a MessagePack map".into())),
+    }
+}
+
+fn encode(value: &impl Serialize) -> Result<Vec<u8>, CastleError> {
+    let value = rmpv::ext::to_value(value)
+        .map_err(|e| CastleError::Other(format!("Failed to encode MessagePack value: {}", e).into()))?;
+    write(&value)
+}
+
+fn write(value: &rmpv::Value) -> Result<Vec<u8>, CastleError> {
+    let mut bytes = Vec::new();
+    rmpv::encode::write_value(&mut bytes, value)
+        .map_err(|e| CastleError::Other(format!("Failed to encode MessagePack: {}", e).into()))?;
+    Ok(bytes)
+}
+
+fn input_to_msgpack(path: &str, input: &Input) -> Result<rmpv::Value, CastleError> {
+    Ok(match input {
+        Input::Primitive(Primitive::String(s)) => rmpv::Value::from(&**s),
+        Input::Primitive(Primitive::Boolean(b)) => rmpv::Value::from(*b),
+        Input::Primitive(Primitive::Number(n)) => rmpv::ext::to_value(n)
+            .map_err(|e| CastleError::Other(format!("Failed to encode {}: {}", path, e).into()))?,
+        Input::List(list) => rmpv::Value::Array(list
+            .iter()
+            .map(|item| input_to_msgpack(path, item))
+            .collect::<Result<_, _>>()?),
+        Input::Map(map) => rmpv::Value::Map(map
+            .iter()
+            .map(|(ident, item)| Ok((rmpv::Value::from(&**ident), input_to_msgpack(&format!("{}.{}", path, ident), item)?)))
+            .collect::<Result<_, CastleError>>()?),
+        Input::None => rmpv::Value::Nil,
+        Input::Variant(_) | Input::Variable(_) => Err(CastleError::Other(
+            format!("{} can't be encoded as MessagePack, only plain values can", path).into(),
+        ))?,
+    })
+}
+
+fn msgpack_to_input(path: &str, value: rmpv::Value) -> Result<Input, CastleError> {
+    Ok(match value {
+        rmpv::Value::Nil => Input::None,
+        rmpv::Value::Boolean(b) => Input::Primitive(Primitive::Boolean(b)),
+        rmpv::Value::Integer(i) => match (i.as_u64(), i.as_i64()) {
+            (Some(u), _) => Input::Primitive(Primitive::Number(Number::new(u))),
+            (None, Some(i)) => Input::Primitive(Primitive::Number(Number::new(i))),
+            (None, None) => unreachable!("MessagePack integers are either a u64 or an i64"),
+        },
+        rmpv::Value::F32(f) => Input::Primitive(Primitive::Number(Number::new(f))),
+        rmpv::Value::F64(f) => Input::Primitive(Primitive::Number(Number::new(f))),
+        rmpv::Value::String(s) => match s.into_str() {
+            Some(s) => Input::Primitive(Primitive::String(s.into())),
+            None => Err(CastleError::Other(format!("{} is not a valid UTF-8 string", path).into()))?,
+        },
+        rmpv::Value::Array(items) => Input::List(items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| msgpack_to_input(&format!("{}[{}]", path, index), item))
+            .collect::<Result<_, _>>()?),
+        rmpv::Value::Map(map) => Input::Map(map_to_inputs(path, map)?),
+        rmpv::Value::Binary(_) | rmpv::Value::Ext(..) => Err(CastleError::Other(
+            format!("{} is binary data, which can't be used as an input", path).into(),
+        ))?,
+    })
+}
+
+fn map_to_inputs(path: &str, map: Vec<(rmpv::Value, rmpv::Value)>) -> Result<HashMap<Box<str>, Input>, CastleError> {
+    map.into_iter()
+        .map(|(key, value)| match key {
+            rmpv::Value::String(key) if key.is_str() => {
+                let key = key.into_str().unwrap();
+                let input = msgpack_to_input(&format!("{}.{}", path, key), value)?;
+                Ok((key.into(), input))
+            },
+            key => Err(CastleError::Other(format!("{} has a non-string key {}", path, key).into())),
+        })
+        .collect()
+}
@@ -0,0 +1,111 @@
+use castle_error::CastleError;
+use castle_schema_parser::types::SchemaDefinition;
+use castle_tokenizer::Number;
+
+pub use castle_schema_parser::types::{
+    EnumDefinition, FieldDefinition, InputDefinition, InputTypeDefinition, Kind, Span, TypeDefinition,
+    VariantDefinition, VariantKindDefinition,
+};
+
+/// The Castle kind of a Rust type, eg: `Vec<String>` is `Vec<String>` and `u32` is `number`.
+///
+/// Implemented by `#[derive(CastleType)]`, `#[derive(CastleInput)]` and `#[derive(CastleEnum)]`
+/// as the name of the type, so a struct can't derive both `CastleType` and `CastleInput`.
+pub trait CastleKind {
+    fn kind() -> Kind;
+}
+
+/// A Rust struct that can be added to the schema as a `type`, see [CastleBuilder::add_type](crate::castle::CastleBuilder::add_type)
+pub trait CastleType: CastleKind {
+    fn type_definition() -> TypeDefinition;
+}
+
+/// A Rust struct that can be added to the schema as an `input`, see [CastleBuilder::add_input_type](crate::castle::CastleBuilder::add_input_type)
+pub trait CastleInput: CastleKind {
+    fn input_type_definition() -> InputTypeDefinition;
+}
+
+/// A Rust enum that can be added to the schema as an `enum`, see [CastleBuilder::add_enum](crate::castle::CastleBuilder::add_enum)
+pub trait CastleEnum: CastleKind {
+    fn enum_definition() -> EnumDefinition;
+}
+
+/// A kind that isn't written in any source, used by the derived impls
+pub fn kind(ident: &str, generics: Vec<Kind>) -> Kind {
+    Kind {
+        ident: ident.into(),
+        generics,
+        span: Span::default(),
+    }
+}
+
+macro_rules! impl_castle_kind {
+    ($ident:literal: $($t:ty),*) => {
+        $(
+            impl CastleKind for $t {
+                fn kind() -> Kind {
+                    kind($ident, vec![])
+                }
+            }
+        )*
+    };
+}
+
+impl_castle_kind!("String": String, &str, Box<str>);
+impl_castle_kind!("number": Number, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+impl_castle_kind!("bool": bool);
+impl_castle_kind!("Uuid": uuid::Uuid);
+impl_castle_kind!("void": ());
+
+impl<T: CastleKind> CastleKind for Option<T> {
+    fn kind() -> Kind {
+        kind("Option", vec![T::kind()])
+    }
+}
+
+impl<T: CastleKind> CastleKind for Vec<T> {
+    fn kind() -> Kind {
+        kind("Vec", vec![T::kind()])
+    }
+}
+
+impl<T: CastleKind> CastleKind for Box<T> {
+    fn kind() -> Kind {
+        T::kind()
+    }
+}
+
+/// Definitions added with [CastleBuilder](crate::castle::CastleBuilder), merged into the parsed schema when it's built
+#[derive(Debug, Default)]
+pub(crate) struct AddedDefinitions {
+    pub types: Vec<TypeDefinition>,
+    pub input_types: Vec<InputTypeDefinition>,
+    pub enums: Vec<EnumDefinition>,
+}
+
+impl AddedDefinitions {
+    /// Adds the definitions to `schema`, definitions can't replace ones already in the schema
+    pub(crate) fn merge_into(self, schema: &mut SchemaDefinition) -> Result<(), CastleError> {
+        for def in self.types {
+            check_undefined(schema, &def.ident)?;
+            schema.types.insert(def.ident.clone(), def);
+        }
+        for def in self.input_types {
+            check_undefined(schema, &def.ident)?;
+            schema.input_types.insert(def.ident.clone(), def);
+        }
+        for def in self.enums {
+            check_undefined(schema, &def.ident)?;
+            schema.enums.insert(def.ident.clone(), def);
+        }
+        Ok(())
+    }
+}
+
+/// Types, input types and enums share their names, a kind can only name one of them
+fn check_undefined(schema: &SchemaDefinition, ident: &str) -> Result<(), CastleError> {
+    match schema.types.contains_key(ident) || schema.input_types.contains_key(ident) || schema.enums.contains_key(ident) {
+        true => Err(CastleError::Validation(format!("{} is defined more than once", ident).into())),
+        false => Ok(()),
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use castle_error::CastleError;
+use castle_query_parser::{Input, Inputs};
+use castle_tokenizer::{Number, Primitive};
+
+pub use castle_shared_parser::VariantType;
+
+/// Reads the inputs of a field (or directive) into a Rust value, see `#[derive(FromInputs)]`
+pub trait FromInputs: Sized {
+    fn from_inputs(inputs: &Inputs) -> Result<Self, CastleError>;
+}
+
+/// Reads a single input into a Rust value. `#[derive(FromInputs)]` implements this for structs
+/// (read from map inputs), and for enums (read from variant inputs).
+///
+/// Omitted inputs are read from [Input::None], so only `Option<T>` can be omitted.
+pub trait FromInput: Sized {
+    fn from_input(input: &Input) -> Result<Self, CastleError>;
+}
+
+/// Reads the input called `name`, used by the derived impls
+pub fn input_field<T: FromInput>(inputs: &HashMap<Box<str>, Input>, name: &str) -> Result<T, CastleError> {
+    T::from_input(inputs.get(name).unwrap_or(&Input::None))
+        .map_err(|e| prefix_error(name, e))
+}
+
+fn prefix_error(prefix: &str, err: CastleError) -> CastleError {
+    match err {
+        CastleError::Other(msg) => CastleError::Other(format!("{}: {}", prefix, msg).into()),
+        err => err,
+    }
+}
+
+/// The error for an input that doesn't have the shape `expected` describes
+pub fn unexpected_input(expected: &str, input: &Input) -> CastleError {
+    match input {
+        Input::None => CastleError::Other(format!("expected {} but the input is missing", expected).into()),
+        input => CastleError::Other(format!("expected {} but got {}", expected, input).into()),
+    }
+}
+
+impl FromInput for Input {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        Ok(input.clone())
+    }
+}
+
+impl FromInput for String {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        input.as_str().map(Into::into).ok_or_else(|| unexpected_input("a String", input))
+    }
+}
+
+impl FromInput for bool {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        match input {
+            Input::Primitive(Primitive::Boolean(b)) => Ok(*b),
+            input => Err(unexpected_input("a bool", input)),
+        }
+    }
+}
+
+impl FromInput for uuid::Uuid {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        input.as_uuid().ok_or_else(|| unexpected_input("a Uuid", input))
+    }
+}
+
+impl FromInput for Number {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        match input {
+            Input::Primitive(Primitive::Number(number)) => Ok(*number),
+            input => Err(unexpected_input("a number", input)),
+        }
+    }
+}
+
+macro_rules! impl_from_input_for_numbers {
+    ($($t:ty),*) => {
+        $(
+            impl FromInput for $t {
+                fn from_input(input: &Input) -> Result<Self, CastleError> {
+                    Option::<$t>::from(Number::from_input(input)?)
+                        .ok_or_else(|| unexpected_input("a number", input))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_input_for_numbers!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T: FromInput> FromInput for Option<T> {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        match input {
+            Input::None => Ok(None),
+            input => T::from_input(input).map(Some),
+        }
+    }
+}
+
+impl<T: FromInput> FromInput for Box<T> {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        T::from_input(input).map(Box::new)
+    }
+}
+
+impl<T: FromInput> FromInput for Vec<T> {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        input
+            .as_list()
+            .ok_or_else(|| unexpected_input("a list", input))?
+            .iter()
+            .enumerate()
+            .map(|(index, item)| T::from_input(item).map_err(|e| prefix_error(&format!("[{}]", index), e)))
+            .collect()
+    }
+}
+
+impl<T: FromInput> FromInput for HashMap<Box<str>, T> {
+    fn from_input(input: &Input) -> Result<Self, CastleError> {
+        input
+            .as_map()
+            .ok_or_else(|| unexpected_input("a map", input))?
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), T::from_input(value).map_err(|e| prefix_error(key, e))?)))
+            .collect()
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use castle_query_parser::{Field, FieldKind, Projection};
+use castle_tokenizer::Number;
+
+use crate::Value;
+
+/// Converts a Rust value into a [Value], see `#[derive(IntoValue)]` for structs and enums.
+///
+/// `field` is the field being resolved, only the keys it projects are produced. `None` means
+/// the projection isn't known (eg: an aliased field projected more than once), so every key is produced.
+/// Keys are the names of the fields, the executor returns them under their aliases.
+pub trait IntoValue<Ctx, E> {
+    fn into_value(self, field: Option<&Field>) -> Value<Ctx, E>;
+}
+
+/// The projection of an object or list field, `None` if `field` doesn't have one
+pub fn field_projection(field: Option<&Field>) -> Option<&Projection> {
+    match &field?.kind {
+        FieldKind::Object(projection) | FieldKind::List(projection) => Some(projection),
+        FieldKind::Match(_) | FieldKind::Field => None,
+    }
+}
+
+/// The projection of the arm matching `variant`, `None` if `field` isn't a match projection with such an arm
+pub fn variant_projection<'a>(field: Option<&'a Field>, variant: &str) -> Option<&'a Projection> {
+    match &field?.kind {
+        FieldKind::Match(arms) => arms.get(variant),
+        _ => None,
+    }
+}
+
+/// Whether the key `name` should be produced for `projection`, and the field to convert its value with
+/// - `None` if the key isn't projected
+/// - `Some(None)` if there's no projection (every key is produced), or the key is projected more than once
+/// - `Some(Some(field))` if the key is projected once
+pub fn projected_field<'a>(projection: Option<&'a Projection>, name: &str) -> Option<Option<&'a Field>> {
+    let projection = match projection {
+        Some(projection) => projection,
+        None => return Some(None),
+    };
+    let mut fields = projection.values().filter(|field| &*field.name == name);
+    match (fields.next(), fields.next()) {
+        (None, _) => None,
+        (Some(field), None) => Some(Some(field)),
+        (Some(_), Some(_)) => Some(None),
+    }
+}
+
+impl<Ctx, E> IntoValue<Ctx, E> for Value<Ctx, E> {
+    fn into_value(self, _field: Option<&Field>) -> Value<Ctx, E> {
+        self
+    }
+}
+
+macro_rules! impl_into_value_with_from {
+    ($($t:ty),*) => {
+        $(
+            impl<Ctx, E> IntoValue<Ctx, E> for $t {
+                fn into_value(self, _field: Option<&Field>) -> Value<Ctx, E> {
+                    self.into()
+                }
+            }
+        )*
+    };
+}
+
+impl_into_value_with_from!(bool, String, &str, Number, i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl<Ctx, E> IntoValue<Ctx, E> for uuid::Uuid {
+    fn into_value(self, _field: Option<&Field>) -> Value<Ctx, E> {
+        Value::String(self.to_string())
+    }
+}
+
+impl<Ctx, E> IntoValue<Ctx, E> for () {
+    fn into_value(self, _field: Option<&Field>) -> Value<Ctx, E> {
+        Value::Void
+    }
+}
+
+impl<Ctx, E, T: IntoValue<Ctx, E>> IntoValue<Ctx, E> for Option<T> {
+    fn into_value(self, field: Option<&Field>) -> Value<Ctx, E> {
+        match self {
+            Some(value) => value.into_value(field),
+            None => Value::None,
+        }
+    }
+}
+
+impl<Ctx, E, T: IntoValue<Ctx, E>> IntoValue<Ctx, E> for Box<T> {
+    fn into_value(self, field: Option<&Field>) -> Value<Ctx, E> {
+        (*self).into_value(field)
+    }
+}
+
+/// Each item is converted with the list field's projection
+impl<Ctx, E, T: IntoValue<Ctx, E>> IntoValue<Ctx, E> for Vec<T> {
+    fn into_value(self, field: Option<&Field>) -> Value<Ctx, E> {
+        Value::Vec(self.into_iter().map(|item| item.into_value(field)).collect())
+    }
+}
+
+/// Maps are converted to objects, keeping only the projected keys
+impl<Ctx, E, K: AsRef<str>, T: IntoValue<Ctx, E>> IntoValue<Ctx, E> for HashMap<K, T> {
+    fn into_value(self, field: Option<&Field>) -> Value<Ctx, E> {
+        let projection = field_projection(field);
+        Value::Object(self
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let field = projected_field(projection, key.as_ref())?;
+                Some((key.as_ref().into(), value.into_value(field)))
+            })
+            .collect())
+    }
+}
@@ -1,2 +1,5 @@
 pub mod value;
-pub mod result;
\ No newline at end of file
+pub mod result;
+pub mod into_value;
+pub mod from_inputs;
+pub mod definitions;
\ No newline at end of file
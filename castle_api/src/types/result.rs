@@ -1,10 +1,204 @@
-use std::{collections::HashMap};
+use std::{collections::HashMap, fmt::Display};
+
+use castle_error::CastleError;
+use castle_shared_parser::Span;
+use serde::{ser::{SerializeMap, SerializeSeq}, Serialize, Serializer};
 
 use crate::Value;
 
 
+/// The result of running a message.
+///
+/// Results are sent to clients in this envelope (shown as JSON):
+/// ```json
+/// {
+///     "data": {
+///         "users": [
+///             { "name": "Alice", "avatar": null }
+///         ]
+///     },
+///     "errors": [
+///         {
+///             "path": ["users", 0, "avatar"],
+///             "field": "avatar",
+///             "span": { "start": { "line_number": 4, "column_number": 13 }, "end": { "line_number": 4, "column_number": 19 } },
+///             "error": "no avatar"
+///         }
+///     ]
+/// }
+/// ```
+/// See [Value]'s [Serialize] impl for how the data is serialized. Errors are serialized with `E`'s
+/// own [Serialize] impl, or mapped to something serializable with [CastleResult::serialize_with].
+/// [CastleResult::castle_errors] follow them in the same list, with their message as the error.
 #[derive(Debug, PartialEq)]
 pub struct CastleResult<Ctx, E> {
+    /// Fields that failed are kept as [Value::Failed], their errors are in [CastleResult::errors]
+    /// or [CastleResult::castle_errors]
     pub data: HashMap<Box<str>, Value<Ctx, E>>,
-    pub errors: Vec<E>
-}
\ No newline at end of file
+    pub errors: Vec<FieldError<E>>,
+    /// Errors Castle found in the values resolvers returned, eg: an object where a list was expected
+    pub castle_errors: Vec<FieldError<CastleError>>,
+}
+
+/// An error returned by the resolver (or a directive) of a field, along with where it happened
+#[derive(Debug, PartialEq)]
+pub struct FieldError<E> {
+    /// Where the failed field is in [CastleResult::data], using response keys (aliases) and list indices
+    pub path: Vec<PathSegment>,
+    /// The name of the field that failed, as defined in the schema
+    pub field: Box<str>,
+    /// Where the field is in the message, if known
+    pub span: Option<Span>,
+    pub error: E,
+}
+
+/// A single step of a [FieldError::path]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PathSegment {
+    /// The response key of a field, or the name of an enum variant
+    Key(Box<str>),
+    /// The index of an item in a list
+    Index(usize),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathSegment::Key(key) => write!(f, "{}", key),
+            PathSegment::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+impl From<&str> for PathSegment {
+    fn from(key: &str) -> Self {
+        PathSegment::Key(key.into())
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> Self {
+        PathSegment::Index(index)
+    }
+}
+
+impl Serialize for PathSegment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            PathSegment::Key(key) => serializer.serialize_str(key),
+            PathSegment::Index(index) => serializer.serialize_u64(*index as u64),
+        }
+    }
+}
+
+impl<Ctx, E> CastleResult<Ctx, E> {
+    /// Serializes the result in the response envelope, using `map_error` to turn each
+    /// error into something serializable (eg: its message)
+    pub fn serialize_with<'a, T, F>(&'a self, map_error: F) -> SerializeWith<'a, Ctx, E, F>
+    where
+        T: Serialize,
+        F: Fn(&'a E) -> T,
+    {
+        SerializeWith { result: self, map_error }
+    }
+
+    /// Serializes the result as JSON in the response envelope, see [CastleResult::serialize_with]
+    pub fn to_json<'a, T, F>(&'a self, map_error: F) -> Result<String, serde_json::Error>
+    where
+        T: Serialize,
+        F: Fn(&'a E) -> T,
+    {
+        serde_json::to_string(&self.serialize_with(map_error))
+    }
+}
+
+impl<Ctx, E: Serialize> Serialize for CastleResult<Ctx, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.serialize_with(|error| error).serialize(serializer)
+    }
+}
+
+/// A [CastleResult] with its errors mapped to something serializable, see [CastleResult::serialize_with]
+pub struct SerializeWith<'a, Ctx, E, F> {
+    result: &'a CastleResult<Ctx, E>,
+    map_error: F,
+}
+
+impl<'a, Ctx, E, T, F> Serialize for SerializeWith<'a, Ctx, E, F>
+where
+    T: Serialize,
+    F: Fn(&'a E) -> T,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut envelope = serializer.serialize_map(Some(2))?;
+        envelope.serialize_entry("data", &self.result.data)?;
+        envelope.serialize_entry("errors", &SerializeErrors(self))?;
+        envelope.end()
+    }
+}
+
+struct SerializeErrors<'a, 'b, Ctx, E, F>(&'b SerializeWith<'a, Ctx, E, F>);
+
+impl<'a, Ctx, E, T, F> Serialize for SerializeErrors<'a, '_, Ctx, E, F>
+where
+    T: Serialize,
+    F: Fn(&'a E) -> T,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let SerializeWith { result, map_error } = self.0;
+        let mut errors = serializer.serialize_seq(Some(result.errors.len() + result.castle_errors.len()))?;
+        for error in result.errors.iter() {
+            errors.serialize_element(&SerializedFieldError::new(error, map_error(&error.error)))?;
+        }
+        for error in result.castle_errors.iter() {
+            errors.serialize_element(&SerializedFieldError::new(error, error.error.to_string()))?;
+        }
+        errors.end()
+    }
+}
+
+// the error and its span are written as maps rather than structs, so that formats which
+// write structs as arrays (eg: MessagePack) produce the same envelope as JSON
+struct SerializedFieldError<'a, T> {
+    path: &'a [PathSegment],
+    field: &'a str,
+    span: Option<Span>,
+    error: T,
+}
+
+impl<'a, T> SerializedFieldError<'a, T> {
+    fn new<E>(field_error: &'a FieldError<E>, error: T) -> Self {
+        SerializedFieldError {
+            path: &field_error.path,
+            field: &field_error.field,
+            span: field_error.span,
+            error,
+        }
+    }
+}
+
+impl<T: Serialize> Serialize for SerializedFieldError<'_, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(4))?;
+        map.serialize_entry("path", self.path)?;
+        map.serialize_entry("field", self.field)?;
+        map.serialize_entry("span", &self.span.map(SerializedSpan))?;
+        map.serialize_entry("error", &self.error)?;
+        map.end()
+    }
+}
+
+struct SerializedSpan(Span);
+
+impl Serialize for SerializedSpan {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(2))?;
+        for (key, position) in [("start", self.0.start), ("end", self.0.end)] {
+            map.serialize_entry(key, &HashMap::from([
+                ("line_number", position.line_number()),
+                ("column_number", position.column_number()),
+            ]))?;
+        }
+        map.end()
+    }
+}
@@ -1,23 +1,45 @@
-use std::{fmt::Debug, collections::HashMap};
-use castle_query_parser::Field;
+use std::{fmt::Debug, collections::HashMap, sync::Arc};
 use castle_tokenizer::Number;
-use serde::{Serialize, Deserialize};
+use serde::{de::{MapAccess, SeqAccess, Visitor}, ser::{SerializeMap, SerializeSeq}, Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::Resolver;
 
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(bound = "")]
+/// The value of a field, see the [Serialize] impl for how values are sent to clients
+#[derive(Debug)]
 pub enum Value<Ctx, E> {
     Bool(bool),
     Number(Number),
     String(String),
     Vec(Vec<Value<Ctx, E>>),
     Object(HashMap<Box<str>, Value<Ctx, E>>),
-    #[serde(skip)]
+    /// A variant of an enum, tagged with the variant's name.
+    /// Unit variants hold [Value::Void], tuple variants a [Value::Vec] and map variants a [Value::Object]
+    Variant(Box<str>, Box<Value<Ctx, E>>),
+    /// No value, only fields with an `Option<T>` return kind can resolve to `None`
+    None,
     Void,
-    #[serde(skip)]
-    Resolver(Box<dyn Resolver<Ctx, E>>),
+    /// The field's resolver (or one of its directives) returned an error,
+    /// the error is in [CastleResult::errors](crate::types::result::CastleResult::errors)
+    Failed,
+    Resolver(Arc<dyn Resolver<Ctx, E>>),
+}
+
+impl<Ctx, E> Clone for Value<Ctx, E> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Bool(b) => Self::Bool(*b),
+            Self::Number(n) => Self::Number(*n),
+            Self::String(s) => Self::String(s.clone()),
+            Self::Vec(v) => Self::Vec(v.clone()),
+            Self::Object(o) => Self::Object(o.clone()),
+            Self::Variant(name, v) => Self::Variant(name.clone(), v.clone()),
+            Self::None => Self::None,
+            Self::Void => Self::Void,
+            Self::Failed => Self::Failed,
+            Self::Resolver(r) => Self::Resolver(r.clone()),
+        }
+    }
 }
 
 impl <Ctx, E> From<Number> for Value<Ctx, E> {
@@ -34,13 +56,122 @@ impl <Ctx, E> PartialEq for Value<Ctx, E> {
             (Self::String(l0), Self::String(r0)) => l0 == r0,
             (Self::Vec(l0), Self::Vec(r0)) => l0 == r0,
             (Self::Object(l0), Self::Object(r0)) => l0 == r0,
+            (Self::Variant(l0, l1), Self::Variant(r0, r1)) => l0 == r0 && l1 == r1,
+            (Self::None, Self::None) => true,
             (Self::Void, Self::Void) => true,
+            (Self::Failed, Self::Failed) => true,
             (Self::Resolver(l0), Self::Resolver(r0)) => l0 == r0,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
 }
 
+/// Values are serialized as plain data, eg: in JSON
+/// - [Value::Bool], [Value::Number] and [Value::String] as booleans, numbers and strings
+/// - [Value::Vec] as arrays and [Value::Object] as objects
+/// - unit variants as their name (`"Emoji"`), other variants as an object with the name as
+///   the only key (`{"SVGIcon": {"url": "..."}}`)
+/// - [Value::None], [Value::Void], [Value::Failed] and unresolved [Value::Resolver]s as `null`
+impl<Ctx, E> Serialize for Value<Ctx, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Bool(b) => serializer.serialize_bool(*b),
+            Self::Number(n) => n.serialize(serializer),
+            Self::String(s) => serializer.serialize_str(s),
+            Self::Vec(v) => {
+                let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                for item in v {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            },
+            Self::Object(o) => {
+                let mut map = serializer.serialize_map(Some(o.len()))?;
+                for (key, value) in o {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            },
+            Self::Variant(name, payload) => match **payload {
+                Self::Void => serializer.serialize_str(name),
+                ref payload => {
+                    let mut map = serializer.serialize_map(Some(1))?;
+                    map.serialize_entry(name, payload)?;
+                    map.end()
+                },
+            },
+            Self::None | Self::Void | Self::Failed | Self::Resolver(_) => serializer.serialize_unit(),
+        }
+    }
+}
+
+/// Deserializes the plain data produced by the [Serialize] impl. Variants can't be told apart
+/// from strings and objects, so they are deserialized as [Value::String] or [Value::Object],
+/// and `null` is deserialized as [Value::None].
+impl<'de, Ctx, E> Deserialize<'de> for Value<Ctx, E> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor(std::marker::PhantomData))
+    }
+}
+
+struct ValueVisitor<Ctx, E>(std::marker::PhantomData<fn() -> (Ctx, E)>);
+
+impl<'de, Ctx, E> Visitor<'de> for ValueVisitor<Ctx, E> {
+    type Value = Value<Ctx, E>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a bool, number, string, list, map or null")
+    }
+
+    fn visit_bool<Err: serde::de::Error>(self, b: bool) -> Result<Self::Value, Err> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<Err: serde::de::Error>(self, i: i64) -> Result<Self::Value, Err> {
+        Ok(Value::Number(i.into()))
+    }
+
+    fn visit_u64<Err: serde::de::Error>(self, u: u64) -> Result<Self::Value, Err> {
+        Ok(Value::Number(u.into()))
+    }
+
+    fn visit_f64<Err: serde::de::Error>(self, f: f64) -> Result<Self::Value, Err> {
+        Ok(Value::Number(f.into()))
+    }
+
+    fn visit_str<Err: serde::de::Error>(self, s: &str) -> Result<Self::Value, Err> {
+        Ok(Value::String(s.to_string()))
+    }
+
+    fn visit_string<Err: serde::de::Error>(self, s: String) -> Result<Self::Value, Err> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_unit<Err: serde::de::Error>(self) -> Result<Self::Value, Err> {
+        Ok(Value::None)
+    }
+
+    fn visit_none<Err: serde::de::Error>(self) -> Result<Self::Value, Err> {
+        Ok(Value::None)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(Value::Vec(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut object = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry::<String, _>()? {
+            object.insert(key.into(), value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
 // Implement From for all the primitive numeric types
 macro_rules! impl_from_primitive {
     ($($t:ty),*) => {
@@ -100,6 +231,17 @@ impl<Ctx, E> Value<Ctx, E> {
             _ => None,
         }
     }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, Self::None)
+    }
+
+    pub fn as_variant(self) -> Option<(Box<str>, Value<Ctx, E>)> {
+        match self {
+            Self::Variant(name, v) => Some((name, *v)),
+            _ => None,
+        }
+    }
 }
 
 
@@ -133,8 +275,23 @@ impl<Ctx, IntoV: Into<Value<Ctx, E>>, AsStr: AsRef<str>, E> From<HashMap<AsStr,
     }
 }
 
+impl<Ctx, E, T: Into<Value<Ctx, E>>> From<Option<T>> for Value<Ctx, E> {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(value) => value.into(),
+            None => Value::None,
+        }
+    }
+}
+
 impl<Ctx, E> From<Box<dyn Resolver<Ctx, E>>> for Value<Ctx, E> {
     fn from(value: Box<dyn Resolver<Ctx, E>>) -> Self {
+        Value::Resolver(value.into())
+    }
+}
+
+impl<Ctx, E> From<Arc<dyn Resolver<Ctx, E>>> for Value<Ctx, E> {
+    fn from(value: Arc<dyn Resolver<Ctx, E>>) -> Self {
         Value::Resolver(value)
     }
 }
@@ -1,8 +1,37 @@
+use castle_error::{CastleError, Diagnostic};
+use castle_query_parser::Message;
+use castle_schema_parser::types::SchemaDefinition;
+use castle_shared_parser::Span;
+
+use self::{
+    validate_projection::validate_projection, validate_schema::validate_schema,
+    validate_variables::validate_variable_definitions,
+};
+
 pub(crate) mod validate_schema;
 pub(crate) mod validate_projection;
 pub(crate) mod validate_directives_exist;
 pub(crate) mod validate_resolvers_exist;
 pub(crate) mod validate_inputs;
+pub(crate) mod validate_variables;
+
+/// Validates a schema on its own, without checking its resolvers or directives have been
+/// implemented (see [CastleBuilder](crate::castle::CastleBuilder) for that).
+/// Every problem found is returned at once as [CastleError::Multiple]
+pub fn check_schema(schema: &SchemaDefinition) -> Result<(), CastleError> {
+    let mut diagnostics = Diagnostics::new();
+    validate_schema(schema, &mut diagnostics);
+    diagnostics.into_result()
+}
+
+/// Validates a parsed message (its variable definitions and projection) against the schema.
+/// Every problem found is returned at once as [CastleError::Multiple]
+pub fn check_message(schema: &SchemaDefinition, message: &Message) -> Result<(), CastleError> {
+    let mut diagnostics = Diagnostics::new();
+    validate_variable_definitions(schema, &message.variables, &mut diagnostics);
+    validate_projection(schema, &message.variables, &message.projection, &mut diagnostics);
+    diagnostics.into_result()
+}
 
 pub fn join_paths(path: &[&str]) -> String {
     let mut joined = String::new();
@@ -13,4 +42,68 @@ pub fn join_paths(path: &[&str]) -> String {
         joined.push_str(path);
     }
     joined
-}
\ No newline at end of file
+}
+
+/// Collects every problem found while validating, so they can all be reported at once
+/// instead of one at a time.
+///
+/// Problems are attributed to the innermost span set with [Diagnostics::within], so deeply
+/// nested checks (eg: type checking inputs) don't need to know where they are in the source.
+#[derive(Debug, Default)]
+pub(crate) struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+    span: Option<Span>,
+}
+
+impl Diagnostics {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a problem found at `path`, in the current span (if there is one)
+    pub(crate) fn push(&mut self, path: &[&str], error: CastleError) {
+        self.diagnostics.push(Diagnostic {
+            path: join_paths(path).into(),
+            error,
+            span: self.span,
+        });
+    }
+
+    /// Records a problem found at `path`, in the given span (if it is in the source)
+    pub(crate) fn push_at(&mut self, path: &[&str], span: Span, error: CastleError) {
+        self.diagnostics.push(Diagnostic {
+            path: join_paths(path).into(),
+            error,
+            span: in_source(span),
+        });
+    }
+
+    /// Runs `f` with `span` as the current span, problems pushed inside `f` without a span of
+    /// their own are attributed to it
+    pub(crate) fn within<T>(&mut self, span: Span, f: impl FnOnce(&mut Self) -> T) -> T {
+        let outer = std::mem::replace(&mut self.span, in_source(span));
+        let result = f(self);
+        self.span = outer;
+        result
+    }
+
+    /// Returns [CastleError::Multiple] with every problem found (sorted by path), if there were any
+    pub(crate) fn into_result(mut self) -> Result<(), CastleError> {
+        match self.diagnostics.is_empty() {
+            true => Ok(()),
+            false => {
+                self.diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+                Err(CastleError::Multiple(self.diagnostics))
+            },
+        }
+    }
+}
+
+/// Definitions added in code (eg: by the derives) aren't in any source, they are given the
+/// default span, which is empty and so can't be the span of anything parsed
+fn in_source(span: Span) -> Option<Span> {
+    match span == Span::default() {
+        true => None,
+        false => Some(span),
+    }
+}
@@ -5,15 +5,17 @@ use castle_schema_parser::types::SchemaDefinition;
 
 use crate::Directive;
 
+use super::Diagnostics;
+
 
 pub(crate) fn validate_directives_exist<Ctx, E>(
     parsed_schema: &SchemaDefinition,
     directives: &HashMap<Box<str>, Box<dyn Directive<Ctx, E>>>,
-) -> Result<(), CastleError> {
-    for name in parsed_schema.directives.keys() {
+    diagnostics: &mut Diagnostics,
+) {
+    for (name, directive_def) in parsed_schema.directives.iter() {
         if !directives.contains_key(name) {
-            return Err(CastleError::MissingDirective(name.clone()));
+            diagnostics.push_at(&[&format!("@{}", name)], directive_def.span, CastleError::MissingDirective(name.clone()));
         }
     }
-    Ok(())
-}
\ No newline at end of file
+}
@@ -1,25 +1,28 @@
+use std::collections::HashMap;
+
 use castle_error::CastleError;
-use castle_query_parser::{Input, Inputs};
+use castle_query_parser::{Input, Inputs, VariableDefinitions};
 use castle_schema_parser::types::{
-    DirectiveLocation, InputDefinition, InputDefinitions, Kind, SchemaDefinition,
+    DirectiveLocation, EnumDefinition, InputDefinition, InputDefinitions, Kind, SchemaDefinition,
+    VariantKindDefinition,
 };
-use castle_shared_parser::Primitive;
+use castle_shared_parser::{Primitive, Variant, VariantType};
 
-use super::{join_paths, validate_schema::validate_directives::validate_directive};
+use super::{join_paths, validate_schema::validate_directives::validate_directive, Diagnostics};
 
 /// we have an [InputDefinition], which has a [input_kind](Kind).
 /// we want to validate that the type the user provided as [Input]
 /// matches the [input_kind](Kind) of the [InputDefinition].
 pub(crate) fn type_check_input_against_input_definition(
     schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
     path: &[&str], // used to build error message
     input_def: &InputDefinition,
     input_value: &Input,
-) -> Result<(), CastleError> {
-    // TODO: maybe mutate the input to include the default value?
-
+    diagnostics: &mut Diagnostics,
+) {
     // we will first check the input kind matches the expected type
-    type_check_input_against_expected_type(schema, path, &input_def.input_kind, input_value)?;
+    type_check_input_against_expected_type(schema, variables, path, &input_def.input_kind, input_value, diagnostics);
 
     // typecheck each of the input directives
     for input_directive in input_def.directives.iter() {
@@ -28,47 +31,161 @@ pub(crate) fn type_check_input_against_input_definition(
             path,
             input_directive,
             DirectiveLocation::InputFieldDefinition,
-        )?;
+            diagnostics,
+        );
     }
-
-    Ok(())
 }
 
 pub(crate) fn type_check_input_against_expected_type(
     schema: &SchemaDefinition,
+    variables: &VariableDefinitions, // the variables declared by the message
     path: &[&str], // used to build error message
     expected_kind: &Kind,
     input_value: &Input,
-) -> Result<(), CastleError> {
+    diagnostics: &mut Diagnostics,
+) {
     match input_value {
         // TODO: maybe implement scalar types?
-        // TODO: Uuid
-        // TODO: Option
-        // TODO: Enum
 
-        Input::Variant(_) => {}
+        Input::Variable(name) => match variables.get(name) {
+            Some(variable) if variable.kind == *expected_kind => {}
+            // a variable of kind T can be used where Option<T> is expected
+            Some(variable) if &*expected_kind.ident == "Option" && variable.kind == expected_kind.generics[0] => {}
+            Some(variable) => diagnostics.push(path, CastleError::Validation(format!(
+                "{} expected input of type {} but variable ${} is declared as {}",
+                join_paths(path),
+                expected_kind,
+                name,
+                variable.kind
+            ).into())),
+            None => diagnostics.push(path, CastleError::Validation(format!(
+                "{} uses variable ${} which was not declared by the message",
+                join_paths(path),
+                name
+            ).into())),
+        },
+        Input::None if &*expected_kind.ident == "Option" => {}
+        input_value if &*expected_kind.ident == "Option" =>
+            type_check_input_against_expected_type(schema, variables, path, &expected_kind.generics[0], input_value, diagnostics),
         Input::Primitive(Primitive::String(..)) if &*expected_kind.ident == "String" => {}
+        Input::Primitive(Primitive::String(uuid)) if &*expected_kind.ident == "Uuid" => match uuid::Uuid::parse_str(uuid) {
+            Ok(_) => {}
+            Err(e) => diagnostics.push(path, CastleError::Validation(format!(
+                "{} expected a Uuid but got \"{}\": {}",
+                join_paths(path),
+                uuid,
+                e
+            ).into())),
+        },
         Input::Primitive(Primitive::Number(..)) if &*expected_kind.ident == "number" => {}
         Input::Primitive(Primitive::Boolean(..)) if &*expected_kind.ident == "bool" => {}
         Input::List(list) if &*expected_kind.ident == "Vec" => for (index, item) in list.iter().enumerate() {
-            type_check_input_against_expected_type(schema, &[&format!("{}[{}]", join_paths(path), index)], &expected_kind.generics[0], item)?;
+            type_check_input_against_expected_type(schema, variables, &[&format!("{}[{}]", join_paths(path), index)], &expected_kind.generics[0], item, diagnostics);
         },
         Input::Map(map) if let Some(input_def) = schema.input_types.get(&expected_kind.ident) =>
             type_check_inputs_against_input_definitions(
                 schema,
+                variables,
                 path,
                 &input_def.input_definitions,
                 map,
-            )?,
-        input_value => Err(CastleError::Validation(format!(
+                diagnostics,
+            ),
+        Input::Variant(variant) if let Some(enum_def) = schema.enums.get(&expected_kind.ident) =>
+            type_check_variant_against_enum_definition(schema, variables, path, enum_def, variant, diagnostics),
+        input_value => diagnostics.push(path, CastleError::Validation(format!(
             "{} expected input of type {} but got {}",
             join_paths(path),
             expected_kind,
             input_value
-        ).into()))?,
+        ).into())),
     }
+}
 
-    Ok(())
+/// Validates that the [Variant] the user provided is a variant of the [EnumDefinition]
+/// - the variant must exist on the enum
+/// - unit variants can't have values, tuple variants need a value for each of their kinds
+/// - map variants need every field (except `Option<T>` fields) and no unspecified fields
+/// - each value is type checked against the kind of the variant
+fn type_check_variant_against_enum_definition(
+    schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
+    path: &[&str],
+    enum_def: &EnumDefinition,
+    variant: &Variant,
+    diagnostics: &mut Diagnostics,
+) {
+    let variant_def = match enum_def.variants.get(variant.ident()) {
+        Some(variant_def) => variant_def,
+        None => return diagnostics.push(path, CastleError::Validation(format!(
+            "{} enum {} has no variant named {}",
+            join_paths(path),
+            enum_def.ident,
+            variant.ident()
+        ).into())),
+    };
+    let path: &[&str] = &[path, &[variant.ident()]].concat();
+
+    match (&variant_def.kind, variant.value()) {
+        (VariantKindDefinition::Unit, VariantType::Unit) => {}
+        (VariantKindDefinition::Tuple(kinds), VariantType::Tuple(items)) if kinds.len() == items.len() => {
+            for (index, (kind, item)) in kinds.iter().zip(items).enumerate() {
+                type_check_input_against_expected_type(schema, variables, &[&format!("{}[{}]", join_paths(path), index)], kind, item, diagnostics);
+            }
+        },
+        (VariantKindDefinition::Tuple(kinds), VariantType::Tuple(items)) => diagnostics.push(path, CastleError::Validation(format!(
+            "{} expected {} values but got {}",
+            join_paths(path),
+            kinds.len(),
+            items.len()
+        ).into())),
+        (VariantKindDefinition::Map(fields), VariantType::Map(map)) => {
+            for (ident, item) in map {
+                let item_path = &[path, &[&**ident]].concat();
+                match fields.get(ident) {
+                    Some(kind) => type_check_input_against_expected_type(schema, variables, item_path, kind, item, diagnostics),
+                    None => diagnostics.push(item_path, CastleError::Validation(format!("{} was not specified in variant definition", join_paths(item_path)).into())),
+                }
+            }
+            for (ident, kind) in fields {
+                if !map.contains_key(ident) && &*kind.ident != "Option" {
+                    let item_path = &[path, &[&**ident]].concat();
+                    diagnostics.push(item_path, CastleError::Validation(format!(
+                        "{} was not specified in input, but is required",
+                        join_paths(item_path)
+                    ).into()))
+                }
+            }
+        },
+        (expected, _) => diagnostics.push(path, CastleError::Validation(format!(
+            "{} expected a {} variant",
+            join_paths(path),
+            match expected {
+                VariantKindDefinition::Unit => "unit",
+                VariantKindDefinition::Tuple(_) => "tuple",
+                VariantKindDefinition::Map(_) => "map",
+            }
+        ).into())),
+    }
+}
+
+/// Type checks the default value of an [InputDefinition] (if it has one) against its [input_kind](Kind)
+pub(crate) fn type_check_input_default(
+    schema: &SchemaDefinition,
+    path: &[&str],
+    input_def: &InputDefinition,
+    diagnostics: &mut Diagnostics,
+) {
+    if let Some(default) = &input_def.default {
+        type_check_input_against_expected_type(
+            schema,
+            &HashMap::new(),
+            &[path, &[&input_def.ident]].concat(),
+            &input_def.input_kind,
+            default,
+            diagnostics,
+        )
+    }
 }
 
 /// We want to validate that the user provided all of the required inputs and no
@@ -79,13 +196,14 @@ pub(crate) fn type_check_input_against_expected_type(
 ///     - [type_check_input_against_input_definition]
 pub(crate) fn type_check_inputs_against_input_definitions(
     schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
     path: &[&str],
     input_defs: &InputDefinitions,
     map: &Inputs,
-) -> Result<(), CastleError> {
-    check_for_unspecified_args(schema, path, input_defs, map)?;
-    check_for_missing_args(path, input_defs, map)?;
-    Ok(())
+    diagnostics: &mut Diagnostics,
+) {
+    check_for_unspecified_args(schema, variables, path, input_defs, map, diagnostics);
+    check_for_missing_args(path, input_defs, map, diagnostics);
 }
 
 /// check that there are no unspecified args being used in the inputs
@@ -93,51 +211,55 @@ pub(crate) fn type_check_inputs_against_input_definitions(
 /// in other words, check that each [Input] was defined in the [InputDefinition]
 pub(crate) fn check_for_unspecified_args(
     schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
     path: &[&str],
     input_defs: &InputDefinitions,
     inputs_map: &Inputs,
-) -> Result<(), CastleError> {
+    diagnostics: &mut Diagnostics,
+) {
     for (arg_ident, input_value) in inputs_map.iter() {
+        let arg_path = &[path, &[&**arg_ident]].concat();
         match input_defs.get(arg_ident) {
-            None => Err(CastleError::Validation(
+            None => diagnostics.push(arg_path, CastleError::Validation(
                 format!(
                     "{} was not specififed in input definition",
-                    join_paths(&[path, &[arg_ident]].concat()),
+                    join_paths(arg_path),
                 )
                 .into(),
-            ))?,
+            )),
             Some(input_def) => type_check_input_against_input_definition(
                 schema,
-                &[path, &[&**arg_ident]].concat(),
+                variables,
+                arg_path,
                 input_def,
                 input_value,
-            )?,
+                diagnostics,
+            ),
         }
     }
-
-    return Ok(());
 }
 
 
 /// check that all args defined in the [InputDefinitions] were used in the [Inputs]
-/// and that none were missing
+/// and that none were missing. Args with a default value or an `Option<T>` kind can be omitted.
 pub(crate) fn check_for_missing_args(
     path: &[&str],
     input_defs: &InputDefinitions,
     inputs_map: &Inputs,
-) -> Result<(), CastleError> {
-    for (arg_ident, _) in input_defs.iter() {
+    diagnostics: &mut Diagnostics,
+) {
+    for (arg_ident, input_def) in input_defs.iter() {
+        let arg_path = &[path, &[&**arg_ident]].concat();
         match inputs_map.get(&**arg_ident) {
-            None => Err(CastleError::Validation(
+            None if input_def.default.is_some() || &*input_def.input_kind.ident == "Option" => {}
+            None => diagnostics.push(arg_path, CastleError::Validation(
                 format!(
                     "{} was not specified in input, but is required",
-                    join_paths(&[path, &[arg_ident]].concat()),
+                    join_paths(arg_path),
                 )
                 .into(),
-            ))?,
+            )),
             Some(..) => {}
         }
     }
-
-    return Ok(());
-}
\ No newline at end of file
+}
@@ -1,64 +1,119 @@
 use castle_error::CastleError;
-use castle_query_parser::FieldKind;
-use castle_schema_parser::types::{SchemaDefinition, FieldDefinition, TypeDefinition, Kind};
+use castle_query_parser::{FieldKind, VariableDefinitions};
+use std::collections::HashMap;
+
+use castle_schema_parser::types::{SchemaDefinition, TypeDefinition, Kind};
 use crate::Projection;
-use super::{validate_inputs::{type_check_inputs_against_input_definitions}, join_paths};
+use super::{validate_inputs::{type_check_inputs_against_input_definitions}, join_paths, Diagnostics};
 
 
-pub(crate) fn validate_projection(schema: &SchemaDefinition, projection: &Projection) -> Result<(), CastleError> {
-    let root = schema.types.get("Root".into()).ok_or(CastleError::Validation("Schema is missing Root type".into()))?;
-    validate_each_projection_field(schema, projection, root, &["root"])?;
-    return Ok(())
+pub(crate) fn validate_projection(
+    schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
+    projection: &Projection,
+    diagnostics: &mut Diagnostics,
+) {
+    match schema.types.get("Root") {
+        Some(root) => validate_each_projection_field(schema, variables, projection, root, &["root"], diagnostics),
+        None => diagnostics.push(&["root"], CastleError::Validation("Schema is missing Root type".into())),
+    }
 }
 
 fn validate_each_projection_field(
     schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
     projection: &Projection,
     type_being_validated: &TypeDefinition,
-    path: &[&str]
-) -> Result<(), CastleError> {
+    path: &[&str],
+    diagnostics: &mut Diagnostics,
+) {
     for (name, value) in projection {
-        let field_def = type_being_validated.fields.get(name)
-            .ok_or(CastleError::Validation(format!("{} has no field named: {}", join_paths(path), name).into()))?;
+        let field_path = &[path, &[name]].concat();
+        let field_def = match type_being_validated.fields.get(&value.name) {
+            Some(field_def) => field_def,
+            None => {
+                diagnostics.push_at(field_path, value.span, CastleError::Validation(format!("{} has no field named: {}", join_paths(path), value.name).into()));
+                continue
+            }
+        };
 
-        type_check_inputs_against_input_definitions(schema, &[path, &[name]].concat(), &field_def.input_definitions, &value.inputs)?;
-        validate_field_kind(&value.kind, schema, field_def, &[path, &[name]].concat())?;
+        diagnostics.within(value.span, |diagnostics| {
+            type_check_inputs_against_input_definitions(schema, variables, field_path, &field_def.input_definitions, &value.inputs, diagnostics);
+            validate_field_kind(&value.kind, schema, variables, &field_def.return_kind, field_path, diagnostics);
+        });
     }
-    Ok(())
 }
 
 fn validate_field_kind(
     input_kind: &FieldKind,
     schema: &SchemaDefinition,
-    field_def: &FieldDefinition,
-    path: &[&str]
-) -> Result<(), CastleError> {
+    variables: &VariableDefinitions,
+    return_kind: &Kind,
+    path: &[&str],
+    diagnostics: &mut Diagnostics,
+) {
     match input_kind {
-        FieldKind::Field => match is_scalar(&field_def.return_kind) {
-            true => Ok(()),
-            false => Err(CastleError::Validation(format!("{} is not a scalar type", join_paths(path)).into()))
+        FieldKind::Field => if !is_scalar(return_kind) {
+            diagnostics.push(path, CastleError::Validation(format!("{} is not a scalar type", join_paths(path)).into()))
         },
-        FieldKind::Object(projection) => match schema.types.get(&field_def.return_kind.ident) {
-            Some(type_def) => validate_each_projection_field(schema, projection, type_def, path),
-            None => Err(CastleError::Validation(format!("{} tried to project an fields on type {}", join_paths(path), field_def.return_kind).into()))
+        FieldKind::Object(projection) => match schema.types.get(&without_option(return_kind).ident) {
+            Some(type_def) => validate_each_projection_field(schema, variables, projection, type_def, path, diagnostics),
+            None => diagnostics.push(path, CastleError::Validation(format!("{} tried to project an fields on type {}", join_paths(path), return_kind).into()))
         },
-        FieldKind::List(projection) => validate_list(schema, field_def, projection, path),
+        FieldKind::List(projection) => validate_list(schema, variables, return_kind, projection, path, diagnostics),
+        FieldKind::Match(arms) => validate_match(schema, variables, return_kind, arms, path, diagnostics),
     }
 }
 
-fn validate_list(schema: &SchemaDefinition, field_def: &FieldDefinition, projection: &Projection, path: &[&str]) -> Result<(), CastleError> {
-    match (&*field_def.return_kind.ident, schema.types.get(&field_def.return_kind.generics[0].ident)) {
-        ("Vec", Some(type_def)) if !is_scalar(&field_def.return_kind) => {
-            validate_each_projection_field(schema, projection, type_def, path)
+fn validate_list(schema: &SchemaDefinition, variables: &VariableDefinitions, return_kind: &Kind, projection: &Projection, path: &[&str], diagnostics: &mut Diagnostics) {
+    match (&*return_kind.ident, schema.types.get(&without_option(&return_kind.generics[0]).ident)) {
+        ("Vec", Some(type_def)) if !is_scalar(return_kind) => {
+            validate_each_projection_field(schema, variables, projection, type_def, path, diagnostics)
         },
-        _ => Err(CastleError::Validation(format!("{} tried to project an fields on type {}", join_paths(path), field_def.return_kind).into()))?,
+        _ => diagnostics.push(path, CastleError::Validation(format!("{} tried to project an fields on type {}", join_paths(path), return_kind).into())),
+    }
+}
+
+/// Validates each arm of a match projection
+/// - the field must return an enum (or `Option` of an enum)
+/// - each arm must name a variant of that enum
+/// - map variants can project their own fields, unit and tuple variants can't project anything
+fn validate_match(schema: &SchemaDefinition, variables: &VariableDefinitions, return_kind: &Kind, arms: &HashMap<Box<str>, Projection>, path: &[&str], diagnostics: &mut Diagnostics) {
+    let enum_def = match schema.enums.get(&without_option(return_kind).ident) {
+        Some(enum_def) => enum_def,
+        None => return diagnostics.push(path, CastleError::Validation(format!("{} tried to match on type {} which is not an enum", join_paths(path), return_kind).into())),
+    };
+
+    for (variant_name, projection) in arms {
+        let variant_path = &[path, &[variant_name]].concat();
+        let variant_def = match enum_def.variants.get(variant_name) {
+            Some(variant_def) => variant_def,
+            None => {
+                diagnostics.push(variant_path, CastleError::Validation(format!("{} has no variant named: {}", join_paths(path), variant_name).into()));
+                continue
+            }
+        };
+
+        match variant_def.map_type_definition(&enum_def.ident) {
+            Some(type_def) => validate_each_projection_field(schema, variables, projection, &type_def, variant_path, diagnostics),
+            None if projection.is_empty() => {},
+            None => diagnostics.push(variant_path, CastleError::Validation(format!("{} has no fields to project", join_paths(variant_path)).into())),
+        }
+    }
+}
+
+/// The `T` of an `Option<T>`, otherwise the kind itself
+fn without_option(kind: &Kind) -> &Kind {
+    match &*kind.ident {
+        "Option" => &kind.generics[0],
+        _ => kind,
     }
 }
 
 fn is_scalar(kind: &Kind) -> bool {
     match &*kind.ident {
-        "String" | "number" | "bool" | "void" => true,
-        "Vec" if is_scalar(&kind.generics[0]) => true,
+        "String" | "number" | "bool" | "void" | "Uuid" => true,
+        "Vec" | "Option" if is_scalar(&kind.generics[0]) => true,
         _ => false,
     }
-}
\ No newline at end of file
+}
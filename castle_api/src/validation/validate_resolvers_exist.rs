@@ -1,24 +1,94 @@
-use std::collections::HashMap;
+use std::collections::HashSet;
 use castle_error::CastleError;
-use castle_schema_parser::types::SchemaDefinition;
+use castle_schema_parser::types::{SchemaDefinition, Kind, VariantKindDefinition};
 
-use crate::{Resolver};
+use crate::castle::TypeResolvers;
 
+use super::Diagnostics;
+
+/// Checks that every field which can't be read off its parent value has a resolver.
+/// - every field of `type Root` needs a resolver
+/// - fields with inputs on any type reachable from `Root` need a resolver
+/// - every registered resolver must belong to a field defined in the schema
 pub(crate) fn validate_resolvers_exist<Ctx, E>(
     parsed_schema: &SchemaDefinition,
-    field_resolvers: &HashMap<Box<str>, Box<dyn Resolver<Ctx, E>>>,
-) -> Result<(), CastleError> {
-    match parsed_schema.types.get("Root") {
-        Some(query_type) => {
-            for field_name in query_type.fields.keys() {
-                if !field_resolvers.contains_key(field_name) {
-                    Err(CastleError::MissingResolver(
-                        format!("Missing resolver for Root.{}", field_name).into(),
-                    ))?;
+    field_resolvers: &TypeResolvers<Ctx, E>,
+    diagnostics: &mut Diagnostics,
+) {
+    if !parsed_schema.types.contains_key("Root") {
+        diagnostics.push(&["Root"], CastleError::MissingResolver("Missing `type Root` root type".into()))
+    }
+
+    for (type_name, resolvers) in field_resolvers.iter() {
+        for field_name in resolvers.keys() {
+            match parsed_schema.types.get(type_name) {
+                Some(type_def) if type_def.fields.contains_key(field_name) => {}
+                _ => diagnostics.push(&[type_name, field_name], CastleError::Validation(
+                    format!("Resolver provided for {}.{}, but it is not defined in the schema", type_name, field_name).into(),
+                )),
+            }
+        }
+    }
+
+    let mut reachable = HashSet::new();
+    collect_reachable_types(parsed_schema, "Root", &mut reachable);
+
+    // sort the types so the errors are deterministic
+    let mut reachable: Vec<_> = reachable.into_iter().collect();
+    reachable.sort();
+
+    for type_name in reachable {
+        let type_def = match parsed_schema.types.get(type_name) {
+            Some(type_def) => type_def,
+            None => continue, // enums don't have field resolvers
+        };
+        let mut field_names: Vec<_> = type_def.fields.keys().collect();
+        field_names.sort();
+        for field_name in field_names {
+            let field_def = &type_def.fields[field_name];
+            let needs_resolver = type_name == "Root" || !field_def.input_definitions.is_empty();
+            let has_resolver = field_resolvers
+                .get(type_name)
+                .is_some_and(|resolvers| resolvers.contains_key(field_name));
+
+            if needs_resolver && !has_resolver {
+                diagnostics.push_at(&[type_name, field_name], field_def.span, CastleError::MissingResolver(
+                    format!("Missing resolver for {}.{}", type_name, field_name).into(),
+                ));
+            }
+        }
+    }
+}
+
+/// Collects the names of every type and enum that can be reached from `name` through field
+/// return kinds, including types used in generics and enum variants
+fn collect_reachable_types<'a>(schema: &'a SchemaDefinition, name: &str, reachable: &mut HashSet<&'a str>) {
+    if let Some((name, type_def)) = schema.types.get_key_value(name) {
+        if reachable.insert(&**name) {
+            for field in type_def.fields.values() {
+                collect_reachable_kind(schema, &field.return_kind, reachable);
+            }
+        }
+    } else if let Some((name, enum_def)) = schema.enums.get_key_value(name) {
+        if reachable.insert(&**name) {
+            for variant in enum_def.variants.values() {
+                match &variant.kind {
+                    VariantKindDefinition::Unit => {},
+                    VariantKindDefinition::Tuple(kinds) => for kind in kinds {
+                        collect_reachable_kind(schema, kind, reachable);
+                    },
+                    VariantKindDefinition::Map(map) => for kind in map.values() {
+                        collect_reachable_kind(schema, kind, reachable);
+                    },
                 }
             }
-            Ok(())
-        },
-        None => Err(CastleError::MissingResolver("Missing `type Root` root type".into()))?,
+        }
+    }
+}
+
+fn collect_reachable_kind<'a>(schema: &'a SchemaDefinition, kind: &Kind, reachable: &mut HashSet<&'a str>) {
+    collect_reachable_types(schema, &kind.ident, reachable);
+    for generic in kind.generics.iter() {
+        collect_reachable_kind(schema, generic, reachable);
     }
 }
@@ -1,4 +1,3 @@
-use castle_error::CastleError;
 use castle_schema_parser::types::{SchemaDefinition, Kind};
 use validate_types::validate_types;
 use validate_enums::validate_enums;
@@ -9,6 +8,8 @@ pub(crate) mod validate_directives;
 mod validate_enums;
 mod validate_directive_definitions;
 
+use super::Diagnostics;
+
 /// It needs to check every type, enum etc that’s used is defined in the schema.
 ///
 /// Currently Testing:
@@ -21,11 +22,10 @@ mod validate_directive_definitions;
 ///    - All type fields have valid types defined in the schema or `built-in` types
 ///    - All type directives are defined in the schema and match directive structure
 /// - A root query type has been defined in the schema
-pub(crate) fn validate_schema(schema: &SchemaDefinition) -> Result<(), CastleError>{
-    validate_directive_definitions(schema)?;
-    validate_types(schema)?;
-    validate_enums(schema)?;
-    return Ok(())
+pub(crate) fn validate_schema(schema: &SchemaDefinition, diagnostics: &mut Diagnostics) {
+    validate_directive_definitions(schema, diagnostics);
+    validate_types(schema, diagnostics);
+    validate_enums(schema, diagnostics);
 }
 
 /// Check if the provided type [Kind] exists in the schema, or is a built-in type
@@ -55,7 +55,7 @@ pub(crate) fn return_type_exists(schema: &SchemaDefinition, kind: &Kind) -> Resu
     })
 }
 
-/// Check if the provided [Kind] exists in the [SchemaDefinition].input_types, or is a built-in type
+/// Check if the provided [Kind] exists in the [SchemaDefinition].input_types or enums, or is a built-in type
 /// If the type is not found, it will return a reason why it failed
 /// If the type is found, it will return void
 pub(crate) fn input_type_exists(schema: &SchemaDefinition, kind: &Kind) -> Result<(), String> {
@@ -74,7 +74,8 @@ pub(crate) fn input_type_exists(schema: &SchemaDefinition, kind: &Kind) -> Resul
         },
         name if kind.generics.len() == 0 => match name {
             name if schema.input_types.contains_key(name) => (),
-            _ => Err(format!("Type {} not defined in schema input_types", name))?,
+            name if schema.enums.contains_key(name) => (),
+            _ => Err(format!("Type {} not defined in schema input_types or enums", name))?,
         }
         _ => Err(format!("Type {} not defined in schema, maybe there is an incorrect number of generics", kind.ident))?,
     })
@@ -3,58 +3,62 @@ use std::collections::HashMap;
 use castle_error::CastleError;
 use castle_schema_parser::types::{EnumDefinition, SchemaDefinition, VariantDefinition, DirectiveLocation, VariantKindDefinition, Kind};
 
+use crate::validation::Diagnostics;
+
 use super::{validate_directives::validate_directive, return_type_exists};
 
 
-pub(super) fn validate_enums(schema: &SchemaDefinition) -> Result<(), CastleError> {
+pub(super) fn validate_enums(schema: &SchemaDefinition, diagnostics: &mut Diagnostics) {
     for enum_def in schema.enums.values() {
-        validate_enum(schema, enum_def)?;
+        validate_enum(schema, enum_def, diagnostics);
     }
-    return Ok(());
 }
 
-fn validate_enum(schema: &SchemaDefinition, enum_def: &EnumDefinition) -> Result<(), CastleError> {
+fn validate_enum(schema: &SchemaDefinition, enum_def: &EnumDefinition, diagnostics: &mut Diagnostics) {
     for directive in enum_def.directives.iter() {
-        validate_directive(schema, &[&enum_def.ident], directive, DirectiveLocation::EnumDefinition)?;
+        diagnostics.within(directive.span, |diagnostics| {
+            validate_directive(schema, &[&enum_def.ident], directive, DirectiveLocation::EnumDefinition, diagnostics)
+        });
     }
 
     for variant in enum_def.variants.values() {
-        validate_variant(schema, &enum_def.ident, variant)?;
+        validate_variant(schema, &enum_def.ident, variant, diagnostics);
     }
-    return Ok(());
 }
 
-fn validate_variant(schema: &SchemaDefinition, enum_name: &str, variant: &VariantDefinition) -> Result<(), CastleError> {
+fn validate_variant(schema: &SchemaDefinition, enum_name: &str, variant: &VariantDefinition, diagnostics: &mut Diagnostics) {
     for directive in variant.directives.iter() {
-        validate_directive(schema, &[&enum_name, &variant.ident], directive, DirectiveLocation::VariantDefinition)?;
+        diagnostics.within(directive.span, |diagnostics| {
+            validate_directive(schema, &[enum_name, &variant.ident], directive, DirectiveLocation::VariantDefinition, diagnostics)
+        });
+    }
+
+    // `None` and `null` are read as literals where a value is expected, so the variant couldn't be passed as an input
+    if matches!(&*variant.ident, "None" | "null") {
+        diagnostics.push_at(&[enum_name, &variant.ident], variant.span, CastleError::Validation(
+            format!("{}.{} can't be used as a variant name, it is read as a missing value in inputs", enum_name, variant.ident).into(),
+        ));
     }
 
     match &variant.kind {
         VariantKindDefinition::Unit => {},
-        VariantKindDefinition::Tuple(tup) => validate_tuple(schema, &enum_name, &variant.ident, &tup)?,
-        VariantKindDefinition::Map(map) => validate_map(schema, &enum_name, &variant.ident, &map)?,
+        VariantKindDefinition::Tuple(tup) => validate_tuple(schema, enum_name, &variant.ident, tup, diagnostics),
+        VariantKindDefinition::Map(map) => validate_map(schema, enum_name, &variant.ident, map, diagnostics),
     }
-
-
-    return Ok(());
 }
 
-fn validate_tuple(schema: &SchemaDefinition, enum_name: &str, variant_name: &str, tup: &Vec<Kind>) -> Result<(), CastleError> {
+fn validate_tuple(schema: &SchemaDefinition, enum_name: &str, variant_name: &str, tup: &[Kind], diagnostics: &mut Diagnostics) {
     for kind in tup.iter() {
-        match return_type_exists(schema, kind) {
-            Ok(()) => {}
-            Err(e) => Err(CastleError::Validation(format!("{}.{} has invalid kind: {}", enum_name, variant_name, e).into()))?,
+        if let Err(e) = return_type_exists(schema, kind) {
+            diagnostics.push_at(&[enum_name, variant_name], kind.span, CastleError::Validation(format!("{}.{} has invalid kind: {}", enum_name, variant_name, e).into()));
         }
     }
-    return Ok(())
 }
 
-fn validate_map(schema: &SchemaDefinition, enum_name: &str, variant_name: &str, map: &HashMap<Box<str>, Kind>) -> Result<(), CastleError> {
-    for (_, value) in map.iter() {
-        match return_type_exists(schema, value) {
-            Ok(()) => {}
-            Err(e) => Err(CastleError::Validation(format!("{}.{} has invalid kind: {}", enum_name, variant_name, e).into()))?,
-        };
+fn validate_map(schema: &SchemaDefinition, enum_name: &str, variant_name: &str, map: &HashMap<Box<str>, Kind>, diagnostics: &mut Diagnostics) {
+    for (field_name, kind) in map.iter() {
+        if let Err(e) = return_type_exists(schema, kind) {
+            diagnostics.push_at(&[enum_name, variant_name, field_name], kind.span, CastleError::Validation(format!("{}.{} has invalid kind: {}", enum_name, variant_name, e).into()));
+        }
     }
-    return Ok(())
-}
\ No newline at end of file
+}
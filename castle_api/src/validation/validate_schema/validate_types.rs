@@ -1,50 +1,65 @@
 use castle_error::CastleError;
-use castle_schema_parser::types::{SchemaDefinition, TypeDefinition, FieldDefinition, DirectiveLocation};
+use castle_schema_parser::types::{SchemaDefinition, TypeDefinition, FieldDefinition, DirectiveLocation, InputTypeDefinition};
+
+use crate::validation::{validate_inputs::type_check_input_default, Diagnostics};
 
 use super::{validate_directives::validate_directive, return_type_exists, input_type_exists};
 
-pub(super) fn validate_types(schema: &SchemaDefinition) -> Result<(), CastleError> {
+pub(super) fn validate_types(schema: &SchemaDefinition, diagnostics: &mut Diagnostics) {
     for type_def in schema.types.values() {
-        validate_type(schema, type_def)?;
+        validate_type(schema, type_def, diagnostics);
+    }
+    for input_type_def in schema.input_types.values() {
+        validate_input_type(schema, input_type_def, diagnostics);
     }
-    return Ok(());
 }
 
 
 /// Validates a type definition.
 /// - validates each field
 /// - validates each directive applied on the type
-fn validate_type(schema: &SchemaDefinition, type_def: &TypeDefinition) -> Result<(), CastleError> {
+fn validate_type(schema: &SchemaDefinition, type_def: &TypeDefinition, diagnostics: &mut Diagnostics) {
     for directive in type_def.directives.iter() {
-        validate_directive(schema, &[&type_def.ident], directive, DirectiveLocation::EnumDefinition)?;
+        diagnostics.within(directive.span, |diagnostics| {
+            validate_directive(schema, &[&type_def.ident], directive, DirectiveLocation::EnumDefinition, diagnostics)
+        });
     }
 
     for field in type_def.fields.values() {
-        validate_field(schema, &type_def.ident, field)?;
+        validate_field(schema, &type_def.ident, field, diagnostics);
+    }
+}
+
+/// Validates an input type definition.
+/// - validates the default value of each input
+fn validate_input_type(schema: &SchemaDefinition, input_type_def: &InputTypeDefinition, diagnostics: &mut Diagnostics) {
+    for input_def in input_type_def.input_definitions.values() {
+        diagnostics.within(input_def.span, |diagnostics| {
+            type_check_input_default(schema, &[&input_type_def.ident], input_def, diagnostics)
+        });
     }
-    return Ok(());
 }
 
 // Validates a field definition.
 // - validates each directive applied on the field
-// - validates each input on the field
+// - validates each input on the field (and its default value)
 // - validates field return kind
-fn validate_field(schema: &SchemaDefinition, type_name: &str, field: &FieldDefinition) -> Result<(), CastleError> {
-    match return_type_exists(schema, &field.return_kind) {
-        Ok(()) => {}
-        Err(e) => Err(CastleError::Validation(format!("{}.{} has invalid return type: {}", type_name, field.ident, e).into()))?,
-    };
+fn validate_field(schema: &SchemaDefinition, type_name: &str, field: &FieldDefinition, diagnostics: &mut Diagnostics) {
+    let path: &[&str] = &[type_name, &field.ident];
+    if let Err(e) = return_type_exists(schema, &field.return_kind) {
+        diagnostics.push_at(path, field.return_kind.span, CastleError::Validation(format!("{}.{} has invalid return type: {}", type_name, field.ident, e).into()));
+    }
 
     for input_def in field.input_definitions.values() {
-        match input_type_exists(schema, &input_def.input_kind) {
-            Ok(()) => {}
-            Err(e) => Err(CastleError::Validation(format!("{}.{} input {} with type {} does not exist in schema: {}", type_name, field.ident, input_def.ident, input_def.input_kind, e).into()))?,
+        if let Err(e) = input_type_exists(schema, &input_def.input_kind) {
+            diagnostics.push_at(&[type_name, &field.ident, &input_def.ident], input_def.input_kind.span, CastleError::Validation(format!("{}.{} input {} with type {} does not exist in schema: {}", type_name, field.ident, input_def.ident, input_def.input_kind, e).into()));
         }
+        diagnostics.within(input_def.span, |diagnostics| type_check_input_default(schema, path, input_def, diagnostics));
     }
 
     for directive in field.directives.iter() {
-        validate_directive(schema, &[&type_name, &field.ident], directive, DirectiveLocation::FieldDefinition)?;
+        diagnostics.within(directive.span, |diagnostics| {
+            validate_directive(schema, path, directive, DirectiveLocation::FieldDefinition, diagnostics)
+        });
     }
-
-    return Ok(());
-}
\ No newline at end of file
+}
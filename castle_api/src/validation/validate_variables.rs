@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use castle_error::CastleError;
+use castle_query_parser::{Inputs, VariableDefinitions};
+use castle_schema_parser::types::SchemaDefinition;
+
+use super::{validate_inputs::type_check_input_against_expected_type, validate_schema::input_type_exists, Diagnostics};
+
+/// Validates the variables declared by a message
+/// - the kind of each variable must be an input type, or a built-in type
+/// - default values must match the kind of the variable
+pub(crate) fn validate_variable_definitions(
+    schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
+    diagnostics: &mut Diagnostics,
+) {
+    for (ident, variable) in variables {
+        let path: &[&str] = &[&format!("${}", ident)];
+        if let Err(reason) = input_type_exists(schema, &variable.kind) {
+            diagnostics.push_at(path, variable.kind.span, CastleError::Validation(format!("{}: {}", path[0], reason).into()))
+        }
+        if let Some(default) = &variable.default {
+            diagnostics.within(variable.span, |diagnostics| {
+                type_check_input_against_expected_type(schema, &HashMap::new(), path, &variable.kind, default, diagnostics)
+            });
+        }
+    }
+}
+
+/// Validates the values provided for the variables of a message
+/// - every provided value must belong to a declared variable
+/// - every provided value must match the kind of its variable
+/// - every variable without a default (or `Option<T>` kind) must be provided
+pub(crate) fn validate_variable_values(
+    schema: &SchemaDefinition,
+    variables: &VariableDefinitions,
+    values: &Inputs,
+    diagnostics: &mut Diagnostics,
+) {
+    for (ident, value) in values {
+        let path: &[&str] = &[&format!("${}", ident)];
+        match variables.get(ident) {
+            Some(variable) => diagnostics.within(variable.span, |diagnostics| {
+                type_check_input_against_expected_type(schema, &HashMap::new(), path, &variable.kind, value, diagnostics)
+            }),
+            None => diagnostics.push(path, CastleError::Validation(format!("{} was provided but is not declared by the message", path[0]).into())),
+        }
+    }
+    for (ident, variable) in variables {
+        if variable.default.is_none() && &*variable.kind.ident != "Option" && !values.contains_key(ident) {
+            diagnostics.push_at(&[&format!("${}", ident)], variable.span, CastleError::Validation(format!("${} was not provided, but is required", ident).into()))
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use castle_api::{castle::CastleBuilder, types::result::CastleResult, Field, FromInput, FromInputs, Input, Inputs, IntoValue, Primitive, Value};
+
+#[derive(IntoValue)]
+struct User {
+    name: String,
+    age: u32,
+    nickname: Option<String>,
+    friends: Vec<Friend>,
+    avatar: Avatar,
+}
+
+#[derive(IntoValue)]
+struct Friend {
+    name: String,
+    age: u32,
+}
+
+/// Panics if converted, so it can only be in fields that aren't projected
+struct Avatar;
+
+impl<Ctx, E> IntoValue<Ctx, E> for Avatar {
+    fn into_value(self, _field: Option<&Field>) -> Value<Ctx, E> {
+        panic!("avatar was converted without being projected")
+    }
+}
+
+#[derive(IntoValue)]
+#[allow(clippy::enum_variant_names)]
+enum Icon {
+    SVGIcon {
+        url: String,
+        size: u32,
+    },
+    Emoji(String),
+    NoIcon,
+}
+
+fn alice() -> User {
+    User {
+        name: "Alice".into(),
+        age: 30,
+        nickname: None,
+        friends: vec![Friend { name: "Bob".into(), age: 41 }],
+        avatar: Avatar,
+    }
+}
+
+#[tokio::test]
+async fn derived_structs_only_convert_projected_fields() {
+    let schema = "
+    type Root {
+        me: User
+    }
+
+    type User {
+        name: String
+        age: number
+        nickname: Option<String>
+        friends: Vec<Friend>
+        avatar: String
+    }
+
+    type Friend {
+        name: String
+        age: number
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+                nickname
+                friends [
+                    name
+                ]
+            }
+        }
+    ";
+
+    async fn me(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        let value: Value<(), ()> = alice().into_value(Some(field));
+        let expected: Value<(), ()> = HashMap::from([
+            ("name", Value::from("Alice")),
+            ("nickname", Value::None),
+            ("friends", vec![HashMap::from([("name", "Bob")])].into()),
+        ]).into();
+        assert_eq!(value, expected);
+        Ok(value)
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("me", me)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.errors, vec![]);
+    assert_eq!(result.data["me"], HashMap::from([
+        ("name", Value::from("Alice")),
+        ("nickname", Value::None),
+        ("friends", vec![HashMap::from([("name", "Bob")])].into()),
+    ]).into());
+}
+
+#[test]
+fn derived_structs_convert_every_field_without_a_projection() {
+    let friend: Value<(), ()> = Friend { name: "Bob".into(), age: 41 }.into_value(None);
+    assert_eq!(friend, HashMap::from([("name", Value::from("Bob")), ("age", Value::from(41u32))]).into());
+}
+
+#[tokio::test]
+async fn derived_enums_become_variants() {
+    let schema = "
+    type Root {
+        icon: Icon
+        emoji: Icon
+        no_icon: Icon
+    }
+
+    enum Icon {
+        SVGIcon {
+            url: String,
+            size: number,
+        },
+        Emoji(String),
+        NoIcon,
+    }
+    ";
+    let query = "
+        message {
+            icon match {
+                SVGIcon { url }
+            }
+            emoji match {
+                Emoji
+            }
+            no_icon match {
+                NoIcon
+            }
+        }
+    ";
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("icon", |field: &Field, _: &()| {
+            let value = Icon::SVGIcon { url: "https://example.com/icon.svg".into(), size: 48 }.into_value(Some(field));
+            async { Ok(value) }
+        })
+        .add_resolver("emoji", |field: &Field, _: &()| {
+            let value = Icon::Emoji("🏰".into()).into_value(Some(field));
+            async { Ok(value) }
+        })
+        .add_resolver("no_icon", |field: &Field, _: &()| {
+            let value = Icon::NoIcon.into_value(Some(field));
+            async { Ok(value) }
+        })
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.errors, vec![]);
+    assert_eq!(result.data["icon"], Value::Variant("SVGIcon".into(), Box::new(
+        HashMap::from([("url", "https://example.com/icon.svg")]).into()
+    )));
+    assert_eq!(result.data["emoji"], Value::Variant("Emoji".into(), Box::new(vec!["🏰"].into())));
+    assert_eq!(result.data["no_icon"], Value::Variant("NoIcon".into(), Box::new(Value::Void)));
+}
+
+#[derive(FromInputs, Debug, PartialEq)]
+struct AvatarInputs {
+    id: uuid::Uuid,
+    size: u32,
+    crop: Option<Crop>,
+    formats: Vec<Format>,
+    fallback: Option<String>,
+}
+
+#[derive(FromInputs, Debug, PartialEq)]
+struct Crop {
+    x: f64,
+    y: f64,
+}
+
+#[derive(FromInputs, Debug, PartialEq)]
+enum Format {
+    Png,
+    Jpeg(u8),
+    Custom {
+        mime: String,
+    },
+}
+
+#[tokio::test]
+async fn derived_inputs_are_read_from_the_field() {
+    let schema = "
+    type Root {
+        avatar(id: Uuid size: number crop: Option<Crop> formats: Vec<Format> fallback: Option<String>): String
+    }
+
+    input Crop {
+        x: number
+        y: number
+    }
+
+    enum Format {
+        Png,
+        Jpeg(number),
+        Custom {
+            mime: String,
+        },
+    }
+    ";
+    let query = r#"
+        message {
+            avatar(
+                id: "67e55044-10b1-426f-9247-bb680e5fe0c8",
+                size: 48,
+                crop: { x: 1.5, y: 2 },
+                formats: [Png, Jpeg(80), Custom { mime: "image/webp" }],
+            )
+        }
+    "#;
+
+    async fn avatar(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        let inputs = AvatarInputs::from_inputs(&field.inputs).unwrap();
+        assert_eq!(inputs, AvatarInputs {
+            id: uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap(),
+            size: 48,
+            crop: Some(Crop { x: 1.5, y: 2.0 }),
+            formats: vec![Format::Png, Format::Jpeg(80), Format::Custom { mime: "image/webp".into() }],
+            fallback: None,
+        });
+        Ok(Value::from("ok"))
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.errors, vec![]);
+    assert_eq!(result.data["avatar"], Value::from("ok"));
+}
+
+#[test]
+fn derived_inputs_report_which_input_is_wrong() {
+    let inputs: Inputs = [
+        ("x".into(), Input::Primitive(Primitive::Number(1u64.into()))),
+        ("y".into(), Input::Primitive(Primitive::String("two".into()))),
+    ].into();
+    let err = Crop::from_inputs(&inputs).unwrap_err();
+    assert_eq!(err.to_string(), "Error: y: expected a number but got \"two\"");
+
+    let err = Crop::from_inputs(&[("x".into(), Input::Primitive(Primitive::Number(1u64.into())))].into()).unwrap_err();
+    assert_eq!(err.to_string(), "Error: y: expected a number but the input is missing");
+
+    let err = Format::from_input(&Input::Primitive(Primitive::Boolean(true))).unwrap_err();
+    assert_eq!(err.to_string(), "Error: expected a variant of Format but got true");
+}
@@ -1,5 +1,8 @@
-use castle_api::{castle::CastleBuilder, types::result::CastleResult, Directive, Resolver, Value, Inputs, Next};
+use std::{collections::HashMap, sync::atomic::{AtomicUsize, Ordering}};
+
+use castle_api::{castle::{Castle, CastleBuilder, ReturnValueCheck}, types::result::{CastleResult, PathSegment}, Directive, Resolver, Value, Input, Inputs, Next, Primitive};
 use castle_query_parser::Field;
+use tokio::sync::Barrier;
 
 async fn run_schema_with_query<Ctx: Send + Sync + 'static, E: Send + Sync + 'static>(
     schema: &str,
@@ -67,6 +70,7 @@ async fn resolver_can_return_string() {
     let expected = CastleResult {
         data: [("bar".into(), "foo".into())].into(),
         errors: vec![],
+        castle_errors: vec![],
     };
 
     assert_eq!(result, expected)
@@ -94,6 +98,7 @@ async fn resolver_can_return_number() {
     let expected = CastleResult {
         data: [("bar".into(), 32.into())].into(),
         errors: vec![],
+        castle_errors: vec![],
     };
     assert_eq!(result, expected)
 }
@@ -144,6 +149,7 @@ async fn testing_void() {
     let expected = CastleResult {
         data: [].into(),
         errors: vec![],
+        castle_errors: vec![],
     };
     assert_eq!(result, expected)
 }
@@ -187,10 +193,785 @@ async fn test_directives() {
     let expected = CastleResult {
         data: [("bar".into(), "foo".into())].into(),
         errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected)
+}
+
+#[tokio::test]
+async fn nested_object_projection_only_returns_projected_fields() {
+    let schema = "
+    type Root {
+        me: User
+    }
+
+    type User {
+        first_name: String
+        last_name: String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                first_name
+            }
+        }
+    ";
+
+    let result: CastleResult<(), ()> = run_schema_with_query(
+        schema,
+        query,
+        vec![("me", |_: &Field, _: &()| async {
+            Ok([("first_name", "Albert"), ("last_name", "Marashi")].into_iter().collect::<HashMap<_, _>>().into())
+        })],
+        &(),
+    )
+    .await;
+    let expected = CastleResult {
+        data: [("me".into(), [("first_name", "Albert")].into_iter().collect::<HashMap<_, _>>().into())].into(),
+        errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected)
+}
+
+#[tokio::test]
+async fn list_projection_is_applied_to_each_item() {
+    let schema = "
+    type Root {
+        users: Vec<User>
+    }
+
+    type User {
+        first_name: String
+        last_name: String
+    }
+    ";
+    let query = "
+        message {
+            users [
+                last_name
+            ]
+        }
+    ";
+
+    let result: CastleResult<(), ()> = run_schema_with_query(
+        schema,
+        query,
+        vec![("users", |_: &Field, _: &()| async {
+            Ok(vec![
+                [("first_name", "Albert"), ("last_name", "Marashi")].into_iter().collect::<HashMap<_, _>>(),
+                [("first_name", "Gerard"), ("last_name", "Smith")].into_iter().collect::<HashMap<_, _>>(),
+            ].into())
+        })],
+        &(),
+    )
+    .await;
+    let expected = CastleResult {
+        data: [("users".into(), vec![
+            [("last_name", "Marashi")].into_iter().collect::<HashMap<_, _>>(),
+            [("last_name", "Smith")].into_iter().collect::<HashMap<_, _>>(),
+        ].into())].into(),
+        errors: vec![],
+        castle_errors: vec![],
     };
     assert_eq!(result, expected)
 }
 
+#[tokio::test]
+async fn nested_field_can_be_resolved_by_a_returned_resolver() {
+    let schema = "
+    type Root {
+        me: User
+    }
+
+    type User {
+        first_name: String
+        full_name: String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                first_name
+                full_name
+            }
+        }
+    ";
+
+    async fn full_name(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok("Albert Marashi".into())
+    }
+
+    let result: CastleResult<(), ()> = run_schema_with_query(
+        schema,
+        query,
+        vec![("me", |_: &Field, _: &()| async {
+            let resolver: Box<dyn Resolver<(), ()>> = Box::new(full_name);
+            Ok(Value::Object([
+                ("first_name".into(), "Albert".into()),
+                ("full_name".into(), resolver.into()),
+            ].into()))
+        })],
+        &(),
+    )
+    .await;
+    let expected = CastleResult {
+        data: [("me".into(), [
+            ("first_name", "Albert"),
+            ("full_name", "Albert Marashi"),
+        ].into_iter().collect::<HashMap<_, _>>().into())].into(),
+        errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected)
+}
+
+#[tokio::test]
+async fn type_resolvers_are_used_for_nested_fields() {
+    let schema = "
+    type Root {
+        me: User
+        org: Organization
+    }
+
+    type User {
+        name: String
+        avatar(size: number): String
+    }
+
+    type Organization {
+        name: String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+                avatar(size: 48)
+            }
+            org {
+                name
+            }
+        }
+    ";
+
+    async fn avatar(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(format!("https://example.com/avatar?size={}", field.inputs["size"]).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("me", |_: &Field, _: &()| async {
+            Ok([("name", "Albert")].into_iter().collect::<HashMap<_, _>>().into())
+        })
+        .add_resolver("org", |_: &Field, _: &()| async {
+            Ok(HashMap::<&str, &str>::new().into())
+        })
+        .add_resolver("User.avatar", avatar)
+        .add_type_resolver("Organization", "name", |_: &Field, _: &()| async {
+            Ok("Framework Tools".into())
+        })
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let expected = CastleResult {
+        data: [
+            ("me".into(), [
+                ("name", "Albert"),
+                ("avatar", "https://example.com/avatar?size=48"),
+            ].into_iter().collect::<HashMap<_, _>>().into()),
+            ("org".into(), [
+                ("name", "Framework Tools"),
+            ].into_iter().collect::<HashMap<_, _>>().into()),
+        ].into(),
+        errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected)
+}
+
+#[tokio::test]
+async fn sibling_fields_are_resolved_concurrently() {
+    let schema = "
+    type Root {
+        me: String
+        notifications: String
+    }
+    ";
+    let query = "
+        message {
+            me
+            notifications
+        }
+    ";
+
+    // each resolver waits for the other, so this only completes if they run at the same time
+    async fn wait_for_sibling(_: &Field, barrier: &Barrier) -> Result<Value<Barrier, ()>, ()> {
+        barrier.wait().await;
+        Ok("done".into())
+    }
+
+    let result: CastleResult<Barrier, ()> = CastleBuilder::new(schema)
+        .add_resolver("me", wait_for_sibling)
+        .add_resolver("notifications", wait_for_sibling)
+        .build()
+        .unwrap()
+        .run_message(query, &Barrier::new(2))
+        .await
+        .unwrap();
+
+    let expected = [("me".into(), "done".into()), ("notifications".into(), "done".into())].into();
+    assert_eq!(result.data, expected);
+    assert!(result.errors.is_empty());
+}
+
+#[derive(Default)]
+struct ConcurrencyCounter {
+    running: AtomicUsize,
+    max_running: AtomicUsize,
+}
+
+async fn count_concurrency(_: &Field, counter: &ConcurrencyCounter) -> Result<Value<ConcurrencyCounter, ()>, ()> {
+    let running = counter.running.fetch_add(1, Ordering::SeqCst) + 1;
+    counter.max_running.fetch_max(running, Ordering::SeqCst);
+    tokio::task::yield_now().await;
+    counter.running.fetch_sub(1, Ordering::SeqCst);
+    Ok(true.into())
+}
+
+#[tokio::test]
+async fn max_concurrency_limits_running_resolvers() {
+    let schema = "
+    type Root {
+        a: bool
+        b: bool
+        c: bool
+    }
+    ";
+    let query = "
+        message {
+            a
+            b
+            c
+        }
+    ";
+
+    let mut builder = CastleBuilder::new(schema);
+    builder
+        .add_resolver("a", count_concurrency)
+        .add_resolver("b", count_concurrency)
+        .add_resolver("c", count_concurrency);
+
+    let unlimited = builder.build().unwrap();
+    let counter = ConcurrencyCounter::default();
+    unlimited.run_message(query, &counter).await.unwrap();
+    assert_eq!(counter.max_running.load(Ordering::SeqCst), 3);
+
+    let mut builder = CastleBuilder::new(schema);
+    builder
+        .add_resolver("a", count_concurrency)
+        .add_resolver("b", count_concurrency)
+        .add_resolver("c", count_concurrency)
+        .set_max_concurrency(1);
+
+    let limited = builder.build().unwrap();
+    let counter = ConcurrencyCounter::default();
+    let result = limited.run_message(query, &counter).await.unwrap();
+    assert_eq!(counter.max_running.load(Ordering::SeqCst), 1);
+    assert_eq!(result.data.len(), 3);
+}
+
+#[tokio::test]
+async fn errors_are_collected_in_field_order() {
+    let schema = "
+    type Root {
+        a: String
+        b: String
+        c: String
+    }
+    ";
+    let query = "
+        message {
+            c
+            a
+            b
+        }
+    ";
+
+    async fn fails_with_name(field: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Err(field.name.to_string())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("a", fails_with_name)
+        .add_resolver("b", fails_with_name)
+        .add_resolver("c", fails_with_name)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let errors: Vec<&str> = result.errors.iter().map(|error| &*error.error).collect();
+    assert_eq!(errors, vec!["a", "b", "c"]);
+}
+
+#[tokio::test]
+async fn errors_have_the_path_of_the_field_that_failed() {
+    let schema = "
+    type Root {
+        users: Vec<User>
+    }
+
+    type User {
+        name: String
+        avatar(size: number): String
+    }
+    ";
+    let query = "
+        message {
+            users [
+                name
+                avatar(size: 48) as small_avatar
+            ]
+        }
+    ";
+
+    async fn users(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(vec![
+            HashMap::from([("name", "Alice")]),
+            HashMap::from([("name", "Bob")]),
+        ].into())
+    }
+
+    async fn avatar(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Err("no avatar".to_string())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("users", users)
+        .add_resolver("User.avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let paths: Vec<Vec<PathSegment>> = result.errors.iter().map(|error| error.path.clone()).collect();
+    assert_eq!(paths, vec![
+        vec!["users".into(), 0.into(), "small_avatar".into()],
+        vec!["users".into(), 1.into(), "small_avatar".into()],
+    ]);
+    assert_eq!(&*result.errors[0].field, "avatar");
+    let span = result.errors[0].span.expect("expected the error to have a span");
+    assert_eq!((span.start.line_number(), span.start.column_number()), (5, 17));
+
+    let expected: Value<(), String> = vec![
+        HashMap::from([("name", Value::from("Alice")), ("small_avatar", Value::Failed)]),
+        HashMap::from([("name", Value::from("Bob")), ("small_avatar", Value::Failed)]),
+    ].into();
+    assert_eq!(result.data["users"], expected);
+}
+
+#[tokio::test]
+async fn values_of_the_wrong_shape_fail_only_their_field() {
+    let schema = "
+    type Root {
+        me: User
+        users: Vec<User>
+        friends: Vec<User>
+        name: String
+    }
+
+    type User {
+        name: String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+            }
+            users [
+                name
+            ]
+            friends [
+                name
+            ]
+            name
+        }
+    ";
+
+    async fn me(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok("Alice".into())
+    }
+
+    async fn users(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(HashMap::from([("name", "Alice")]).into())
+    }
+
+    async fn friends(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(Value::Vec(vec![HashMap::from([("name", "Bob")]).into(), "Carol".into()]))
+    }
+
+    async fn name(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok("Alice".into())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("me", me)
+        .add_resolver("users", users)
+        .add_resolver("friends", friends)
+        .add_resolver("name", name)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert!(result.errors.is_empty());
+    let errors: Vec<_> = result.castle_errors.iter().map(|error| (error.path.clone(), error.error.to_string())).collect();
+    assert_eq!(errors, vec![
+        (vec!["friends".into(), 1.into()], "Return value error: friends expected resolver to return an object".to_string()),
+        (vec!["me".into()], "Return value error: me expected resolver to return an object".to_string()),
+        (vec!["users".into()], "Return value error: users expected resolver to return a list".to_string()),
+    ]);
+    assert_eq!(result.data["me"], Value::Failed);
+    assert_eq!(result.data["users"], Value::Failed);
+    assert_eq!(result.data["friends"], Value::Vec(vec![HashMap::from([("name", "Bob")]).into(), Value::Failed]));
+    assert_eq!(result.data["name"], Value::from("Alice"));
+}
+
+#[tokio::test]
+async fn aliased_fields_are_returned_under_their_alias() {
+    let schema = "
+    type Root {
+        profile_pic(size: number): String
+        me: User
+    }
+
+    type User {
+        first_name: String
+    }
+    ";
+    let query = "
+        message {
+            profile_pic(size: 100) as large_pic
+            profile_pic(size: 50) as small_pic
+            me {
+                first_name
+                first_name as name
+            }
+        }
+    ";
+
+    async fn profile_pic(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(format!("https://example.com/pic?size={}", field.inputs["size"]).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("profile_pic", profile_pic)
+        .add_resolver("me", |_: &Field, _: &()| async {
+            Ok([("first_name", "Albert")].into_iter().collect::<HashMap<_, _>>().into())
+        })
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let expected = CastleResult {
+        data: [
+            ("large_pic".into(), "https://example.com/pic?size=100".into()),
+            ("small_pic".into(), "https://example.com/pic?size=50".into()),
+            ("me".into(), [
+                ("first_name", "Albert"),
+                ("name", "Albert"),
+            ].into_iter().collect::<HashMap<_, _>>().into()),
+        ].into(),
+        errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected)
+}
+
+#[tokio::test]
+async fn match_projection_only_returns_fields_of_the_matched_arm() {
+    let schema = "
+    type Root {
+        icon: Icon
+        emoji: Icon
+        no_icon: Icon
+    }
+
+    enum Icon {
+        SVGIcon {
+            url: String,
+            size: number,
+        },
+        Emoji(String),
+        NoIcon,
+    }
+    ";
+    let query = "
+        message {
+            icon match {
+                SVGIcon { url }
+                Emoji
+            }
+            emoji match {
+                SVGIcon { url }
+                Emoji
+            }
+            no_icon match {
+                NoIcon
+            }
+        }
+    ";
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("icon", |_: &Field, _: &()| async {
+            let svg: Value<(), ()> = [
+                ("url", Value::from("https://example.com/icon.svg")),
+                ("size", Value::from(48)),
+            ].into_iter().collect::<HashMap<_, _>>().into();
+            Ok(Value::Variant("SVGIcon".into(), Box::new(svg)))
+        })
+        .add_resolver("emoji", |_: &Field, _: &()| async {
+            Ok(Value::Variant("Emoji".into(), Box::new(vec!["🏰"].into())))
+        })
+        .add_resolver("no_icon", |_: &Field, _: &()| async {
+            Ok(Value::Variant("NoIcon".into(), Box::new(Value::Void)))
+        })
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let expected = CastleResult {
+        data: [
+            ("icon".into(), Value::Variant("SVGIcon".into(), Box::new(
+                [("url", "https://example.com/icon.svg")].into_iter().collect::<HashMap<_, _>>().into()
+            ))),
+            ("emoji".into(), Value::Variant("Emoji".into(), Box::new(vec!["🏰"].into()))),
+            ("no_icon".into(), Value::Variant("NoIcon".into(), Box::new(Value::Void))),
+        ].into(),
+        errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected)
+}
+
+#[tokio::test]
+async fn variables_are_substituted_into_inputs() {
+    let schema = "
+    type Root {
+        profile_pic(name: String size: number): String
+    }
+    ";
+    let query = "
+        message GetProfilePic($name: String, $size: number = 48) {
+            profile_pic(name: $name, size: $size)
+        }
+    ";
+
+    async fn profile_pic(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(format!("https://example.com/{}?size={}", field.inputs["name"].as_str().unwrap(), field.inputs["size"]).into())
+    }
+
+    let castle = CastleBuilder::new(schema)
+        .add_resolver("profile_pic", profile_pic)
+        .build()
+        .unwrap();
+
+    let result: CastleResult<(), ()> = castle
+        .run_message_with_variables(query, &[("name".into(), Input::Primitive(Primitive::String("albert".into())))].into(), &())
+        .await
+        .unwrap();
+    assert_eq!(result.data, [("profile_pic".into(), "https://example.com/albert?size=48".into())].into());
+
+    let result: CastleResult<(), ()> = castle
+        .run_message_with_variables(query, &[
+            ("name".into(), Input::Primitive(Primitive::String("albert".into()))),
+            ("size".into(), Input::Primitive(Primitive::Number(100.into()))),
+        ].into(), &())
+        .await
+        .unwrap();
+    assert_eq!(result.data, [("profile_pic".into(), "https://example.com/albert?size=100".into())].into());
+}
+
+#[tokio::test]
+async fn invalid_variable_values_fail() {
+    let schema = "
+    type Root {
+        profile_pic(size: number): String
+    }
+    ";
+    let query = "
+        message GetProfilePic($size: number) {
+            profile_pic(size: $size)
+        }
+    ";
+
+    let castle: Castle<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("profile_pic", |_: &Field, _: &()| async { unimplemented!() })
+        .build()
+        .unwrap();
+
+    // missing
+    castle.run_message_with_variables(query, &HashMap::new(), &()).await.unwrap_err();
+    // wrong type
+    castle.run_message_with_variables(query, &[
+        ("size".into(), Input::Primitive(Primitive::String("100".into()))),
+    ].into(), &()).await.unwrap_err();
+    // not declared
+    castle.run_message_with_variables(query, &[
+        ("size".into(), Input::Primitive(Primitive::Number(100.into()))),
+        ("width".into(), Input::Primitive(Primitive::Number(100.into()))),
+    ].into(), &()).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn omitted_inputs_are_given_their_default_values() {
+    let schema = "
+    type Root {
+        avatar(size: number = 48 crop: Crop): String
+    }
+
+    input Crop {
+        x: number
+        y: number = 0
+    }
+    ";
+    let query = "
+        message {
+            avatar(crop: { x: 10 })
+        }
+    ";
+
+    async fn avatar(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        let crop = field.inputs["crop"].as_map().unwrap();
+        Ok(format!("size={} x={} y={}", field.inputs["size"], crop["x"], crop["y"]).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.data, [("avatar".into(), "size=48 x=10 y=0".into())].into());
+}
+
+#[tokio::test]
+async fn omitted_optional_inputs_are_none() {
+    let schema = "
+    type Root {
+        avatar(size: Option<number>, crop: Option<Crop>): String
+    }
+
+    input Crop {
+        x: number
+        y: Option<number>
+    }
+    ";
+    let query = "
+        message {
+            avatar(crop: { x: 10 }) as omitted
+            avatar(size: None, crop: { x: 10, y: None }) as explicit
+        }
+    ";
+
+    async fn avatar(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        let crop = field.inputs["crop"].as_map().unwrap();
+        Ok(format!("size={} y={}", field.inputs["size"], crop["y"]).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.data, [
+        ("omitted".into(), "size=None y=None".into()),
+        ("explicit".into(), "size=None y=None".into()),
+    ].into());
+}
+
+#[tokio::test]
+async fn resolvers_can_read_enum_variant_inputs() {
+    let schema = "
+    type Root {
+        height(unit: Unit): String
+    }
+
+    enum Unit {
+        Meter,
+        Feet,
+    }
+    ";
+    let query = "
+        message {
+            height(unit: Feet)
+        }
+    ";
+
+    async fn height(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        let unit = field.inputs["unit"].as_variant().unwrap();
+        Ok(format!("6 {}", unit.ident()).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("height", height)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.data, [("height".into(), "6 Feet".into())].into());
+}
+
+#[tokio::test]
+async fn resolvers_can_read_uuid_and_optional_inputs() {
+    let schema = "
+    type Root {
+        user(id: Uuid size: Option<number>): String
+    }
+    ";
+    let query = "
+        message GetUser($id: Uuid, $size: Option<number>) {
+            user(id: $id, size: $size)
+        }
+    ";
+
+    async fn user(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        let id = field.inputs["id"].as_uuid().unwrap();
+        Ok(format!("{} {}", id.as_hyphenated(), field.inputs["size"].is_none()).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("user", user)
+        .build()
+        .unwrap()
+        .run_message_with_variables(query, &[
+            ("id".into(), Input::Primitive(Primitive::String("67e55044-10b1-426f-9247-bb680e5fe0c8".into()))),
+        ].into(), &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.data, [("user".into(), "67e55044-10b1-426f-9247-bb680e5fe0c8 true".into())].into());
+}
+
 // use std::future::Future;
 
 // struct A;
@@ -231,3 +1012,242 @@ async fn test_directives() {
 
 //     some_fn(x).await;
 // }
+
+#[tokio::test]
+async fn optional_fields_that_resolve_to_nothing_are_none() {
+    let schema = "
+    type Root {
+        me: Option<User>
+        nickname: Option<String>
+        friends: Vec<Option<User>>
+    }
+
+    type User {
+        name: String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+            }
+            nickname
+            friends [
+                name
+            ]
+        }
+    ";
+
+    async fn me(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(None::<Value<(), ()>>.into())
+    }
+
+    async fn nickname(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(Value::Void)
+    }
+
+    async fn friends(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(Value::Vec(vec![Value::None, HashMap::from([("name", "Bob")]).into()]))
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("me", me)
+        .add_resolver("nickname", nickname)
+        .add_resolver("friends", friends)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let expected = CastleResult {
+        data: [
+            ("me".into(), Value::None),
+            ("nickname".into(), Value::None),
+            ("friends".into(), Value::Vec(vec![Value::None, HashMap::from([("name", "Bob")]).into()])),
+        ].into(),
+        errors: vec![],
+        castle_errors: vec![],
+    };
+    assert_eq!(result, expected);
+}
+
+#[tokio::test]
+async fn non_optional_fields_cannot_resolve_to_none() {
+    let schema = "
+    type Root {
+        name: String
+        nickname: String
+        me: User
+    }
+
+    type User {
+        name: String
+        email: String
+        icon: Option<String>
+    }
+    ";
+    let query = "
+        message {
+            name
+            nickname
+            me {
+                name
+                email
+                icon
+            }
+        }
+    ";
+
+    async fn name(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(Value::None)
+    }
+
+    async fn nickname(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(Value::Void)
+    }
+
+    async fn me(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(HashMap::from([("name", "Alice")]).into())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("name", name)
+        .add_resolver("nickname", nickname)
+        .add_resolver("me", me)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert!(result.errors.is_empty());
+    let errors: Vec<_> = result.castle_errors.iter().map(|error| (error.path.clone(), error.error.to_string())).collect();
+    assert_eq!(errors, vec![
+        (vec!["me".into(), "email".into()], "Return value error: email resolved to nothing, but its type String is not an Option".to_string()),
+        (vec!["name".into()], "Return value error: name resolved to None, but its type String is not an Option".to_string()),
+        (vec!["nickname".into()], "Return value error: nickname resolved to nothing, but its type String is not an Option".to_string()),
+    ]);
+    assert_eq!(result.data["name"], Value::Failed);
+    assert_eq!(result.data["nickname"], Value::Failed);
+    // fields missing from the parent are None if they're optional
+    assert_eq!(result.data["me"], HashMap::from([
+        ("name", Value::from("Alice")),
+        ("email", Value::Failed),
+        ("icon", Value::None),
+    ]).into());
+}
+
+const RETURN_VALUE_SCHEMA: &str = "
+    type Root {
+        name: String
+        users: Vec<User>
+    }
+
+    type User {
+        name: String
+        age: number
+    }
+";
+
+const RETURN_VALUE_QUERY: &str = "
+    message {
+        name
+        users [
+            name
+        ]
+    }
+";
+
+async fn wrong_name(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+    Ok(Value::Number(1u64.into()))
+}
+
+async fn users_with_a_bad_item(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+    Ok(Value::Vec(vec![
+        HashMap::from([("name", Value::from("Alice")), ("age", Value::Number(30u64.into()))]).into(),
+        HashMap::from([("name", Value::Bool(true)), ("age", Value::Number(41u64.into()))]).into(),
+    ]))
+}
+
+async fn run_with_return_value_check(check: ReturnValueCheck<String>) -> CastleResult<(), String> {
+    CastleBuilder::new(RETURN_VALUE_SCHEMA)
+        .add_resolver("name", wrong_name)
+        .add_resolver("users", users_with_a_bad_item)
+        .set_return_value_check(check)
+        .build()
+        .unwrap()
+        .run_message(RETURN_VALUE_QUERY, &())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn strict_return_value_check_fails_mismatched_fields() {
+    let result = run_with_return_value_check(ReturnValueCheck::Strict(|err| err.to_string())).await;
+
+    let errors: Vec<(Vec<PathSegment>, &str)> = result.errors.iter().map(|error| (error.path.clone(), &*error.error)).collect();
+    assert_eq!(errors, vec![
+        (vec!["name".into()], "Return value error: name expected String but got a number"),
+        (vec!["users".into(), 1.into(), "name".into()], "Return value error: name expected String but got a bool"),
+    ]);
+    assert_eq!(result.data["name"], Value::Failed);
+    let expected: Value<(), String> = vec![
+        HashMap::from([("name", Value::from("Alice"))]),
+        HashMap::from([("name", Value::Failed)]),
+    ].into();
+    assert_eq!(result.data["users"], expected);
+}
+
+#[tokio::test]
+async fn warn_return_value_check_still_returns_the_value() {
+    let result = run_with_return_value_check(ReturnValueCheck::Warn(|err| err.to_string())).await;
+
+    assert_eq!(result.errors.len(), 2);
+    assert_eq!(result.data["name"], Value::Number(1u64.into()));
+}
+
+#[tokio::test]
+async fn return_values_are_not_checked_by_default() {
+    let result = run_with_return_value_check(ReturnValueCheck::Off).await;
+
+    assert_eq!(result.errors, vec![]);
+    assert_eq!(result.data["name"], Value::Number(1u64.into()));
+}
+
+#[tokio::test]
+async fn checked_return_values_only_contain_projected_fields() {
+    let schema = "
+    type Root {
+        me: User
+    }
+
+    type User {
+        name: String
+        age: number
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+            }
+        }
+    ";
+
+    async fn me(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(HashMap::from([("name", Value::from("Alice")), ("age", Value::Number(30u64.into()))]).into())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("me", me)
+        .set_return_value_check(ReturnValueCheck::Strict(|err| err.to_string()))
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.errors, vec![]);
+    assert_eq!(result.data["me"], HashMap::from([("name", "Alice")]).into());
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use castle_api::{castle::CastleBuilder, msgpack, types::result::CastleResult, Input, Inputs, Number, Primitive, Value};
+use castle_query_parser::Field;
+
+#[test]
+fn values_round_trip_keeping_their_number_kinds() {
+    let value: Value<(), ()> = HashMap::from([
+        ("unsigned", Value::from(3u8)),
+        ("signed", Value::from(-3i32)),
+        ("float", Value::from(3.0f64)),
+        ("name", Value::from("Alice")),
+        ("admin", Value::from(true)),
+        ("friends", Value::Vec(vec![HashMap::from([("name", "Bob")]).into()])),
+        ("pet", Value::None),
+    ]).into();
+
+    let bytes = msgpack::encode_value(&value).unwrap();
+    let decoded: Value<(), ()> = msgpack::decode_value(&bytes).unwrap();
+    assert_eq!(decoded, value);
+
+    let raw = rmpv::decode::read_value(&mut &bytes[..]).unwrap();
+    let raw: HashMap<&str, &rmpv::Value> = raw.as_map().unwrap().iter().map(|(k, v)| (k.as_str().unwrap(), v)).collect();
+    assert!(raw["unsigned"].is_u64());
+    assert!(raw["signed"].is_i64() && !raw["signed"].is_u64());
+    assert!(raw["float"].is_f64());
+}
+
+#[tokio::test]
+async fn results_are_encoded_as_data_and_errors() {
+    let schema = "
+    type Root {
+        name: String
+        avatar: String
+    }
+    ";
+    let query = "
+        message {
+            name
+            avatar
+        }
+    ";
+
+    async fn name(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok("Alice".into())
+    }
+
+    async fn avatar(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Err("no avatar".to_string())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("name", name)
+        .add_resolver("avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let bytes = msgpack::encode_result(&result, |error| error).unwrap();
+    let envelope = msgpack::decode_value::<(), ()>(&bytes).unwrap().as_object().unwrap();
+
+    let expected_data: Value<(), ()> = HashMap::from([("name", Value::from("Alice")), ("avatar", Value::None)]).into();
+    assert_eq!(envelope["data"], expected_data);
+
+    let error = envelope["errors"].clone().as_vec().unwrap().remove(0).as_object().unwrap();
+    assert_eq!(error["error"], Value::from("no avatar"));
+    assert_eq!(error["path"], Value::Vec(vec!["avatar".into()]));
+}
+
+#[test]
+fn variables_round_trip() {
+    let variables: Inputs = HashMap::from([
+        ("id".into(), Input::Primitive(Primitive::String("abc".into()))),
+        ("size".into(), Input::Primitive(Primitive::Number(Number::new(48u8)))),
+        ("offset".into(), Input::Primitive(Primitive::Number(Number::new(-1i8)))),
+        ("scale".into(), Input::Primitive(Primitive::Number(Number::new(1.5f64)))),
+        ("tags".into(), Input::List(vec![Input::Primitive(Primitive::Boolean(true))])),
+        ("filter".into(), Input::Map(HashMap::from([("name".into(), Input::None)]))),
+    ]);
+
+    let bytes = msgpack::encode_variables(&variables).unwrap();
+    assert_eq!(msgpack::decode_variables(&bytes).unwrap(), variables);
+}
+
+#[test]
+fn variables_must_be_a_map() {
+    let bytes = msgpack::encode_value::<(), ()>(&Value::from(3u8)).unwrap();
+    assert!(msgpack::decode_variables(&bytes).is_err());
+}
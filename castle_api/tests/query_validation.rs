@@ -1,6 +1,8 @@
-use castle_api::{castle::Castle};
-use castle_error::CastleError;
+use castle_api::{castle::Castle, check_message};
+use castle_error::{CastleError, ExtendedErrorDisplay};
 use castle_query_parser::Field;
+use castle_query_parser::parse_message;
+use castle_schema_parser::parsers::parse_schema::parse_schema;
 use castle_api::castle::CastleBuilder;
 
 async fn create_castle() -> Castle<(), ()> {
@@ -499,3 +501,524 @@ async fn fails_for_invalid_field_multiple_layers_of_nesting() {
         .unwrap_err();
 }
 
+
+
+#[tokio::test]
+async fn aliased_fields_validate_against_the_original_field() {
+    let msg = r#"
+    message {
+        foo(bar: 1) as first_foo
+        foo(bar: 2) as second_foo
+        some_thing {
+            hello as greeting
+        }
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn alias_of_undefined_field_fails() {
+    let msg = r#"
+    message {
+        doesnt_exist as hello
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn duplicate_response_keys_fail() {
+    let msg = r#"
+    message {
+        hello
+        thing_is_true as hello
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+async fn create_castle_with_enum() -> Castle<(), ()> {
+    let schema = r#"
+        type Root {
+            icon: Icon
+            maybe_icon: Option<Icon>
+            hello: String
+        }
+
+        enum Icon {
+            SVGIcon {
+                url: String,
+                size: number,
+            },
+            Emoji(String),
+            NoIcon,
+        }
+    "#;
+    CastleBuilder::new(schema)
+        .add_resolver("icon", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("maybe_icon", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("hello", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn match_projection_validates() {
+    let msg = r#"
+    message {
+        icon match {
+            SVGIcon { url size }
+            Emoji
+            NoIcon
+        }
+        maybe_icon match {
+            SVGIcon { url }
+        }
+    }
+    "#;
+
+    create_castle_with_enum()
+        .await.validate_message(msg)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn match_on_non_enum_fails() {
+    let msg = r#"
+    message {
+        hello match {
+            SVGIcon { url }
+        }
+    }
+    "#;
+
+    create_castle_with_enum()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn match_with_unknown_variant_fails() {
+    let msg = r#"
+    message {
+        icon match {
+            PNGIcon
+        }
+    }
+    "#;
+
+    create_castle_with_enum()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn match_with_undefined_variant_field_fails() {
+    let msg = r#"
+    message {
+        icon match {
+            SVGIcon { url colour }
+        }
+    }
+    "#;
+
+    create_castle_with_enum()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn match_projecting_fields_of_unit_variant_fails() {
+    let msg = r#"
+    message {
+        icon match {
+            NoIcon { url }
+        }
+    }
+    "#;
+
+    create_castle_with_enum()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn declared_variables_validate() {
+    let msg = r#"
+    message SayHello($bar: number = 1, $arg: Xyz) {
+        foo(bar: $bar)
+        baz(arg: $arg)
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn undeclared_variable_fails() {
+    let msg = r#"
+    message {
+        foo(bar: $bar)
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn variable_declared_with_wrong_kind_fails() {
+    let msg = r#"
+    message SayHello($bar: String) {
+        foo(bar: $bar)
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn variable_with_undefined_kind_fails() {
+    let msg = r#"
+    message SayHello($bar: Abc) {
+        hello
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn variable_with_mismatched_default_fails() {
+    let msg = r#"
+    message SayHello($bar: number = "1") {
+        foo(bar: $bar)
+    }
+    "#;
+
+    create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn omitted_args_with_defaults_or_option_kinds_validate() {
+    let schema = r#"
+        type Root {
+            avatar(size: number = 48 shape: Option<String>): String
+        }
+    "#;
+    let msg = r#"
+    message {
+        avatar
+    }
+    "#;
+
+    CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("avatar", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap()
+        .validate_message(msg)
+        .unwrap();
+}
+
+async fn create_castle_with_enum_inputs() -> Castle<(), ()> {
+    let schema = r#"
+        type Root {
+            height(unit: Unit): number
+            paint(colour: Colour): String
+            foo(bar: number): String
+        }
+
+        enum Unit {
+            Meter,
+            Feet,
+        }
+
+        enum Colour {
+            Rgb(number, number, number),
+            Named {
+                name: String,
+                shade: Option<String>,
+            },
+        }
+    "#;
+    CastleBuilder::new(schema)
+        .add_resolver("height", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("paint", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("foo", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn enum_variant_inputs_validate() {
+    let msg = r#"
+    message {
+        height(unit: Meter)
+        paint(colour: Rgb(255, 0, 0)) as red
+        paint(colour: Named { name: "teal" }) as teal
+    }
+    "#;
+
+    create_castle_with_enum_inputs()
+        .await.validate_message(msg)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn enum_variant_input_that_does_not_exist_fails() {
+    let msg = r#"
+    message {
+        height(unit: Inch)
+    }
+    "#;
+
+    create_castle_with_enum_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn enum_variant_input_with_wrong_shape_fails() {
+    let msg = r#"
+    message {
+        paint(colour: Rgb)
+    }
+    "#;
+
+    create_castle_with_enum_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn enum_tuple_variant_input_with_wrong_arity_fails() {
+    let msg = r#"
+    message {
+        paint(colour: Rgb(255, 0))
+    }
+    "#;
+
+    create_castle_with_enum_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn enum_tuple_variant_input_with_wrong_kind_fails() {
+    let msg = r#"
+    message {
+        paint(colour: Rgb(255, 0, "0"))
+    }
+    "#;
+
+    create_castle_with_enum_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn enum_map_variant_input_with_missing_or_unspecified_fields_fails() {
+    let missing = r#"
+    message {
+        paint(colour: Named { shade: "dark" })
+    }
+    "#;
+    let unspecified = r#"
+    message {
+        paint(colour: Named { name: "teal", hue: 180 })
+    }
+    "#;
+
+    let castle = create_castle_with_enum_inputs().await;
+    castle.validate_message(missing).unwrap_err();
+    castle.validate_message(unspecified).unwrap_err();
+}
+
+#[tokio::test]
+async fn enum_variant_input_for_non_enum_kind_fails() {
+    let msg = r#"
+    message {
+        foo(bar: Meter)
+    }
+    "#;
+
+    create_castle_with_enum_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+async fn create_castle_with_option_and_uuid_inputs() -> Castle<(), ()> {
+    let schema = r#"
+        type Root {
+            avatar(size: Option<number>): String
+            user(id: Uuid): String
+            users(ids: Vec<Uuid>): String
+        }
+    "#;
+    CastleBuilder::new(schema)
+        .add_resolver("avatar", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("user", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("users", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn option_inputs_accept_values_none_and_null() {
+    let msg = r#"
+    message {
+        avatar(size: 48) as with_value
+        avatar(size: None) as with_none
+        avatar(size: null) as with_null
+        avatar as omitted
+    }
+    "#;
+
+    create_castle_with_option_and_uuid_inputs()
+        .await.validate_message(msg)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn option_input_with_wrong_inner_type_fails() {
+    let msg = r#"
+    message {
+        avatar(size: "48")
+    }
+    "#;
+
+    create_castle_with_option_and_uuid_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn none_for_non_option_input_fails() {
+    let msg = r#"
+    message {
+        user(id: None)
+    }
+    "#;
+
+    create_castle_with_option_and_uuid_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn uuid_inputs_validate() {
+    let msg = r#"
+    message {
+        user(id: "67e55044-10b1-426f-9247-bb680e5fe0c8")
+        users(ids: ["67e55044-10b1-426f-9247-bb680e5fe0c8", "936da01f-9abd-4d9d-80c7-02af85c822a8"])
+    }
+    "#;
+
+    create_castle_with_option_and_uuid_inputs()
+        .await.validate_message(msg)
+        .unwrap();
+}
+
+#[tokio::test]
+async fn invalid_uuid_input_fails() {
+    let msg = r#"
+    message {
+        user(id: "not-a-uuid")
+    }
+    "#;
+
+    create_castle_with_option_and_uuid_inputs()
+        .await.validate_message(msg)
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn every_message_error_is_reported() {
+    let msg = r#"
+    message {
+        foo(bar: "1")
+        doesnt_exist
+        some_thing {
+            hello
+            omega
+        }
+    }
+    "#;
+
+    let err = create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+
+    let diagnostics = match err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    let paths: Vec<&str> = diagnostics.iter().map(|diagnostic| &*diagnostic.path).collect();
+    assert_eq!(paths, vec!["root.doesnt_exist", "root.foo.bar", "root.some_thing.omega"]);
+}
+
+#[tokio::test]
+async fn message_errors_point_at_the_source() {
+    let msg = "
+message {
+    foo(bar: \"1\")
+    doesnt_exist
+}
+";
+
+    let err = create_castle()
+        .await.validate_message(msg)
+        .unwrap_err();
+
+    let diagnostics = match &err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    let spans: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.span.expect("expected the error to have a span"))
+        .map(|span| (span.start.line_number(), span.start.column_number(), span.end.column_number()))
+        .collect();
+    // root.doesnt_exist is the field itself, root.foo.bar is an input of foo so it points at foo
+    assert_eq!(spans, vec![(4, 5, 17), (3, 5, 8)]);
+
+    let pretty = err.extended_error(msg);
+    assert!(pretty.contains("^^^^^^^^^^^^ Schema validation error: root has no field named: doesnt_exist"));
+}
+
+#[test]
+fn message_can_be_checked_against_a_schema_without_resolvers() {
+    let schema = parse_schema("
+    type Root {
+        foo(bar: number): String
+    }
+    ").unwrap();
+
+    let valid = parse_message("message { foo(bar: 1) }").unwrap();
+    check_message(&schema, &valid).unwrap();
+
+    let invalid = parse_message("message { foo(bar: \"1\") baz }").unwrap();
+    match check_message(&schema, &invalid).unwrap_err() {
+        CastleError::Multiple(diagnostics) => assert_eq!(diagnostics.len(), 2),
+        err => panic!("expected multiple errors, got {:?}", err),
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use castle_api::{castle::CastleBuilder, types::result::CastleResult, Value};
+use castle_query_parser::Field;
+use serde_json::json;
+
+#[test]
+fn numbers_serialize_as_plain_json_numbers() {
+    let values: Vec<Value<(), ()>> = vec![3u8.into(), (-3i32).into(), 3.5f64.into()];
+    let json = serde_json::to_string(&Value::<(), ()>::Vec(values)).unwrap();
+    assert_eq!(json, "[3,-3,3.5]");
+}
+
+#[test]
+fn variants_serialize_as_their_name_or_a_single_key_object() {
+    let unit: Value<(), ()> = Value::Variant("Emoji".into(), Box::new(Value::Void));
+    let map: Value<(), ()> = Value::Variant("SVGIcon".into(), Box::new(HashMap::from([("url", "a.svg")]).into()));
+    assert_eq!(serde_json::to_value(&unit).unwrap(), json!("Emoji"));
+    assert_eq!(serde_json::to_value(&map).unwrap(), json!({ "SVGIcon": { "url": "a.svg" } }));
+}
+
+#[test]
+fn values_can_be_deserialized_from_json() {
+    let value: Value<(), ()> = serde_json::from_str(r#"{"name": "Alice", "age": 30, "scores": [1.5, -2], "pet": null}"#).unwrap();
+    let expected: Value<(), ()> = HashMap::from([
+        ("name", Value::from("Alice")),
+        ("age", Value::from(30u8)),
+        ("scores", Value::Vec(vec![1.5f64.into(), (-2i32).into()])),
+        ("pet", Value::None),
+    ]).into();
+    assert_eq!(value, expected);
+}
+
+#[tokio::test]
+async fn result_serializes_as_data_and_errors() {
+    let schema = "
+    type Root {
+        users: Vec<User>
+    }
+
+    type User {
+        name: String
+        avatar: String
+    }
+    ";
+    let query = "
+        message {
+            users [
+                name
+                avatar
+            ]
+        }
+    ";
+
+    async fn users(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Ok(vec![HashMap::from([("name", "Alice")])].into())
+    }
+
+    async fn avatar(_: &Field, _: &()) -> Result<Value<(), String>, String> {
+        Err("no avatar".to_string())
+    }
+
+    let result: CastleResult<(), String> = CastleBuilder::new(schema)
+        .add_resolver("users", users)
+        .add_resolver("User.avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let expected = json!({
+        "data": {
+            "users": [
+                { "name": "Alice", "avatar": null }
+            ]
+        },
+        "errors": [
+            {
+                "path": ["users", 0, "avatar"],
+                "field": "avatar",
+                "span": {
+                    "start": { "line_number": 5, "column_number": 17 },
+                    "end": { "line_number": 5, "column_number": 23 }
+                },
+                "error": "no avatar"
+            }
+        ]
+    });
+    assert_eq!(serde_json::to_value(&result).unwrap(), expected);
+
+    let mapped: serde_json::Value = serde_json::from_str(
+        &result.to_json(|error| json!({ "message": error })).unwrap()
+    ).unwrap();
+    assert_eq!(mapped["errors"][0]["error"], json!({ "message": "no avatar" }));
+}
+
+#[tokio::test]
+async fn castle_errors_serialize_with_their_message_after_resolver_errors() {
+    let schema = "
+    type Root {
+        me: User
+        name: String
+    }
+
+    type User {
+        name: String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+            }
+            name
+        }
+    ";
+
+    async fn me(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok("Alice".into())
+    }
+
+    async fn name(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Err(())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_resolver("me", me)
+        .add_resolver("name", name)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["errors"][0]["path"], json!(["name"]));
+    assert_eq!(json["errors"][0]["error"], json!(null));
+    assert_eq!(json["errors"][1]["path"], json!(["me"]));
+    assert_eq!(json["errors"][1]["error"], json!("Return value error: me expected resolver to return an object"));
+}
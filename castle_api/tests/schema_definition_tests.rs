@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use castle_api::{castle::CastleBuilder, types::result::CastleResult, CastleEnum, CastleInput, CastleType, Field, Value};
+use castle_error::{CastleError, ExtendedErrorDisplay};
+use castle_schema_parser::parsers::parse_schema::parse_schema;
+
+/// A person who uses the app
+#[derive(CastleType)]
+#[allow(dead_code)]
+struct User {
+    id: uuid::Uuid,
+    /// Shown to others
+    name: String,
+    age: u32,
+    nickname: Option<String>,
+    friends: Vec<User>,
+    icon: Icon,
+}
+
+#[derive(CastleEnum)]
+#[allow(dead_code, clippy::enum_variant_names)]
+enum Icon {
+    SVGIcon {
+        url: String,
+        size: u32,
+    },
+    Emoji(String),
+    /// No icon at all
+    NoIcon,
+}
+
+#[derive(CastleInput)]
+#[allow(dead_code)]
+struct Crop {
+    /// From the left
+    x: f64,
+    y: Option<f64>,
+}
+
+const SCHEMA: &str = "
+    ## A person who uses the app
+    type User {
+        id: Uuid
+        ## Shown to others
+        name: String
+        age: number
+        nickname: Option<String>
+        friends: Vec<User>
+        icon: Icon
+    }
+
+    enum Icon {
+        SVGIcon {
+            url: String,
+            size: number,
+        },
+        Emoji(String),
+        ## No icon at all
+        NoIcon,
+    }
+
+    input Crop {
+        ## From the left
+        x: number
+        y: Option<number>
+    }
+";
+
+#[test]
+fn derived_definitions_match_the_same_definitions_in_a_schema() {
+    let parsed = parse_schema(SCHEMA).unwrap();
+    assert_eq!(User::type_definition(), parsed.types["User"]);
+    assert_eq!(Icon::enum_definition(), parsed.enums["Icon"]);
+    assert_eq!(Crop::input_type_definition(), parsed.input_types["Crop"]);
+    assert_eq!(User::type_definition().fields["name"].docs.as_deref(), Some("Shown to others"));
+}
+
+#[test]
+fn derived_doc_comments_are_in_the_parsed_schema() {
+    let castle = CastleBuilder::<(), ()>::new("type Root { me: User }")
+        .add_type::<User>()
+        .add_enum::<Icon>()
+        .add_resolver("me", |_: &Field, _: &()| async { Ok(Value::from("me")) })
+        .build()
+        .unwrap();
+
+    let user = &castle.parsed_schema.types["User"];
+    assert_eq!(user.docs.as_deref(), Some("A person who uses the app"));
+    assert_eq!(user.fields["name"].docs.as_deref(), Some("Shown to others"));
+    assert_eq!(user.fields["age"].docs, None);
+    assert_eq!(castle.parsed_schema.enums["Icon"].variants["NoIcon"].docs.as_deref(), Some("No icon at all"));
+}
+
+#[tokio::test]
+async fn derived_definitions_can_be_mixed_with_a_schema() {
+    let schema = "
+    type Root {
+        me: User
+        avatar(crop: Crop): String
+    }
+    ";
+    let query = "
+        message {
+            me {
+                name
+                icon match {
+                    Emoji
+                }
+            }
+            avatar(crop: { x: 1 })
+        }
+    ";
+
+    async fn me(_: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(HashMap::from([
+            ("name", Value::from("Alice")),
+            ("icon", Value::Variant("Emoji".into(), Box::new(vec!["🏰"].into()))),
+        ]).into())
+    }
+
+    async fn avatar(field: &Field, _: &()) -> Result<Value<(), ()>, ()> {
+        Ok(format!("crop {}", field.inputs["crop"].as_map().unwrap()["x"]).into())
+    }
+
+    let result: CastleResult<(), ()> = CastleBuilder::new(schema)
+        .add_type::<User>()
+        .add_enum::<Icon>()
+        .add_input_type::<Crop>()
+        .add_resolver("me", me)
+        .add_resolver("avatar", avatar)
+        .build()
+        .unwrap()
+        .run_message(query, &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.errors, vec![]);
+    assert_eq!(result.data["me"], HashMap::from([
+        ("name", Value::from("Alice")),
+        ("icon", Value::Variant("Emoji".into(), Box::new(vec!["🏰"].into()))),
+    ]).into());
+    assert_eq!(result.data["avatar"], Value::from("crop 1"));
+}
+
+#[derive(CastleType)]
+#[allow(dead_code)]
+struct Root {
+    version: String,
+}
+
+#[tokio::test]
+async fn schemas_can_be_entirely_derived() {
+    let result: CastleResult<(), ()> = CastleBuilder::new("")
+        .add_type::<Root>()
+        .add_resolver("version", |_: &Field, _: &()| async { Ok(Value::from("1.0")) })
+        .build()
+        .unwrap()
+        .run_message("message { version }", &())
+        .await
+        .unwrap();
+
+    assert_eq!(result.data["version"], Value::from("1.0"));
+}
+
+#[test]
+fn derived_definitions_cant_replace_schema_definitions() {
+    let err = CastleBuilder::<(), ()>::new(SCHEMA)
+        .add_type::<User>()
+        .build()
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "Schema validation error: User is defined more than once");
+
+    // types, input types and enums can't share a name either
+    let err = CastleBuilder::<(), ()>::new("type Root { me: String }\nenum Crop { Square }")
+        .add_input_type::<Crop>()
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Schema validation error: Crop is defined more than once");
+
+    let err = CastleBuilder::<(), ()>::new("type Root { me: String }\ninput Icon { url: String }")
+        .add_enum::<Icon>()
+        .build()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Schema validation error: Icon is defined more than once");
+}
+
+#[derive(CastleType)]
+#[allow(dead_code)]
+struct Profile {
+    picture: Crop,
+}
+
+#[test]
+fn errors_in_derived_definitions_have_no_span() {
+    let schema = "
+    type Root {
+        profile: Profile
+    }
+    ";
+    let err = CastleBuilder::<(), ()>::new(schema)
+        .add_type::<Profile>()
+        .add_resolver("profile", |_: &Field, _: &()| async { unimplemented!() })
+        .build()
+        .unwrap_err();
+
+    let diagnostics = match &err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    assert_eq!(&*diagnostics[0].path, "Profile.picture");
+    assert!(diagnostics[0].span.is_none());
+    // without a span the error isn't underlined in the unrelated schema
+    assert_eq!(err.extended_error(schema), "Profile.picture has invalid return type: Type Crop not defined in schema types or enums");
+}
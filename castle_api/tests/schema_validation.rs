@@ -2,8 +2,10 @@
 // error if unknown argument provided to directive
 // error if directive definition argument with no default is missing in the directive
 // error if the directive is allowed on the given directive location
-use castle_api::{castle::CastleBuilder, Directive};
+use castle_api::{castle::CastleBuilder, check_schema, Directive};
+use castle_schema_parser::parsers::parse_schema::parse_schema;
 use castle_query_parser::Field;
+use castle_error::{CastleError, ExtendedErrorDisplay};
 
 struct MockDirective;
 
@@ -377,4 +379,215 @@ async fn directive_with_missing_arg_fails() {
 
 // todo: test generic types
 // todo: enum types
-// todo: option type
\ No newline at end of file
+// todo: option type
+
+#[tokio::test]
+async fn nested_field_with_inputs_and_no_resolver_fails() {
+    let schema = "
+    type Root {
+        me: User
+    }
+
+    type User {
+        name: String
+        avatar(size: number): String
+    }
+    ";
+
+    let err = CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("me", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap_err();
+
+    assert_eq!(err.to_string(), "Missing resolver: Missing resolver for User.avatar");
+}
+
+#[tokio::test]
+async fn nested_field_with_inputs_and_type_resolver_succeeds() {
+    let schema = "
+    type Root {
+        me: User
+    }
+
+    type User {
+        name: String
+        avatar(size: number): String
+    }
+    ";
+
+    CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("me", |_: &Field, _: &()|async { unimplemented!() })
+        .add_type_resolver("User", "avatar", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn resolver_for_undefined_field_fails() {
+    let schema = "
+    type Root {
+        foo: String
+    }
+    ";
+
+    CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("foo", |_: &Field, _: &()|async { unimplemented!() })
+        .add_resolver("Root.bar", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn input_default_matching_kind_succeeds() {
+    let schema = "
+    type Root {
+        avatar(size: number = 48): String
+    }
+
+    input Dimensions {
+        unit: String = \"px\"
+    }
+    ";
+
+    CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("avatar", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn input_default_with_wrong_kind_fails() {
+    let schema = "
+    type Root {
+        avatar(size: number = \"48\"): String
+    }
+    ";
+
+    CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("avatar", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn input_type_default_with_wrong_kind_fails() {
+    let schema = "
+    type Root {
+        foo: String
+    }
+
+    input Dimensions {
+        unit: String = 1
+    }
+    ";
+
+    CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("foo", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn every_schema_error_is_reported() {
+    let schema = "
+    type Root {
+        me: User
+        avatar(size: Size): String
+    }
+
+    type User {
+        name: Strin
+        friends(first: number): Vec<User>
+    }
+    ";
+
+    let err = CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("me", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap_err();
+
+    let diagnostics = match err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    let paths: Vec<&str> = diagnostics.iter().map(|diagnostic| &*diagnostic.path).collect();
+    assert_eq!(paths, vec!["Root.avatar", "Root.avatar.size", "User.friends", "User.name"]);
+}
+
+#[tokio::test]
+async fn schema_errors_point_at_the_source() {
+    let schema = "
+type Root {
+    me: User
+}
+
+type User {
+    name: Strin
+}
+";
+
+    let err = CastleBuilder::<(), ()>::new(schema)
+        .add_resolver("me", |_: &Field, _: &()|async { unimplemented!() })
+        .build()
+        .unwrap_err();
+
+    let diagnostics = match &err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    let span = diagnostics[0].span.expect("expected the error to have a span");
+    assert_eq!((span.start.line_number(), span.start.column_number()), (7, 11));
+    assert_eq!((span.end.line_number(), span.end.column_number()), (7, 16));
+
+    let pretty = err.extended_error(schema);
+    println!("{}", pretty);
+    assert!(pretty.contains("name: Strin"));
+    assert!(pretty.contains("^^^^^ Schema validation error: User.name has invalid return type"));
+}
+
+#[test]
+fn schema_can_be_checked_without_resolvers() {
+    let schema = parse_schema("
+    type Root {
+        me: User
+        other: Missing
+    }
+
+    type User {
+        name: String
+    }
+    ").unwrap();
+
+    let err = check_schema(&schema).unwrap_err();
+    let diagnostics = match &err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    // the missing resolvers for me and other aren't reported, only the undefined type
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(&*diagnostics[0].path, "Root.other");
+}
+
+#[test]
+fn variants_named_like_missing_values_fail() {
+    let schema = parse_schema("
+    type Root {
+        shape: Shape
+    }
+
+    enum Shape {
+        Circle
+        None
+        null
+    }
+    ").unwrap();
+
+    let err = check_schema(&schema).unwrap_err();
+    let diagnostics = match &err {
+        CastleError::Multiple(diagnostics) => diagnostics,
+        err => panic!("expected multiple errors, got {:?}", err),
+    };
+    let mut paths: Vec<&str> = diagnostics.iter().map(|diagnostic| &*diagnostic.path).collect();
+    paths.sort();
+    assert_eq!(paths, vec!["Shape.None", "Shape.null"]);
+}
@@ -0,0 +1,67 @@
+use std::{fs, path::{Path, PathBuf}};
+
+use castle_api::{check_message, check_schema};
+use castle_error::{CastleError, ExtendedErrorDisplay};
+use castle_query_parser::parse_message_recovering;
+use castle_schema_parser::{parsers::parse_schema::parse_schema_recovering, types::SchemaDefinition};
+
+/// Parses and validates each schema file, reporting every problem found.
+/// Returns whether every schema is valid.
+pub(crate) fn run_schemas(files: &[PathBuf]) -> bool {
+    let mut succeeded = true;
+    for file in files {
+        succeeded &= load_schema(file).is_some();
+    }
+    succeeded
+}
+
+/// Parses and validates each message file against the schema, reporting every problem found.
+/// Returns whether the schema and every message are valid.
+pub(crate) fn run_messages(schema_file: &Path, files: &[PathBuf]) -> bool {
+    let schema = match load_schema(schema_file) {
+        Some(schema) => schema,
+        None => return false,
+    };
+    let mut succeeded = true;
+    for file in files {
+        let checked = read(file).map(|src| {
+            let (message, mut errors) = parse_message_recovering(&src);
+            // a message is only validated if it parses
+            if errors.is_empty() {
+                errors.extend(check_message(&schema, &message).err());
+            }
+            report(file, &src, &errors)
+        });
+        succeeded &= checked == Some(true);
+    }
+    succeeded
+}
+
+/// Reads, parses and validates a schema, reporting any problems with it
+fn load_schema(file: &Path) -> Option<SchemaDefinition> {
+    let src = read(file)?;
+    let (schema, mut errors) = parse_schema_recovering(&src);
+    // a schema is only validated if it parses, otherwise missing definitions would be reported too
+    if errors.is_empty() {
+        errors.extend(check_schema(&schema).err());
+    }
+    report(file, &src, &errors).then_some(schema)
+}
+
+fn read(file: &Path) -> Option<String> {
+    match fs::read_to_string(file) {
+        Ok(src) => Some(src),
+        Err(e) => {
+            eprintln!("{}: {}", file.display(), e);
+            None
+        },
+    }
+}
+
+/// Prints the errors found in the file, returns whether there weren't any
+fn report(file: &Path, src: &str, errors: &[CastleError]) -> bool {
+    for error in errors {
+        eprintln!("{}: {}", file.display(), error.extended_error(src));
+    }
+    errors.is_empty()
+}
@@ -0,0 +1,38 @@
+use std::{fs, path::PathBuf};
+
+use castle_error::ExtendedErrorDisplay;
+
+/// Formats each file in place, or with `check` only reports the files that aren't formatted.
+/// Returns whether every file was (or already is) formatted.
+pub(crate) fn run(files: &[PathBuf], check: bool) -> bool {
+    let mut succeeded = true;
+    for file in files {
+        let src = match fs::read_to_string(file) {
+            Ok(src) => src,
+            Err(e) => {
+                eprintln!("{}: {}", file.display(), e);
+                succeeded = false;
+                continue
+            },
+        };
+        let formatted = match castle_fmt::format(&src) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprintln!("{}: {}", file.display(), e.extended_error(&src));
+                succeeded = false;
+                continue
+            },
+        };
+        if formatted == src {
+            continue
+        }
+        if check {
+            println!("{} is not formatted", file.display());
+            succeeded = false;
+        } else if let Err(e) = fs::write(file, formatted) {
+            eprintln!("{}: {}", file.display(), e);
+            succeeded = false;
+        }
+    }
+    succeeded
+}
@@ -0,0 +1,54 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::{Parser, Subcommand};
+
+mod check;
+mod fmt;
+
+#[derive(Parser)]
+#[command(
+    name = "castle",
+    about = "Tools for Castle schemas and messages",
+    after_help = "Exits with 0 on success, 1 if any file has a problem and 2 if the arguments are invalid",
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Formats schema and message files in place
+    Fmt {
+        /// Only check the files are formatted, exits with 1 if any of them aren't
+        #[arg(long)]
+        check: bool,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Parses and validates schema files
+    CheckSchema {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Parses and validates message files against a schema
+    CheckMessage {
+        /// The schema the messages are validated against
+        #[arg(long)]
+        schema: PathBuf,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+fn main() -> ExitCode {
+    let succeeded = match Cli::parse().command {
+        Command::Fmt { check, files } => fmt::run(&files, check),
+        Command::CheckSchema { files } => check::run_schemas(&files),
+        Command::CheckMessage { schema, files } => check::run_messages(&schema, &files),
+    };
+    match succeeded {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,154 @@
+use castle_input_cursor::{Position, Span};
+use castle_tokenizer::{Token, TokenKind, Tokenizable, Tokenizer};
+
+use crate::{SyntaxKind, SyntaxToken};
+
+/// Splits the source into tokens without losing any of it, the tokens' texts joined together
+/// are the source.
+///
+/// The tokens the parsers use come from the tokenizer, everything between them is trivia:
+/// whitespace, newlines, comments and characters the tokenizer couldn't tokenize.
+pub(crate) fn lex(src: &str) -> Vec<SyntaxToken> {
+    let offsets = Offsets::new(src);
+    let mut lexer = Lexer {
+        src,
+        tokens: Vec::new(),
+        offset: 0,
+        pos: Position::default(),
+    };
+    let mut tokenizer = Tokenizer::recovering(src.as_bytes());
+    while let Ok(Some(token)) = tokenizer.next(true) {
+        let start = offsets.offset(token.span.start).max(lexer.offset);
+        let end = offsets.offset(token.span.end).max(start);
+        lexer.trivia(start);
+        lexer.push(kind(&token), end);
+    }
+    lexer.trivia(src.len());
+    lexer.tokens
+}
+
+fn kind(token: &Token) -> SyntaxKind {
+    match &token.kind {
+        TokenKind::Identifier(_) => SyntaxKind::Identifier,
+        TokenKind::Keyword(keyword) => SyntaxKind::Keyword(*keyword),
+        TokenKind::Punctuator(punctuator) => SyntaxKind::Punctuator(*punctuator),
+        TokenKind::Primitive(_) => SyntaxKind::Primitive,
+        TokenKind::LineTerminator => SyntaxKind::Newline,
+        TokenKind::Comment(_) => SyntaxKind::Comment,
+    }
+}
+
+struct Lexer<'a> {
+    src: &'a str,
+    tokens: Vec<SyntaxToken>,
+    /// The byte offset of the end of the last token
+    offset: usize,
+    pos: Position,
+}
+
+impl Lexer<'_> {
+    /// Pushes the source up to `end` as a token
+    fn push(&mut self, kind: SyntaxKind, end: usize) {
+        let text = &self.src[self.offset..end];
+        let start = self.pos;
+        self.pos = advance(start, text);
+        self.offset = end;
+        self.tokens.push(SyntaxToken {
+            kind,
+            text: text.into(),
+            span: Span::new(start, self.pos),
+        });
+    }
+
+    /// Pushes the trivia up to `end`
+    fn trivia(&mut self, end: usize) {
+        while self.offset < end {
+            let rest = &self.src[self.offset..end];
+            let (kind, len) = match rest.chars().next() {
+                Some('\r') if rest.starts_with("\r\n") => (SyntaxKind::Newline, 2),
+                Some(c) if is_newline(c) => (SyntaxKind::Newline, c.len_utf8()),
+                Some('#') => (SyntaxKind::Comment, rest.find(['\r', '\n']).unwrap_or(rest.len())),
+                Some('/') if is_slash_comment(rest) => (SyntaxKind::Comment, rest.find(['\r', '\n']).unwrap_or(rest.len())),
+                Some(c) if c.is_whitespace() => (SyntaxKind::Whitespace, run(rest, |c| c.is_whitespace() && !is_newline(c))),
+                _ => (SyntaxKind::Unknown, unknown(rest)),
+            };
+            self.push(kind, self.offset + len);
+        }
+    }
+}
+
+/// The length of the characters at the start of `text` up to whitespace or a comment
+fn unknown(text: &str) -> usize {
+    text.char_indices()
+        .skip(1)
+        .find(|(i, c)| c.is_whitespace() || *c == '#' || is_slash_comment(&text[*i..]))
+        .map_or(text.len(), |(i, _)| i)
+}
+
+/// Whether `text` starts with a `///` doc comment, which like the tokenizer's is followed by a
+/// space or the end of the line
+fn is_slash_comment(text: &str) -> bool {
+    text.starts_with("///") && matches!(text.as_bytes().get(3), None | Some(b' ' | b'\t' | b'\r' | b'\n'))
+}
+
+/// The length of the characters at the start of `text` matching `pred`
+fn run(text: &str, pred: impl Fn(char) -> bool) -> usize {
+    text.find(|c| !pred(c)).unwrap_or(text.len())
+}
+
+fn is_newline(c: char) -> bool {
+    matches!(c, '\r' | '\n' | '\u{2028}' | '\u{2029}')
+}
+
+/// The position after `text` when it starts at `pos`, lines are counted the same way as the
+/// tokenizer's cursor counts them
+fn advance(pos: Position, text: &str) -> Position {
+    let mut chars = text.chars().peekable();
+    let (mut line, mut column) = (pos.line_number(), pos.column_number());
+    while let Some(c) = chars.next() {
+        if c == '\r' {
+            chars.next_if_eq(&'\n');
+        }
+        match is_newline(c) {
+            true => (line, column) = (line + 1, 1),
+            false => column += 1,
+        }
+    }
+    Position::new(line, column)
+}
+
+/// Finds the byte offsets of the tokenizer's positions
+struct Offsets<'a> {
+    src: &'a str,
+    /// The byte offset of the start of each line
+    lines: Vec<usize>,
+}
+
+impl<'a> Offsets<'a> {
+    fn new(src: &'a str) -> Self {
+        let mut lines = vec![0];
+        let mut chars = src.char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            let mut end = offset + c.len_utf8();
+            if c == '\r' && chars.next_if(|(_, c)| *c == '\n').is_some() {
+                end += 1;
+            }
+            if is_newline(c) {
+                lines.push(end);
+            }
+        }
+        Offsets { src, lines }
+    }
+
+    fn offset(&self, pos: Position) -> usize {
+        let line_start = match self.lines.get(pos.line_number() as usize - 1) {
+            Some(line_start) => *line_start,
+            None => return self.src.len(),
+        };
+        self.src[line_start..]
+            .char_indices()
+            .map(|(offset, _)| line_start + offset)
+            .nth(pos.column_number() as usize - 1)
+            .unwrap_or(self.src.len())
+    }
+}
@@ -0,0 +1,44 @@
+//! Lossless concrete syntax trees for Castle schemas and messages, for tools that rewrite
+//! source without losing its formatting (eg: formatters, codemods and refactorings).
+//!
+//! Every character of the source is in the tree, so printing a tree gives back its source
+//! exactly. The tokens the parsers use come from [castle_tokenizer], and everything between
+//! them is kept as trivia tokens: whitespace, newlines, `#` comments and characters that
+//! couldn't be tokenized.
+//!
+//! Trees are built for any source, valid or not. Tokens that don't fit the grammar are put
+//! in [NodeKind::Error] nodes, and parts that are missing are left out.
+//!
+//! Typed nodes (eg: [TypeDefinitionNode]) give access to the parts of a node, and find what a
+//! node parsed to in the [SchemaDefinition](castle_schema_parser::types::SchemaDefinition)
+//! or [Message](castle_query_parser::Message) of the same source:
+//!
+//! ```
+//! use castle_cst::{parse_schema_cst, SchemaNode};
+//!
+//! let tree = parse_schema_cst("type User {\n    name: String # shown to others\n}\n");
+//! let schema = SchemaNode::cast(&tree).unwrap();
+//! let definition = schema.definition().unwrap();
+//! let user = schema.types().next().unwrap();
+//! let name = user.fields().next().unwrap();
+//! assert_eq!(name.definition(user.definition(&definition).unwrap()).unwrap().return_kind.ident, "String".into());
+//! assert_eq!(tree.to_string(), "type User {\n    name: String # shown to others\n}\n");
+//! ```
+//!
+//! Trees can be edited through [SyntaxNode::tokens_mut] and printed with [SyntaxNode::text].
+
+mod lexer;
+mod nodes;
+mod parsers;
+mod syntax;
+
+pub use nodes::{
+    message::{ArmNode, FieldNode, MessageNode, ProjectionNode, VariableDefinitionNode},
+    schema::{
+        DirectiveDefinitionNode, EnumDefinitionNode, FieldDefinitionNode, InputDefinitionNode,
+        InputTypeDefinitionNode, SchemaNode, TypeDefinitionNode, VariantDefinitionNode,
+    },
+    DirectiveNode, InputNode, KindNode, ValueNode,
+};
+pub use parsers::{parse_message::parse_message_cst, parse_schema::parse_schema_cst};
+pub use syntax::{NodeKind, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use castle_error::CastleError;
+use castle_query_parser::{parse_message, Field, Message, Projection, VariableDefinition, VariableDefinitions};
+use castle_tokenizer::Keyword;
+
+use crate::{NodeKind, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use super::{child, children, children_in, identifier, InputNode, KindNode, ValueNode};
+
+typed_node!(
+    /// The root of a message's tree
+    MessageNode,
+    Message
+);
+
+impl<'a> MessageNode<'a> {
+    /// The name after `message`, if there is one
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn variable_definitions(&self) -> impl Iterator<Item = VariableDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::VariableDefinitions, VariableDefinitionNode::cast)
+    }
+
+    /// The `{}` block of the message
+    pub fn projection(&self) -> Option<ProjectionNode<'a>> {
+        children(self.0, ProjectionNode::cast).next()
+    }
+
+    /// Parses the tree's source (including any edits made to the tree)
+    pub fn message(&self) -> Result<Message, CastleError> {
+        parse_message(&self.0.text())
+    }
+}
+
+typed_node!(
+    /// A variable declared by a message, eg: `$size: number = 48`
+    VariableDefinitionNode,
+    VariableDefinition
+);
+
+impl<'a> VariableDefinitionNode<'a> {
+    /// The name after the `$`
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn kind(&self) -> Option<KindNode<'a>> {
+        children(self.0, KindNode::cast).next()
+    }
+
+    /// The value after the `=`
+    pub fn default(&self) -> Option<ValueNode<'a>> {
+        children(self.0, ValueNode::cast).next()
+    }
+
+    /// The variable in the parsed message
+    pub fn definition<'d>(&self, variables: &'d VariableDefinitions) -> Option<&'d VariableDefinition> {
+        variables.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// The `{}` or `[]` block of fields projected from an object or a list
+    ProjectionNode,
+    Projection
+);
+
+impl<'a> ProjectionNode<'a> {
+    pub fn fields(&self) -> impl Iterator<Item = FieldNode<'a>> {
+        children(self.0, FieldNode::cast)
+    }
+}
+
+typed_node!(
+    /// A projected field, eg: `profile_pic(size: 48) as avatar`
+    FieldNode,
+    Field
+);
+
+impl<'a> FieldNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    /// The name after `as`
+    pub fn rename(&self) -> Option<&'a SyntaxToken> {
+        self.0
+            .child_tokens()
+            .skip_while(|token| token.kind != SyntaxKind::Keyword(Keyword::As))
+            .find(|token| token.kind == SyntaxKind::Identifier)
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = InputNode<'a>> {
+        children_in(self.0, NodeKind::Inputs, InputNode::cast)
+    }
+
+    /// The `{}` or `[]` block of an object or list field
+    pub fn projection(&self) -> Option<ProjectionNode<'a>> {
+        children(self.0, ProjectionNode::cast).next()
+    }
+
+    /// The arms of a `match` field
+    pub fn arms(&self) -> impl Iterator<Item = ArmNode<'a>> {
+        children_in(self.0, NodeKind::Match, ArmNode::cast)
+    }
+
+    /// The field in the parsed projection of its block, found by its response key (the rename
+    /// if there is one, otherwise the name)
+    pub fn field<'d>(&self, projection: &'d Projection) -> Option<&'d Field> {
+        projection.get(self.rename().or_else(|| self.name())?.text())
+    }
+}
+
+typed_node!(
+    /// An arm of a `match`, eg: `SVGIcon { url }`
+    ArmNode,
+    Arm
+);
+
+impl<'a> ArmNode<'a> {
+    /// The name of the variant
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn projection(&self) -> Option<ProjectionNode<'a>> {
+        child(self.0, NodeKind::Projection).and_then(ProjectionNode::cast)
+    }
+
+    /// The projection of the arm in the parsed arms of its match
+    pub fn arm<'d>(&self, arms: &'d HashMap<Box<str>, Projection>) -> Option<&'d Projection> {
+        arms.get(self.name()?.text())
+    }
+}
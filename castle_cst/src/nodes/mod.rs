@@ -0,0 +1,121 @@
+use castle_schema_parser::types::AppliedDirective;
+
+use crate::{NodeKind, SyntaxKind, SyntaxNode, SyntaxToken};
+
+/// Declares a typed node wrapping a [SyntaxNode] of one [NodeKind]
+macro_rules! typed_node {
+    ($(#[$doc:meta])* $name:ident, $kind:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy)]
+        pub struct $name<'a>(&'a SyntaxNode);
+
+        impl<'a> $name<'a> {
+            #[doc = concat!("The node if it is a [NodeKind::", stringify!($kind), "]")]
+            pub fn cast(node: &'a SyntaxNode) -> Option<Self> {
+                match node.kind {
+                    NodeKind::$kind => Some(Self(node)),
+                    _ => None,
+                }
+            }
+
+            pub fn syntax(&self) -> &'a SyntaxNode {
+                self.0
+            }
+        }
+    };
+}
+
+pub(crate) mod message;
+pub(crate) mod schema;
+
+/// The first child node of `kind`
+fn child(node: &SyntaxNode, kind: NodeKind) -> Option<&SyntaxNode> {
+    node.child_nodes().find(|child| child.kind == kind)
+}
+
+/// The child nodes that can be cast to `N`
+fn children<'a, N: 'a>(node: &'a SyntaxNode, cast: fn(&'a SyntaxNode) -> Option<N>) -> impl Iterator<Item = N> + 'a {
+    node.child_nodes().filter_map(cast)
+}
+
+/// The nodes in the first child node of `kind` that can be cast to `N`, eg: the fields in the
+/// `{}` block of a type
+fn children_in<'a, N: 'a>(
+    node: &'a SyntaxNode,
+    kind: NodeKind,
+    cast: fn(&'a SyntaxNode) -> Option<N>,
+) -> impl Iterator<Item = N> + 'a {
+    child(node, kind).into_iter().flat_map(move |block| children(block, cast))
+}
+
+/// The first identifier directly in the node, the name of most nodes
+fn identifier(node: &SyntaxNode) -> Option<&SyntaxToken> {
+    node.child_tokens().find(|token| token.kind == SyntaxKind::Identifier)
+}
+
+typed_node!(
+    /// A kind with its generics, eg: `Vec<User>`
+    KindNode,
+    Kind
+);
+
+impl<'a> KindNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn generics(&self) -> impl Iterator<Item = KindNode<'a>> {
+        children(self.0, KindNode::cast)
+    }
+}
+
+typed_node!(
+    /// An applied directive, eg: `@cached(seconds: 60)`
+    DirectiveNode,
+    Directive
+);
+
+impl<'a> DirectiveNode<'a> {
+    /// The name after the `@`
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = InputNode<'a>> {
+        children_in(self.0, NodeKind::Inputs, InputNode::cast)
+    }
+
+    /// This directive in the directives of its definition, found by where it is in the source
+    /// since a directive can be applied more than once
+    pub fn applied<'d>(&self, directives: &'d [AppliedDirective]) -> Option<&'d AppliedDirective> {
+        let start = self.0.span()?.start;
+        directives.iter().find(|directive| directive.span.start == start)
+    }
+}
+
+typed_node!(
+    /// An input given to a field or directive, or a field of a map value, eg: `size: 48`
+    InputNode,
+    Input
+);
+
+impl<'a> InputNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn value(&self) -> Option<ValueNode<'a>> {
+        children(self.0, ValueNode::cast).next()
+    }
+}
+
+typed_node!(
+    /// A value, eg: `48`, `[1, 2]`, `Emoji("🏰")`, `{ x: 1 }` or `$size`
+    ValueNode,
+    Value
+);
+
+/// The applied directives in the node
+fn directives(node: &SyntaxNode) -> impl Iterator<Item = DirectiveNode<'_>> {
+    children(node, DirectiveNode::cast)
+}
@@ -0,0 +1,236 @@
+use castle_error::CastleError;
+use castle_schema_parser::{
+    parsers::parse_schema::parse_schema,
+    types::{
+        DirectiveDefinition, EnumDefinition, FieldDefinition, InputDefinition, InputDefinitions,
+        InputTypeDefinition, SchemaDefinition, TypeDefinition, VariantDefinition,
+    },
+};
+
+use crate::{NodeKind, SyntaxKind, SyntaxNode, SyntaxToken};
+
+use super::{child, children, children_in, directives, identifier, DirectiveNode, KindNode, ValueNode};
+
+typed_node!(
+    /// The root of a schema's tree
+    SchemaNode,
+    Schema
+);
+
+impl<'a> SchemaNode<'a> {
+    pub fn types(&self) -> impl Iterator<Item = TypeDefinitionNode<'a>> {
+        children(self.0, TypeDefinitionNode::cast)
+    }
+
+    pub fn enums(&self) -> impl Iterator<Item = EnumDefinitionNode<'a>> {
+        children(self.0, EnumDefinitionNode::cast)
+    }
+
+    pub fn input_types(&self) -> impl Iterator<Item = InputTypeDefinitionNode<'a>> {
+        children(self.0, InputTypeDefinitionNode::cast)
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveDefinitionNode<'a>> {
+        children(self.0, DirectiveDefinitionNode::cast)
+    }
+
+    /// Parses the tree's source (including any edits made to the tree)
+    pub fn definition(&self) -> Result<SchemaDefinition, CastleError> {
+        parse_schema(&self.0.text())
+    }
+}
+
+typed_node!(
+    /// `type Name { ... }` with the directives before it
+    TypeDefinitionNode,
+    TypeDefinition
+);
+
+impl<'a> TypeDefinitionNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveNode<'a>> {
+        directives(self.0)
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = FieldDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::FieldDefinitions, FieldDefinitionNode::cast)
+    }
+
+    /// The type in the parsed schema
+    pub fn definition<'d>(&self, schema: &'d SchemaDefinition) -> Option<&'d TypeDefinition> {
+        schema.types.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// A field of a type, or of a map variant, eg: `avatar(size: number): String @lowercase`
+    FieldDefinitionNode,
+    FieldDefinition
+);
+
+impl<'a> FieldDefinitionNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn input_definitions(&self) -> impl Iterator<Item = InputDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::InputDefinitions, InputDefinitionNode::cast)
+    }
+
+    pub fn return_kind(&self) -> Option<KindNode<'a>> {
+        children(self.0, KindNode::cast).next()
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveNode<'a>> {
+        directives(self.0)
+    }
+
+    /// The field in its parsed type, for the fields of a map variant use
+    /// [VariantDefinition::map_type_definition]
+    pub fn definition<'d>(&self, type_definition: &'d TypeDefinition) -> Option<&'d FieldDefinition> {
+        type_definition.fields.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// `enum Name { ... }` with the directives before it
+    EnumDefinitionNode,
+    EnumDefinition
+);
+
+impl<'a> EnumDefinitionNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveNode<'a>> {
+        directives(self.0)
+    }
+
+    pub fn variants(&self) -> impl Iterator<Item = VariantDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::VariantDefinitions, VariantDefinitionNode::cast)
+    }
+
+    /// The enum in the parsed schema
+    pub fn definition<'d>(&self, schema: &'d SchemaDefinition) -> Option<&'d EnumDefinition> {
+        schema.enums.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// A variant of an enum, eg: `NoIcon`, `Emoji(String)` or `SVGIcon { url: String }`
+    VariantDefinitionNode,
+    VariantDefinition
+);
+
+impl<'a> VariantDefinitionNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    /// The fields of a map variant
+    pub fn fields(&self) -> impl Iterator<Item = FieldDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::FieldDefinitions, FieldDefinitionNode::cast)
+    }
+
+    /// The kinds of a tuple variant
+    pub fn kinds(&self) -> impl Iterator<Item = KindNode<'a>> {
+        children_in(self.0, NodeKind::Kinds, KindNode::cast)
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveNode<'a>> {
+        directives(self.0)
+    }
+
+    /// The variant in its parsed enum
+    pub fn definition<'d>(&self, enum_definition: &'d EnumDefinition) -> Option<&'d VariantDefinition> {
+        enum_definition.variants.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// `input Name { ... }` with the directives before it
+    InputTypeDefinitionNode,
+    InputTypeDefinition
+);
+
+impl<'a> InputTypeDefinitionNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveNode<'a>> {
+        directives(self.0)
+    }
+
+    pub fn input_definitions(&self) -> impl Iterator<Item = InputDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::InputDefinitions, InputDefinitionNode::cast)
+    }
+
+    /// The input type in the parsed schema
+    pub fn definition<'d>(&self, schema: &'d SchemaDefinition) -> Option<&'d InputTypeDefinition> {
+        schema.input_types.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// An input of a field, directive or input type, eg: `size: number = 48`
+    InputDefinitionNode,
+    InputDefinition
+);
+
+impl<'a> InputDefinitionNode<'a> {
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn input_kind(&self) -> Option<KindNode<'a>> {
+        children(self.0, KindNode::cast).next()
+    }
+
+    /// The value after the `=`
+    pub fn default(&self) -> Option<ValueNode<'a>> {
+        children(self.0, ValueNode::cast).next()
+    }
+
+    pub fn directives(&self) -> impl Iterator<Item = DirectiveNode<'a>> {
+        directives(self.0)
+    }
+
+    /// The input in the parsed input definitions of its field, directive or input type
+    pub fn definition<'d>(&self, input_definitions: &'d InputDefinitions) -> Option<&'d InputDefinition> {
+        input_definitions.get(self.name()?.text())
+    }
+}
+
+typed_node!(
+    /// `directive @name(inputs) on Location | Location`
+    DirectiveDefinitionNode,
+    DirectiveDefinition
+);
+
+impl<'a> DirectiveDefinitionNode<'a> {
+    /// The name after the `@`
+    pub fn name(&self) -> Option<&'a SyntaxToken> {
+        identifier(self.0)
+    }
+
+    pub fn input_definitions(&self) -> impl Iterator<Item = InputDefinitionNode<'a>> {
+        children_in(self.0, NodeKind::InputDefinitions, InputDefinitionNode::cast)
+    }
+
+    /// The names of the locations after `on`
+    pub fn locations(&self) -> impl Iterator<Item = &'a SyntaxToken> {
+        child(self.0, NodeKind::DirectiveLocations)
+            .into_iter()
+            .flat_map(|locations| locations.child_tokens().filter(|token| token.kind == SyntaxKind::Identifier).skip(1))
+    }
+
+    /// The directive in the parsed schema
+    pub fn definition<'d>(&self, schema: &'d SchemaDefinition) -> Option<&'d DirectiveDefinition> {
+        schema.directives.get(self.name()?.text())
+    }
+}
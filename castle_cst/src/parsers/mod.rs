@@ -0,0 +1,183 @@
+pub(crate) mod parse_message;
+pub(crate) mod parse_schema;
+mod parse_shared;
+
+use castle_tokenizer::{Keyword, Punctuator};
+
+use crate::{NodeKind, SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+
+/// Builds a tree from the tokens of the source.
+///
+/// The grammar is the same as the schema and message parsers', but nothing is an error here:
+/// tokens that don't fit are wrapped in [NodeKind::Error] nodes and missing tokens are left
+/// out, so every token ends up in the tree.
+pub(crate) struct Builder {
+    /// The tokens in reverse, so the next token is popped off the end
+    tokens: Vec<SyntaxToken>,
+    /// The nodes being built, the root is first
+    stack: Vec<SyntaxNode>,
+}
+
+impl Builder {
+    pub fn new(mut tokens: Vec<SyntaxToken>, root: NodeKind) -> Self {
+        tokens.reverse();
+        Builder {
+            tokens,
+            stack: vec![SyntaxNode::new(root)],
+        }
+    }
+
+    /// Finishes the root node, trivia at the end of the source is put in the root
+    pub fn finish_root(mut self) -> SyntaxNode {
+        self.trivia();
+        while self.stack.len() > 1 {
+            self.finish();
+        }
+        self.stack.pop().unwrap()
+    }
+
+    /// The kind of the next token that isn't trivia, `None` at the end of the source
+    pub fn peek(&self) -> Option<SyntaxKind> {
+        self.tokens.iter().rev().map(|token| token.kind).find(|kind| !kind.is_trivia())
+    }
+
+    pub fn at(&self, kind: SyntaxKind) -> bool {
+        self.peek() == Some(kind)
+    }
+
+    pub fn at_punctuator(&self, punctuator: Punctuator) -> bool {
+        self.at(SyntaxKind::Punctuator(punctuator))
+    }
+
+    /// Whether the next token that isn't trivia is `kind` and on the same line, for the parts
+    /// of the grammar that can't be on the next line (eg: the block of a field in a message)
+    pub fn at_on_line(&self, kind: SyntaxKind) -> bool {
+        self.tokens
+            .iter()
+            .rev()
+            .find(|token| !matches!(token.kind, SyntaxKind::Whitespace | SyntaxKind::Comment | SyntaxKind::Unknown))
+            .is_some_and(|token| token.kind == kind)
+    }
+
+    /// Whether the next token is the identifier `text`, eg: `on` in directive definitions
+    pub fn at_identifier(&self, text: &str) -> bool {
+        self.tokens
+            .iter()
+            .rev()
+            .find(|token| !token.is_trivia())
+            .is_some_and(|token| token.kind == SyntaxKind::Identifier && &*token.text == text)
+    }
+
+    /// Adds the next token (and the trivia before it) to the current node
+    pub fn bump(&mut self) {
+        self.trivia();
+        if let Some(token) = self.tokens.pop() {
+            self.push(SyntaxElement::Token(token));
+        }
+    }
+
+    /// Bumps the next token if it is `kind`
+    pub fn eat(&mut self, kind: SyntaxKind) -> bool {
+        match self.at(kind) {
+            true => {
+                self.bump();
+                true
+            },
+            false => false,
+        }
+    }
+
+    pub fn eat_punctuator(&mut self, punctuator: Punctuator) -> bool {
+        self.eat(SyntaxKind::Punctuator(punctuator))
+    }
+
+    /// Starts a node, the trivia before it is left in the current node
+    pub fn start(&mut self, kind: NodeKind) {
+        self.trivia();
+        self.stack.push(SyntaxNode::new(kind));
+    }
+
+    /// Where a node that starts here could be started later on, with [Builder::start_at], once
+    /// its kind is known (eg: directives before a type definition)
+    pub fn checkpoint(&mut self) -> usize {
+        self.trivia();
+        self.current().children.len()
+    }
+
+    /// Starts a node holding the children of the current node from `checkpoint`
+    pub fn start_at(&mut self, checkpoint: usize, kind: NodeKind) {
+        let mut node = SyntaxNode::new(kind);
+        node.children = self.current().children.split_off(checkpoint);
+        self.stack.push(node);
+    }
+
+    /// Finishes the current node, adding it to its parent
+    pub fn finish(&mut self) {
+        let node = self.stack.pop().unwrap();
+        self.push(SyntaxElement::Node(node));
+    }
+
+    /// Wraps the next token in an error node
+    pub fn error(&mut self) {
+        self.start(NodeKind::Error);
+        self.bump();
+        self.finish();
+    }
+
+    fn current(&mut self) -> &mut SyntaxNode {
+        self.stack.last_mut().unwrap()
+    }
+
+    fn push(&mut self, element: SyntaxElement) {
+        self.current().children.push(element);
+    }
+
+    /// Adds the trivia before the next token to the current node
+    fn trivia(&mut self) {
+        while self.tokens.last().is_some_and(|token| token.is_trivia()) {
+            let token = self.tokens.pop().unwrap();
+            self.push(SyntaxElement::Token(token));
+        }
+    }
+
+    /// Parses a list of items up to `closing` (which is bumped), separated by `,` or newlines.
+    /// Anything that doesn't start an item is an error.
+    ///
+    /// The list ends early before a definition keyword, or before a `}` or `]` that isn't
+    /// `closing`, so an unclosed block doesn't take in the rest of the source.
+    pub fn list(&mut self, closing: Punctuator, starts_item: fn(SyntaxKind) -> bool, item: fn(&mut Builder)) {
+        loop {
+            match self.peek() {
+                None => return,
+                Some(SyntaxKind::Punctuator(punctuator)) if punctuator == closing => {
+                    self.bump();
+                    return;
+                },
+                Some(SyntaxKind::Punctuator(Punctuator::Comma)) => self.bump(),
+                Some(kind) if starts_item(kind) => item(self),
+                Some(SyntaxKind::Keyword(Keyword::Type | Keyword::Enum | Keyword::Input | Keyword::Directive | Keyword::Message))
+                | Some(SyntaxKind::Punctuator(Punctuator::CloseBlock | Punctuator::CloseBracket)) => return,
+                Some(_) => self.error(),
+            }
+        }
+    }
+
+    /// Parses a node of `kind` that is a `opening` bracket, a list and its `closing` bracket
+    pub fn block(
+        &mut self,
+        kind: NodeKind,
+        opening: Punctuator,
+        closing: Punctuator,
+        starts_item: fn(SyntaxKind) -> bool,
+        item: fn(&mut Builder),
+    ) {
+        self.start(kind);
+        self.eat_punctuator(opening);
+        self.list(closing, starts_item, item);
+        self.finish();
+    }
+}
+
+fn is_identifier(kind: SyntaxKind) -> bool {
+    kind == SyntaxKind::Identifier
+}
@@ -0,0 +1,91 @@
+use castle_tokenizer::{Keyword, Punctuator};
+
+use crate::{lexer::lex, NodeKind, SyntaxKind, SyntaxNode};
+
+use super::{
+    is_identifier,
+    parse_shared::{parse_inputs, parse_kind, parse_value},
+    Builder,
+};
+
+/// Parses a message into a lossless tree, see [crate] for how it relates to
+/// [Message](castle_query_parser::Message)
+pub fn parse_message_cst(msg: &str) -> SyntaxNode {
+    let mut builder = Builder::new(lex(msg), NodeKind::Message);
+    if builder.eat(SyntaxKind::Keyword(Keyword::Message)) {
+        builder.eat(SyntaxKind::Identifier);
+        if builder.at_punctuator(Punctuator::OpenParen) {
+            builder.block(
+                NodeKind::VariableDefinitions,
+                Punctuator::OpenParen,
+                Punctuator::CloseParen,
+                |kind| kind == SyntaxKind::Punctuator(Punctuator::Dollar),
+                parse_variable_definition,
+            );
+        }
+        if builder.at_punctuator(Punctuator::OpenBlock) {
+            parse_projection(&mut builder, Punctuator::OpenBlock, Punctuator::CloseBlock);
+        }
+    }
+    while builder.peek().is_some() {
+        builder.error();
+    }
+    builder.finish_root()
+}
+
+/// `$name: Kind = default`
+fn parse_variable_definition(builder: &mut Builder) {
+    builder.start(NodeKind::VariableDefinition);
+    builder.bump();
+    if builder.at_on_line(SyntaxKind::Identifier) {
+        builder.bump();
+    }
+    if builder.eat_punctuator(Punctuator::Colon) {
+        parse_kind(builder);
+    }
+    if builder.eat_punctuator(Punctuator::Default) {
+        parse_value(builder);
+    }
+    builder.finish();
+}
+
+/// The `{}` or `[]` block of fields
+fn parse_projection(builder: &mut Builder, opening: Punctuator, closing: Punctuator) {
+    builder.block(NodeKind::Projection, opening, closing, is_identifier, parse_field);
+}
+
+/// `name(inputs) as rename` followed by a projection or a match on the same line
+fn parse_field(builder: &mut Builder) {
+    builder.start(NodeKind::Field);
+    builder.bump();
+    if builder.at_punctuator(Punctuator::OpenParen) {
+        parse_inputs(builder, Punctuator::OpenParen, Punctuator::CloseParen);
+    }
+    if builder.at_on_line(SyntaxKind::Keyword(Keyword::As)) {
+        builder.bump();
+        builder.eat(SyntaxKind::Identifier);
+    }
+    if builder.at_on_line(SyntaxKind::Punctuator(Punctuator::OpenBlock)) {
+        parse_projection(builder, Punctuator::OpenBlock, Punctuator::CloseBlock);
+    } else if builder.at_on_line(SyntaxKind::Punctuator(Punctuator::OpenBracket)) {
+        parse_projection(builder, Punctuator::OpenBracket, Punctuator::CloseBracket);
+    } else if builder.at_on_line(SyntaxKind::Keyword(Keyword::Match)) {
+        builder.start(NodeKind::Match);
+        builder.bump();
+        if builder.eat_punctuator(Punctuator::OpenBlock) {
+            builder.list(Punctuator::CloseBlock, is_identifier, parse_arm);
+        }
+        builder.finish();
+    }
+    builder.finish();
+}
+
+/// A variant name with a projection of its fields on the same line
+fn parse_arm(builder: &mut Builder) {
+    builder.start(NodeKind::Arm);
+    builder.bump();
+    if builder.at_on_line(SyntaxKind::Punctuator(Punctuator::OpenBlock)) {
+        parse_projection(builder, Punctuator::OpenBlock, Punctuator::CloseBlock);
+    }
+    builder.finish();
+}
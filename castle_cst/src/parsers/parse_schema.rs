@@ -0,0 +1,131 @@
+use castle_tokenizer::{Keyword, Punctuator};
+
+use crate::{lexer::lex, NodeKind, SyntaxKind, SyntaxNode};
+
+use super::{
+    is_identifier,
+    parse_shared::{parse_directives, parse_kind, parse_value},
+    Builder,
+};
+
+/// Parses a schema into a lossless tree, see [crate] for how it relates to
+/// [SchemaDefinition](castle_schema_parser::types::SchemaDefinition)
+pub fn parse_schema_cst(schema: &str) -> SyntaxNode {
+    let mut builder = Builder::new(lex(schema), NodeKind::Schema);
+    while builder.peek().is_some() {
+        parse_item(&mut builder);
+    }
+    builder.finish_root()
+}
+
+/// A definition with the directives before it, or an error
+fn parse_item(builder: &mut Builder) {
+    let checkpoint = builder.checkpoint();
+    parse_directives(builder);
+    let kind = match builder.peek() {
+        Some(SyntaxKind::Keyword(Keyword::Type)) => NodeKind::TypeDefinition,
+        Some(SyntaxKind::Keyword(Keyword::Enum)) => NodeKind::EnumDefinition,
+        Some(SyntaxKind::Keyword(Keyword::Input)) => NodeKind::InputTypeDefinition,
+        Some(SyntaxKind::Keyword(Keyword::Directive)) => NodeKind::DirectiveDefinition,
+        // directives at the end of the schema
+        None => return,
+        // directives followed by something else, it's an error on the next item
+        Some(_) if builder.checkpoint() != checkpoint => return,
+        Some(_) => return builder.error(),
+    };
+    builder.start_at(checkpoint, kind);
+    builder.bump();
+    match kind {
+        NodeKind::DirectiveDefinition => parse_directive_definition(builder),
+        _ => {
+            builder.eat(SyntaxKind::Identifier);
+            match kind {
+                NodeKind::TypeDefinition => parse_field_definitions(builder),
+                NodeKind::EnumDefinition => builder.block(
+                    NodeKind::VariantDefinitions,
+                    Punctuator::OpenBlock,
+                    Punctuator::CloseBlock,
+                    is_identifier,
+                    parse_variant_definition,
+                ),
+                _ => parse_input_definitions(builder, Punctuator::OpenBlock, Punctuator::CloseBlock),
+            }
+        },
+    }
+    builder.finish();
+}
+
+/// `@name(inputs) on Location | Location`, after the `directive` keyword
+fn parse_directive_definition(builder: &mut Builder) {
+    builder.eat_punctuator(Punctuator::At);
+    if builder.at_on_line(SyntaxKind::Identifier) {
+        builder.bump();
+    }
+    if builder.at_punctuator(Punctuator::OpenParen) {
+        parse_input_definitions(builder, Punctuator::OpenParen, Punctuator::CloseParen);
+    }
+    if builder.at_identifier("on") {
+        builder.start(NodeKind::DirectiveLocations);
+        builder.bump();
+        while builder.eat(SyntaxKind::Identifier) && builder.eat_punctuator(Punctuator::Or) {}
+        builder.finish();
+    }
+}
+
+/// The `{}` block of a type or a map variant
+fn parse_field_definitions(builder: &mut Builder) {
+    builder.block(
+        NodeKind::FieldDefinitions,
+        Punctuator::OpenBlock,
+        Punctuator::CloseBlock,
+        is_identifier,
+        parse_field_definition,
+    );
+}
+
+/// `name(inputs): Kind @directives`
+fn parse_field_definition(builder: &mut Builder) {
+    builder.start(NodeKind::FieldDefinition);
+    builder.bump();
+    if builder.at_punctuator(Punctuator::OpenParen) {
+        parse_input_definitions(builder, Punctuator::OpenParen, Punctuator::CloseParen);
+    }
+    if builder.eat_punctuator(Punctuator::Colon) {
+        parse_kind(builder);
+    }
+    parse_directives(builder);
+    builder.finish();
+}
+
+/// `Name`, `Name(Kind, Kind)` or `Name { field: Kind }` with directives
+fn parse_variant_definition(builder: &mut Builder) {
+    builder.start(NodeKind::VariantDefinition);
+    builder.bump();
+    match builder.peek() {
+        Some(SyntaxKind::Punctuator(Punctuator::OpenBlock)) => parse_field_definitions(builder),
+        Some(SyntaxKind::Punctuator(Punctuator::OpenParen)) => {
+            builder.block(NodeKind::Kinds, Punctuator::OpenParen, Punctuator::CloseParen, is_identifier, parse_kind)
+        },
+        _ => {},
+    }
+    parse_directives(builder);
+    builder.finish();
+}
+
+fn parse_input_definitions(builder: &mut Builder, opening: Punctuator, closing: Punctuator) {
+    builder.block(NodeKind::InputDefinitions, opening, closing, is_identifier, parse_input_definition);
+}
+
+/// `name: Kind = default @directives`
+fn parse_input_definition(builder: &mut Builder) {
+    builder.start(NodeKind::InputDefinition);
+    builder.bump();
+    if builder.eat_punctuator(Punctuator::Colon) {
+        parse_kind(builder);
+    }
+    if builder.eat_punctuator(Punctuator::Default) {
+        parse_value(builder);
+    }
+    parse_directives(builder);
+    builder.finish();
+}
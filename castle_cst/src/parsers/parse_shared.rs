@@ -0,0 +1,103 @@
+use castle_tokenizer::Punctuator;
+
+use crate::{NodeKind, SyntaxKind};
+
+use super::{is_identifier, Builder};
+
+/// A kind with its generics, eg: `HashMap<String, Vec<User>>`
+pub(crate) fn parse_kind(builder: &mut Builder) {
+    if !builder.at(SyntaxKind::Identifier) {
+        return;
+    }
+    builder.start(NodeKind::Kind);
+    builder.bump();
+    if builder.eat_punctuator(Punctuator::GenericOpen) {
+        loop {
+            match builder.peek() {
+                Some(SyntaxKind::Identifier) => parse_kind(builder),
+                Some(SyntaxKind::Punctuator(Punctuator::Comma)) => builder.bump(),
+                Some(SyntaxKind::Punctuator(Punctuator::GenericClose)) => {
+                    builder.bump();
+                    break;
+                },
+                _ => break,
+            }
+        }
+    }
+    builder.finish();
+}
+
+/// The directives applied to a definition, eg: `@lowercase @cached(seconds: 60)`
+pub(crate) fn parse_directives(builder: &mut Builder) {
+    while builder.at_punctuator(Punctuator::At) {
+        builder.start(NodeKind::Directive);
+        builder.bump();
+        if builder.at_on_line(SyntaxKind::Identifier) {
+            builder.bump();
+        }
+        if builder.at_punctuator(Punctuator::OpenParen) {
+            parse_inputs(builder, Punctuator::OpenParen, Punctuator::CloseParen);
+        }
+        builder.finish();
+    }
+}
+
+/// Inputs of a field or directive, or the fields of a map value, eg: `(size: 48, crop: None)`
+pub(crate) fn parse_inputs(builder: &mut Builder, opening: Punctuator, closing: Punctuator) {
+    builder.block(NodeKind::Inputs, opening, closing, is_identifier, parse_input);
+}
+
+/// `ident: value`
+fn parse_input(builder: &mut Builder) {
+    builder.start(NodeKind::Input);
+    builder.bump();
+    if builder.eat_punctuator(Punctuator::Colon) {
+        parse_value(builder);
+    }
+    builder.finish();
+}
+
+fn starts_value(kind: SyntaxKind) -> bool {
+    matches!(
+        kind,
+        SyntaxKind::Identifier
+            | SyntaxKind::Primitive
+            | SyntaxKind::Punctuator(Punctuator::OpenBlock | Punctuator::OpenBracket | Punctuator::Dollar)
+    )
+}
+
+/// A primitive, variant, map, list, variable or `None`, nothing is parsed if the next token
+/// can't start a value
+pub(crate) fn parse_value(builder: &mut Builder) {
+    let kind = match builder.peek() {
+        Some(kind) if starts_value(kind) => kind,
+        _ => return,
+    };
+    builder.start(NodeKind::Value);
+    match kind {
+        SyntaxKind::Punctuator(Punctuator::OpenBlock) => parse_inputs(builder, Punctuator::OpenBlock, Punctuator::CloseBlock),
+        SyntaxKind::Punctuator(Punctuator::OpenBracket) => {
+            builder.block(NodeKind::List, Punctuator::OpenBracket, Punctuator::CloseBracket, starts_value, parse_value)
+        },
+        SyntaxKind::Punctuator(Punctuator::Dollar) => {
+            builder.bump();
+            if builder.at_on_line(SyntaxKind::Identifier) {
+                builder.bump();
+            }
+        },
+        SyntaxKind::Identifier => {
+            builder.bump();
+            match builder.peek() {
+                Some(SyntaxKind::Punctuator(Punctuator::OpenBlock)) => {
+                    parse_inputs(builder, Punctuator::OpenBlock, Punctuator::CloseBlock)
+                },
+                Some(SyntaxKind::Punctuator(Punctuator::OpenParen)) => {
+                    builder.block(NodeKind::List, Punctuator::OpenParen, Punctuator::CloseParen, starts_value, parse_value)
+                },
+                _ => {},
+            }
+        },
+        _ => builder.bump(),
+    }
+    builder.finish();
+}
@@ -0,0 +1,224 @@
+use std::fmt::{self, Display, Formatter};
+
+use castle_input_cursor::Span;
+use castle_tokenizer::{Keyword, Punctuator};
+
+/// What a [SyntaxToken] is, trivia (whitespace, newlines, comments and characters that
+/// couldn't be tokenized) is kept alongside the tokens the parsers use
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SyntaxKind {
+    Identifier,
+    Keyword(Keyword),
+    Punctuator(Punctuator),
+    /// A string, number or boolean
+    Primitive,
+    /// Spaces and tabs
+    Whitespace,
+    /// A single line terminator, `\r\n` is one newline
+    Newline,
+    /// A `#` comment, not including the line terminator after it
+    Comment,
+    /// Characters that couldn't be tokenized
+    Unknown,
+}
+
+impl SyntaxKind {
+    /// Whether the parsers skip this kind of token
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::Whitespace | SyntaxKind::Newline | SyntaxKind::Comment | SyntaxKind::Unknown)
+    }
+}
+
+/// What a [SyntaxNode] is
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NodeKind {
+    // schemas
+    Schema,
+    TypeDefinition,
+    EnumDefinition,
+    InputTypeDefinition,
+    DirectiveDefinition,
+    /// The `{}` block of a type, or of a map variant
+    FieldDefinitions,
+    FieldDefinition,
+    /// The `{}` block of an enum
+    VariantDefinitions,
+    VariantDefinition,
+    /// The `()` of a tuple variant
+    Kinds,
+    /// The `{}` block of an input type, or the `()` of a field or directive definition
+    InputDefinitions,
+    InputDefinition,
+    /// `on FieldDefinition | TypeDefinition`
+    DirectiveLocations,
+    /// A kind with its generics, eg: `Vec<User>`
+    Kind,
+    /// An applied directive, eg: `@cached(seconds: 60)`
+    Directive,
+
+    // messages
+    Message,
+    VariableDefinitions,
+    VariableDefinition,
+    /// The `{}` or `[]` block of fields
+    Projection,
+    Field,
+    /// `match` and its `{}` block of arms
+    Match,
+    Arm,
+
+    // values
+    /// The `()` of inputs, or the `{}` of a map value
+    Inputs,
+    /// `ident: value`
+    Input,
+    Value,
+    /// The `[]` of a list value, or the `()` of a tuple variant value
+    List,
+
+    /// Tokens that don't fit where they are
+    Error,
+}
+
+/// A token of the source with its exact text
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxToken {
+    pub kind: SyntaxKind,
+    pub text: Box<str>,
+    /// Where the token was in the source, not updated when the tree is edited
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+/// A node of the tree, holding its children in source order.
+///
+/// A node starts and ends with a token the parsers use, trivia before the first token or
+/// after the last token of a node belongs to the node's parent.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyntaxNode {
+    pub kind: NodeKind,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxToken {
+    #[inline]
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    #[inline]
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    #[inline]
+    pub fn is_trivia(&self) -> bool {
+        self.kind.is_trivia()
+    }
+}
+
+impl SyntaxNode {
+    pub fn new(kind: NodeKind) -> Self {
+        Self {
+            kind,
+            children: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// The child nodes, leaving out tokens
+    pub fn child_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// The child tokens, leaving out nodes and trivia
+    pub fn child_tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Token(token) if !token.is_trivia() => Some(token),
+            _ => None,
+        })
+    }
+
+    /// Every token in the node, including trivia and the tokens of child nodes, in source order
+    pub fn tokens(&self) -> Vec<&SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens<'a>(&'a self, tokens: &mut Vec<&'a SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// Every token in the node mutably, in source order, eg: to rename an identifier
+    pub fn tokens_mut(&mut self) -> Vec<&mut SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens_mut(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens_mut<'a>(&'a mut self, tokens: &mut Vec<&'a mut SyntaxToken>) {
+        for child in &mut self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens_mut(tokens),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+    }
+
+    /// Every node below this one (not including it), parents before their children
+    pub fn descendants(&self) -> Vec<&SyntaxNode> {
+        let mut nodes = Vec::new();
+        for node in self.child_nodes() {
+            nodes.push(node);
+            nodes.extend(node.descendants());
+        }
+        nodes
+    }
+
+    /// Where the node was in the source, from the start of its first token to the end of its
+    /// last token. `None` for an empty node.
+    pub fn span(&self) -> Option<Span> {
+        let tokens = self.tokens();
+        Some(tokens.first()?.span.to(tokens.last()?.span))
+    }
+
+    /// The source text of the node
+    pub fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for SyntaxNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.tokens().into_iter().try_for_each(|token| f.write_str(&token.text))
+    }
+}
+
+impl Display for SyntaxToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
@@ -0,0 +1,283 @@
+use castle_cst::{
+    parse_message_cst, parse_schema_cst, FieldDefinitionNode, MessageNode, NodeKind, SchemaNode, SyntaxElement,
+    SyntaxKind, SyntaxNode, SyntaxToken,
+};
+use castle_query_parser::FieldKind;
+use castle_tokenizer::{Keyword, Punctuator};
+
+const SCHEMA: &str = r#"
+# The schema of the app
+
+directive @lowercase on FieldDefinition | InputFieldDefinition
+@cached(seconds: 60)
+type User { # users of the app
+    name: String @lowercase, age: number
+
+
+    # in pixels
+    avatar(size: number = 48
+        crop: Option<Crop>): String
+    friends: Vec<User>
+}
+enum Icon {
+    SVGIcon { url: String, size: number }
+    Emoji(String), NoIcon
+}
+input Crop { x: number, y: number = 0 }
+type Empty {}
+"#;
+
+const MESSAGE: &str = r#"message GetUser($id: Uuid, $size: number = 48) {
+    user(id: $id) { # the signed in user
+        name as display_name
+        avatar(size: $size, crop: Some({ x: 1, y: 2 }))
+        icon match {
+            SVGIcon { url }
+            NoIcon
+        }
+        friends [ name ]
+    }
+}
+"#;
+
+fn kinds(node: &SyntaxNode) -> Vec<NodeKind> {
+    node.child_nodes().map(|child| child.kind).collect()
+}
+
+fn names<'a, T>(nodes: impl Iterator<Item = T>, name: impl Fn(&T) -> Option<&'a SyntaxToken>) -> Vec<&'a str> {
+    nodes.map(|node| name(&node).unwrap().text()).collect()
+}
+
+#[test]
+fn trees_print_back_to_their_source() {
+    let sources = [
+        SCHEMA,
+        MESSAGE,
+        "",
+        "\n\n",
+        "# only a comment",
+        "type User {\r\n\tname: String\r\n}\r\n",
+        "type Emoji { icon: String = \"🏰 castle\" }\n  \t",
+        "type Broken { name: String ~ age: ! }\ntype Unclosed {\n    a: String",
+        "enum } type { ) ] @@ input",
+        "message { user { name } } trailing ~ tokens",
+        "message { avatar(url: \"unterminated) }",
+    ];
+    for source in sources {
+        assert_eq!(parse_schema_cst(source).to_string(), source);
+        assert_eq!(parse_message_cst(source).to_string(), source);
+    }
+}
+
+#[test]
+fn tokens_keep_trivia_with_their_positions() {
+    let tree = parse_schema_cst("type A { # note\r\n\tb: String\n}");
+    let tokens: Vec<_> = tree
+        .tokens()
+        .into_iter()
+        .map(|token| (token.kind, token.text(), token.span.start.line_number(), token.span.start.column_number()))
+        .collect();
+    assert_eq!(tokens, vec![
+        (SyntaxKind::Keyword(Keyword::Type), "type", 1, 1),
+        (SyntaxKind::Whitespace, " ", 1, 5),
+        (SyntaxKind::Identifier, "A", 1, 6),
+        (SyntaxKind::Whitespace, " ", 1, 7),
+        (SyntaxKind::Punctuator(Punctuator::OpenBlock), "{", 1, 8),
+        (SyntaxKind::Whitespace, " ", 1, 9),
+        (SyntaxKind::Comment, "# note", 1, 10),
+        (SyntaxKind::Newline, "\r\n", 1, 16),
+        (SyntaxKind::Whitespace, "\t", 2, 1),
+        (SyntaxKind::Identifier, "b", 2, 2),
+        (SyntaxKind::Punctuator(Punctuator::Colon), ":", 2, 3),
+        (SyntaxKind::Whitespace, " ", 2, 4),
+        (SyntaxKind::Identifier, "String", 2, 5),
+        (SyntaxKind::Newline, "\n", 2, 11),
+        (SyntaxKind::Punctuator(Punctuator::CloseBlock), "}", 3, 1),
+    ]);
+}
+
+#[test]
+fn doc_comments_are_comment_trivia() {
+    let src = "/// A person\n## who uses the app\ntype User { a: b/ }\n";
+    let tree = parse_schema_cst(src);
+    let comments: Vec<_> = tree
+        .tokens()
+        .into_iter()
+        .filter(|token| token.kind == SyntaxKind::Comment)
+        .map(|token| token.text())
+        .collect();
+    assert_eq!(comments, vec!["/// A person", "## who uses the app"]);
+    assert_eq!(tree.to_string(), src);
+}
+
+#[test]
+fn nodes_start_and_end_with_tokens_the_parsers_use() {
+    let tree = parse_schema_cst(SCHEMA);
+    for node in tree.descendants() {
+        let tokens = node.tokens();
+        assert!(!tokens.first().unwrap().is_trivia(), "{:?} starts with trivia", node.kind);
+        assert!(!tokens.last().unwrap().is_trivia(), "{:?} ends with trivia", node.kind);
+    }
+    // the comment before `avatar` is in the type's block, not in the field
+    let user = SchemaNode::cast(&tree).unwrap().types().next().unwrap();
+    let block = user.syntax().child_nodes().find(|node| node.kind == NodeKind::FieldDefinitions).unwrap();
+    let comments: Vec<_> = block
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            SyntaxElement::Token(token) if token.kind == SyntaxKind::Comment => Some(token.text()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(comments, vec!["# users of the app", "# in pixels"]);
+}
+
+#[test]
+fn schema_nodes_give_access_to_their_parts() {
+    let tree = parse_schema_cst(SCHEMA);
+    assert_eq!(kinds(&tree), vec![
+        NodeKind::DirectiveDefinition,
+        NodeKind::TypeDefinition,
+        NodeKind::EnumDefinition,
+        NodeKind::InputTypeDefinition,
+        NodeKind::TypeDefinition,
+    ]);
+    let schema = SchemaNode::cast(&tree).unwrap();
+
+    let directive = schema.directives().next().unwrap();
+    assert_eq!(directive.name().unwrap().text(), "lowercase");
+    assert_eq!(directive.locations().map(|token| token.text()).collect::<Vec<_>>(), vec!["FieldDefinition", "InputFieldDefinition"]);
+
+    let user = schema.types().next().unwrap();
+    assert_eq!(user.name().unwrap().text(), "User");
+    assert_eq!(names(user.directives(), |d| d.name()), vec!["cached"]);
+    assert_eq!(names(user.fields(), FieldDefinitionNode::name), vec!["name", "age", "avatar", "friends"]);
+    let avatar = user.fields().nth(2).unwrap();
+    assert_eq!(names(avatar.input_definitions(), |i| i.name()), vec!["size", "crop"]);
+    let crop = avatar.input_definitions().nth(1).unwrap();
+    let crop_kind = crop.input_kind().unwrap();
+    assert_eq!(crop_kind.syntax().text(), "Option<Crop>");
+    assert_eq!(names(crop_kind.generics(), |k| k.name()), vec!["Crop"]);
+    assert_eq!(avatar.input_definitions().next().unwrap().default().unwrap().syntax().text(), "48");
+    assert_eq!(avatar.return_kind().unwrap().syntax().text(), "String");
+
+    let icon = schema.enums().next().unwrap();
+    assert_eq!(names(icon.variants(), |v| v.name()), vec!["SVGIcon", "Emoji", "NoIcon"]);
+    let svg = icon.variants().next().unwrap();
+    assert_eq!(names(svg.fields(), FieldDefinitionNode::name), vec!["url", "size"]);
+    let emoji = icon.variants().nth(1).unwrap();
+    assert_eq!(names(emoji.kinds(), |k| k.name()), vec!["String"]);
+
+    let input = schema.input_types().next().unwrap();
+    assert_eq!(names(input.input_definitions(), |i| i.name()), vec!["x", "y"]);
+}
+
+#[test]
+fn schema_nodes_map_onto_the_parsed_schema() {
+    let tree = parse_schema_cst(SCHEMA);
+    let schema = SchemaNode::cast(&tree).unwrap();
+    let definition = schema.definition().unwrap();
+
+    for type_node in schema.types() {
+        let type_def = type_node.definition(&definition).unwrap();
+        assert_eq!(type_def.span.start, type_node.name().unwrap().span.start);
+        for field in type_node.fields() {
+            let field_def = field.definition(type_def).unwrap();
+            assert_eq!(field_def.span, field.name().unwrap().span);
+            assert_eq!(field_def.return_kind.to_string(), field.return_kind().unwrap().syntax().text());
+            for input in field.input_definitions() {
+                assert_eq!(input.definition(&field_def.input_definitions).unwrap().span, input.name().unwrap().span);
+            }
+            for directive in field.directives() {
+                assert_eq!(&*directive.applied(&field_def.directives).unwrap().ident, directive.name().unwrap().text());
+            }
+        }
+        for directive in type_node.directives() {
+            assert_eq!(directive.applied(&type_def.directives).unwrap().inputs.len(), 1);
+        }
+    }
+    let icon = schema.enums().next().unwrap();
+    let icon_def = icon.definition(&definition).unwrap();
+    for variant in icon.variants() {
+        assert_eq!(variant.definition(icon_def).unwrap().span, variant.name().unwrap().span);
+    }
+    let svg = icon.variants().next().unwrap();
+    let svg_type = svg.definition(icon_def).unwrap().map_type_definition("Icon").unwrap();
+    assert_eq!(svg.fields().filter_map(|field| field.definition(&svg_type)).count(), 2);
+    let crop = schema.input_types().next().unwrap();
+    assert_eq!(crop.definition(&definition).unwrap().input_definitions.len(), 2);
+    let lowercase = schema.directives().next().unwrap();
+    assert_eq!(lowercase.definition(&definition).unwrap().locations.len(), 2);
+}
+
+#[test]
+fn message_nodes_give_access_to_their_parts_and_map_onto_the_parsed_message() {
+    let tree = parse_message_cst(MESSAGE);
+    let message_node = MessageNode::cast(&tree).unwrap();
+    let message = message_node.message().unwrap();
+    assert_eq!(message_node.name().unwrap().text(), "GetUser");
+
+    let variables: Vec<_> = message_node.variable_definitions().collect();
+    assert_eq!(names(variables.iter().copied(), |v| v.name()), vec!["id", "size"]);
+    assert_eq!(variables[1].kind().unwrap().syntax().text(), "number");
+    assert_eq!(variables[1].default().unwrap().syntax().text(), "48");
+    assert!(variables[0].definition(&message.variables).unwrap().default.is_none());
+
+    let user = message_node.projection().unwrap().fields().next().unwrap();
+    assert_eq!(names(user.inputs(), |i| i.name()), vec!["id"]);
+    let user_field = user.field(&message.projection).unwrap();
+    let user_projection = match &user_field.kind {
+        FieldKind::Object(projection) => projection,
+        kind => panic!("expected an object, got {:?}", kind),
+    };
+
+    let fields: Vec<_> = user.projection().unwrap().fields().collect();
+    assert_eq!(names(fields.iter().copied(), |f| f.name()), vec!["name", "avatar", "icon", "friends"]);
+    assert_eq!(fields[0].rename().unwrap().text(), "display_name");
+    assert!(fields[1].rename().is_none());
+    assert_eq!(fields[1].inputs().nth(1).unwrap().value().unwrap().syntax().text(), "Some({ x: 1, y: 2 })");
+    for field in &fields {
+        assert_eq!(field.field(user_projection).unwrap().span, field.name().unwrap().span);
+    }
+
+    let arms: Vec<_> = fields[2].arms().collect();
+    assert_eq!(names(arms.iter().copied(), |a| a.name()), vec!["SVGIcon", "NoIcon"]);
+    let parsed_arms = match &fields[2].field(user_projection).unwrap().kind {
+        FieldKind::Match(arms) => arms,
+        kind => panic!("expected a match, got {:?}", kind),
+    };
+    assert_eq!(arms[0].arm(parsed_arms).unwrap().len(), 1);
+    assert!(arms[1].projection().is_none());
+    assert_eq!(names(fields[3].projection().unwrap().fields(), |f| f.name()), vec!["name"]);
+}
+
+#[test]
+fn invalid_sources_still_have_trees() {
+    let source = "type Broken { name: String ~ age: = 5 }\ntype Unclosed {\n    a: String";
+    let tree = parse_schema_cst(source);
+    let schema = SchemaNode::cast(&tree).unwrap();
+    assert_eq!(names(schema.types(), |t| t.name()), vec!["Broken", "Unclosed"]);
+    let broken = schema.types().next().unwrap();
+    assert_eq!(names(broken.fields(), FieldDefinitionNode::name), vec!["name", "age"]);
+    let errors: Vec<_> = tree.descendants().into_iter().filter(|node| node.kind == NodeKind::Error).collect();
+    assert_eq!(errors.iter().map(|node| node.text()).collect::<Vec<_>>(), vec!["=", "5"]);
+    assert!(tree.tokens().iter().any(|token| token.kind == SyntaxKind::Unknown && token.text() == "~"));
+    let unclosed = schema.types().nth(1).unwrap();
+    assert_eq!(names(unclosed.fields(), FieldDefinitionNode::name), vec!["a"]);
+
+    let tree = parse_message_cst("message { user { name } } extra");
+    assert_eq!(kinds(&tree), vec![NodeKind::Projection, NodeKind::Error]);
+}
+
+#[test]
+fn trees_can_be_edited_without_losing_formatting() {
+    let mut tree = parse_schema_cst(SCHEMA);
+    for token in tree.tokens_mut() {
+        if token.kind == SyntaxKind::Identifier && token.text() == "User" {
+            token.text = "Person".into();
+        }
+    }
+    assert_eq!(tree.to_string(), SCHEMA.replace("type User", "type Person").replace("Vec<User>", "Vec<Person>"));
+    let definition = SchemaNode::cast(&tree).unwrap().definition().unwrap();
+    assert_eq!(definition.types["Person"].fields["friends"].return_kind.to_string(), "Vec<Person>");
+}
@@ -0,0 +1,184 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Expr, ExprLit, Fields, FieldsNamed, Lit, Meta};
+
+pub(crate) fn derive_type(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let fields = named_fields(&input, "CastleType")?.named.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap().to_string();
+        let ty = &field.ty;
+        let docs = docs(&field.attrs);
+        quote! {
+            (#field_name.into(), ::castle_api::types::definitions::FieldDefinition {
+                ident: #field_name.into(),
+                input_definitions: ::std::collections::HashMap::new(),
+                return_kind: <#ty as ::castle_api::CastleKind>::kind(),
+                directives: vec![],
+                docs: #docs,
+                span: Default::default(),
+            })
+        }
+    });
+    let kind = derive_kind(&input);
+    let docs = docs(&input.attrs);
+    Ok(quote! {
+        #kind
+
+        impl ::castle_api::CastleType for #ident {
+            fn type_definition() -> ::castle_api::types::definitions::TypeDefinition {
+                ::castle_api::types::definitions::TypeDefinition {
+                    ident: #name.into(),
+                    fields: [#(#fields),*].into_iter().collect(),
+                    directives: vec![],
+                    docs: #docs,
+                    span: Default::default(),
+                }
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_input(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let input_definitions = named_fields(&input, "CastleInput")?.named.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap().to_string();
+        let ty = &field.ty;
+        let docs = docs(&field.attrs);
+        quote! {
+            (#field_name.into(), ::castle_api::types::definitions::InputDefinition {
+                ident: #field_name.into(),
+                input_kind: <#ty as ::castle_api::CastleKind>::kind(),
+                default: None,
+                directives: vec![],
+                docs: #docs,
+                span: Default::default(),
+            })
+        }
+    });
+    let kind = derive_kind(&input);
+    let docs = docs(&input.attrs);
+    Ok(quote! {
+        #kind
+
+        impl ::castle_api::CastleInput for #ident {
+            fn input_type_definition() -> ::castle_api::types::definitions::InputTypeDefinition {
+                ::castle_api::types::definitions::InputTypeDefinition {
+                    ident: #name.into(),
+                    input_definitions: [#(#input_definitions),*].into_iter().collect(),
+                    directives: vec![],
+                    docs: #docs,
+                    span: Default::default(),
+                }
+            }
+        }
+    })
+}
+
+pub(crate) fn derive_enum(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let data = match &input.data {
+        Data::Enum(data) if input.generics.params.is_empty() => data,
+        _ => return Err(syn::Error::new_spanned(&input.ident, "CastleEnum can only be derived for enums without generics")),
+    };
+    let variants = data.variants.iter().map(|variant| {
+        let variant_name = variant.ident.to_string();
+        let kind = match &variant.fields {
+            Fields::Unit => quote!(::castle_api::types::definitions::VariantKindDefinition::Unit),
+            Fields::Unnamed(fields) => {
+                let types = fields.unnamed.iter().map(|field| &field.ty);
+                quote! {
+                    ::castle_api::types::definitions::VariantKindDefinition::Tuple(vec![
+                        #(<#types as ::castle_api::CastleKind>::kind()),*
+                    ])
+                }
+            },
+            Fields::Named(fields) => {
+                let names = fields.named.iter().map(|field| field.ident.as_ref().unwrap().to_string());
+                let types = fields.named.iter().map(|field| &field.ty);
+                quote! {
+                    ::castle_api::types::definitions::VariantKindDefinition::Map([
+                        #((#names.into(), <#types as ::castle_api::CastleKind>::kind())),*
+                    ].into_iter().collect())
+                }
+            },
+        };
+        let docs = docs(&variant.attrs);
+        quote! {
+            (#variant_name.into(), ::castle_api::types::definitions::VariantDefinition {
+                ident: #variant_name.into(),
+                kind: #kind,
+                directives: vec![],
+                docs: #docs,
+                span: Default::default(),
+            })
+        }
+    });
+    let kind = derive_kind(&input);
+    let docs = docs(&input.attrs);
+    Ok(quote! {
+        #kind
+
+        impl ::castle_api::CastleEnum for #ident {
+            fn enum_definition() -> ::castle_api::types::definitions::EnumDefinition {
+                ::castle_api::types::definitions::EnumDefinition {
+                    ident: #name.into(),
+                    variants: [#(#variants),*].into_iter().collect(),
+                    directives: vec![],
+                    docs: #docs,
+                    span: Default::default(),
+                }
+            }
+        }
+    })
+}
+
+/// The `///` doc comments of a type, field or variant as the `docs` of its definition, lines
+/// are joined with `\n` like the `##` comments of a schema
+fn docs(attrs: &[Attribute]) -> TokenStream {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(line), .. }) => Some(line.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .map(|line| line.strip_prefix(' ').unwrap_or(&line).trim_end().to_string())
+        .collect();
+    match lines.is_empty() {
+        true => quote!(None),
+        false => {
+            let docs = lines.join("\n");
+            quote!(Some(#docs.into()))
+        },
+    }
+}
+
+/// The fields of a struct, schema definitions can't be generic
+fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<&'a FieldsNamed> {
+    match &input.data {
+        Data::Struct(data) if input.generics.params.is_empty() => match &data.fields {
+            Fields::Named(fields) => Ok(fields),
+            fields => Err(syn::Error::new_spanned(fields, format!("{} can only be derived for structs with named fields", derive))),
+        },
+        _ => Err(syn::Error::new_spanned(&input.ident, format!("{} can only be derived for structs without generics", derive))),
+    }
+}
+
+/// Implements `CastleKind` as the name of the type
+fn derive_kind(input: &DeriveInput) -> TokenStream {
+    let ident = &input.ident;
+    let name = ident.to_string();
+    quote! {
+        impl ::castle_api::CastleKind for #ident {
+            fn kind() -> ::castle_api::types::definitions::Kind {
+                ::castle_api::types::definitions::kind(#name, vec![])
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    match &input.data {
+        Data::Struct(data) => {
+            let fields = match &data.fields {
+                Fields::Named(fields) => fields,
+                fields => return Err(syn::Error::new_spanned(fields, "FromInputs can only be derived for structs with named fields")),
+            };
+            let construct = construct(quote!(Self), fields, quote!(inputs));
+            let expected = format!("a map of {}", ident);
+            Ok(quote! {
+                impl #impl_generics ::castle_api::FromInputs for #ident #ty_generics #where_clause {
+                    fn from_inputs(inputs: &::castle_api::Inputs) -> Result<Self, ::castle_api::CastleError> {
+                        Ok(#construct)
+                    }
+                }
+
+                impl #impl_generics ::castle_api::FromInput for #ident #ty_generics #where_clause {
+                    fn from_input(input: &::castle_api::Input) -> Result<Self, ::castle_api::CastleError> {
+                        match input.as_map() {
+                            Some(inputs) => <Self as ::castle_api::FromInputs>::from_inputs(inputs),
+                            None => Err(::castle_api::types::from_inputs::unexpected_input(#expected, input)),
+                        }
+                    }
+                }
+            })
+        },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                let variant_ident = &variant.ident;
+                let variant_name = variant_ident.to_string();
+                match &variant.fields {
+                    Fields::Unit => quote! {
+                        (#variant_name, ::castle_api::types::from_inputs::VariantType::Unit) => Ok(Self::#variant_ident),
+                    },
+                    Fields::Unnamed(fields) => {
+                        let len = fields.unnamed.len();
+                        let indices = 0..len;
+                        quote! {
+                            (#variant_name, ::castle_api::types::from_inputs::VariantType::Tuple(items)) if items.len() == #len => Ok(Self::#variant_ident(
+                                #(::castle_api::FromInput::from_input(&items[#indices])?),*
+                            )),
+                        }
+                    },
+                    Fields::Named(fields) => {
+                        let construct = construct(quote!(Self::#variant_ident), fields, quote!(inputs));
+                        quote! {
+                            (#variant_name, ::castle_api::types::from_inputs::VariantType::Map(inputs)) => Ok(#construct),
+                        }
+                    },
+                }
+            });
+            let expected = format!("a variant of {}", ident);
+            Ok(quote! {
+                impl #impl_generics ::castle_api::FromInput for #ident #ty_generics #where_clause {
+                    fn from_input(input: &::castle_api::Input) -> Result<Self, ::castle_api::CastleError> {
+                        let variant = input
+                            .as_variant()
+                            .ok_or_else(|| ::castle_api::types::from_inputs::unexpected_input(#expected, input))?;
+                        match (variant.ident(), variant.value()) {
+                            #(#arms)*
+                            _ => Err(::castle_api::types::from_inputs::unexpected_input(#expected, input)),
+                        }
+                    }
+                }
+            })
+        },
+        Data::Union(data) => Err(syn::Error::new_spanned(data.union_token, "FromInputs can't be derived for unions")),
+    }
+}
+
+/// Constructs `path` with each of `fields` read from the input map `inputs`
+fn construct(path: TokenStream, fields: &syn::FieldsNamed, inputs: TokenStream) -> TokenStream {
+    let names: Vec<_> = fields.named.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+    let keys = names.iter().map(|name| name.to_string());
+    quote! {
+        #path {
+            #(#names: ::castle_api::types::from_inputs::input_field(#inputs, #keys)?),*
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, Data, DeriveInput, Fields, Type};
+
+pub(crate) fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    let ident = &input.ident;
+    let mut field_types: Vec<&Type> = Vec::new();
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            field_types.extend(data.fields.iter().map(|field| &field.ty));
+            match &data.fields {
+                Fields::Named(fields) => {
+                    let names: Vec<_> = fields.named.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+                    let object = object(&names, quote!(self.), quote!(::castle_api::types::into_value::field_projection(field)));
+                    quote!(::castle_api::Value::Object(#object))
+                },
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                    ::castle_api::IntoValue::into_value(self.0, field)
+                },
+                Fields::Unnamed(fields) => return Err(syn::Error::new_spanned(fields, "IntoValue can only be derived for tuple structs with one field")),
+                Fields::Unit => quote!(::castle_api::Value::Void),
+            }
+        },
+        Data::Enum(data) => {
+            let arms = data.variants.iter().map(|variant| {
+                field_types.extend(variant.fields.iter().map(|field| &field.ty));
+                let variant_ident = &variant.ident;
+                let variant_name = variant_ident.to_string();
+                let (pattern, payload) = match &variant.fields {
+                    Fields::Unit => (quote!(), quote!(::castle_api::Value::Void)),
+                    Fields::Unnamed(fields) => {
+                        let bindings: Vec<_> = (0..fields.unnamed.len()).map(|i| format_ident!("item_{}", i)).collect();
+                        (quote!((#(#bindings),*)), quote! {
+                            ::castle_api::Value::Vec(vec![#(::castle_api::IntoValue::into_value(#bindings, None)),*])
+                        })
+                    },
+                    Fields::Named(fields) => {
+                        let names: Vec<_> = fields.named.iter().map(|field| field.ident.as_ref().unwrap()).collect();
+                        let object = object(&names, quote!(), quote!(::castle_api::types::into_value::variant_projection(field, #variant_name)));
+                        (quote!({ #(#names),* }), quote!(::castle_api::Value::Object(#object)))
+                    },
+                };
+                quote! {
+                    Self::#variant_ident #pattern => ::castle_api::Value::Variant(#variant_name.into(), Box::new(#payload)),
+                }
+            }).collect::<Vec<_>>();
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        },
+        Data::Union(data) => return Err(syn::Error::new_spanned(data.union_token, "IntoValue can't be derived for unions")),
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(__Ctx));
+    generics.params.push(parse_quote!(__E));
+    let where_clause = generics.make_where_clause();
+    for ty in field_types {
+        where_clause.predicates.push(parse_quote!(#ty: ::castle_api::IntoValue<__Ctx, __E>));
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::castle_api::IntoValue<__Ctx, __E> for #ident #ty_generics #where_clause {
+            fn into_value(self, field: Option<&::castle_api::Field>) -> ::castle_api::Value<__Ctx, __E> {
+                #body
+            }
+        }
+    })
+}
+
+/// Builds a map of the projected fields, `access` is prepended to each field name to get its value
+fn object(names: &[&syn::Ident], access: TokenStream, projection: TokenStream) -> TokenStream {
+    let keys = names.iter().map(|name| name.to_string());
+    quote! {{
+        let projection = #projection;
+        let mut object = ::std::collections::HashMap::new();
+        #(
+            if let Some(field) = ::castle_api::types::into_value::projected_field(projection, #keys) {
+                object.insert(#keys.into(), ::castle_api::IntoValue::into_value(#access #names, field));
+            }
+        )*
+        object
+    }}
+}
@@ -0,0 +1,63 @@
+//! Derive macros for `castle_api`, use them through the re-exports in `castle_api`.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod definitions;
+mod from_inputs;
+mod into_value;
+
+/// Implements `IntoValue` for a struct or enum
+/// - structs with named fields become objects, keyed by field name
+/// - newtype structs become the value of their field, unit structs `Value::Void`
+/// - enum variants become `Value::Variant`s: unit variants hold `Value::Void`, tuple variants
+///   a `Value::Vec` and map variants a `Value::Object`
+///
+/// Only the keys projected by the field being resolved are converted.
+#[proc_macro_derive(IntoValue)]
+pub fn derive_into_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_value::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `FromInputs` and `FromInput` for a struct with named fields, reading each field
+/// from the input with the same name. For enums only `FromInput` is implemented, reading
+/// variant inputs with the same shape as the Rust variant.
+#[proc_macro_derive(FromInputs)]
+pub fn derive_from_inputs(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_inputs::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `CastleType` for a struct with named fields, defining a `type` with a field for
+/// each of the struct's fields. The field kinds come from `CastleKind`, eg: `Vec<u32>` is `Vec<number>`.
+#[proc_macro_derive(CastleType)]
+pub fn derive_castle_type(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    definitions::derive_type(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `CastleInput` for a struct with named fields, defining an `input` with an input
+/// for each of the struct's fields
+#[proc_macro_derive(CastleInput)]
+pub fn derive_castle_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    definitions::derive_input(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `CastleEnum` for an enum, defining an `enum` with the same variants
+#[proc_macro_derive(CastleEnum)]
+pub fn derive_castle_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    definitions::derive_enum(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
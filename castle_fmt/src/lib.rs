@@ -0,0 +1,35 @@
+//! Formats Castle schemas and messages in a stable style, keeping `#` comments.
+//!
+//! The source is parsed, then printed from the parsed definitions in source order:
+//! - items are separated by a blank line, blank lines between fields are kept (at most one)
+//! - fields, variants and input type fields are on their own line, indented by 4 spaces
+//! - field inputs, directive inputs and values are on one line, separated by `, `
+//! - comments are kept on their own line before the following definition, or after the
+//!   definition on the same line
+//!
+//! The formatted source is parsed again to check it has the same definitions as the original.
+
+use castle_error::CastleError;
+use castle_tokenizer::{Keyword, TokenKind, Tokenizable, Tokenizer};
+
+mod message;
+mod schema;
+mod source;
+mod writer;
+
+pub use message::format_message;
+pub use schema::format_schema;
+
+/// Formats a message if the source starts with `message`, otherwise formats it as a schema
+pub fn format(src: &str) -> Result<String, CastleError> {
+    match is_message(src)? {
+        true => format_message(src),
+        false => format_schema(src),
+    }
+}
+
+/// Whether the source is a message, ie: its first token is the `message` keyword
+pub fn is_message(src: &str) -> Result<bool, CastleError> {
+    let mut tokenizer = Tokenizer::new(src.as_bytes());
+    Ok(matches!(tokenizer.peek_token_kind(true)?, Some(TokenKind::Keyword(Keyword::Message))))
+}
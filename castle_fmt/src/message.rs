@@ -0,0 +1,105 @@
+use castle_error::CastleError;
+use castle_input_cursor::Position;
+use castle_query_parser::{parse_message, Field, FieldKind, Message, Projection};
+use castle_shared_parser::print::{print_input, print_sorted_inputs};
+
+use crate::{source::Source, writer::Writer};
+
+/// Formats a message, keeping the order of its fields and its comments
+pub fn format_message(src: &str) -> Result<String, CastleError> {
+    let message = parse_message(src)?;
+    let source = Source::new(src)?;
+    let message_line = source.first_line();
+    let mut writer = Writer::new(source);
+
+    if message.name.is_some() || !message.variables.is_empty() || !message.projection.is_empty() {
+        let header = format!(
+            "message{}{}",
+            message.name.as_ref().map(|name| format!(" {}", name)).unwrap_or_default(),
+            variable_definitions(&message),
+        );
+        write_projection(&mut writer, Position::new(message_line, 1), &header, "{", &message.projection, "}");
+    }
+
+    let formatted = writer.finish();
+    let reparsed = parse_message(&formatted)?;
+    if reparsed.name != message.name || reparsed.variables != message.variables || reparsed.projection != message.projection {
+        return Err(CastleError::Other("Formatting changed the meaning of the message".into()))
+    }
+    Ok(formatted)
+}
+
+/// The variables of a message in source order, eg: `($id: Uuid, $size: number = 48)`
+fn variable_definitions(message: &Message) -> String {
+    if message.variables.is_empty() {
+        return String::new()
+    }
+    let mut variables: Vec<_> = message.variables.values().collect();
+    variables.sort_by_key(|variable| variable.span.start);
+    let variables: Vec<_> = variables.into_iter().map(|variable| format!(
+        "${}: {}{}",
+        variable.ident,
+        variable.kind,
+        variable.default.as_ref().map(|default| format!(" = {}", print_input(default))).unwrap_or_default(),
+    )).collect();
+    format!("({})", variables.join(", "))
+}
+
+/// Writes `header {`, the projected fields in source order and the closing line,
+/// or `header {}` if nothing is projected (and there are no comments in the projection)
+fn write_projection(writer: &mut Writer, pos: Position, header: &str, open: &str, projection: &Projection, close: &str) {
+    let line = pos.line_number();
+    let close_line = writer.source().block_close_line(pos).unwrap_or(line);
+    if projection.is_empty() && !writer.has_comments_before(close_line) {
+        writer.line(line, &format!("{} {}{}", header, open, close));
+        return
+    }
+    writer.open(line, &format!("{} {}", header, open));
+    let mut fields: Vec<_> = projection.values().collect();
+    fields.sort_by_key(|field| field.span.start);
+    for field in fields {
+        write_field(writer, field);
+    }
+    writer.close(close_line, close);
+}
+
+fn write_field(writer: &mut Writer, field: &Field) {
+    let mut header = field.name.to_string();
+    if !field.inputs.is_empty() {
+        header.push_str(&format!("({})", print_sorted_inputs(&field.inputs)));
+    }
+    if let Some(rename) = &field.rename {
+        header.push_str(&format!(" as {}", rename));
+    }
+    match &field.kind {
+        FieldKind::Field => writer.line(field.span.start.line_number(), &header),
+        FieldKind::Object(projection) => write_projection(writer, field.span.start, &header, "{", projection, "}"),
+        FieldKind::List(projection) => write_projection(writer, field.span.start, &header, "[", projection, "]"),
+        FieldKind::Match(arms) => {
+            let line = field.span.start.line_number();
+            let close_line = writer.source().block_close_line(field.span.start).unwrap_or(line);
+            let header = format!("{} match", header);
+            if arms.is_empty() && !writer.has_comments_before(close_line) {
+                writer.line(line, &format!("{} {{}}", header));
+                return
+            }
+            let mut arms: Vec<_> = arms
+                .iter()
+                .map(|(variant, projection)| {
+                    let pos = writer.source().ident_in_block_after(field.span.start, variant).unwrap_or(field.span.start);
+                    (pos, variant, projection)
+                })
+                .collect();
+            arms.sort_by(|(l_pos, l_variant, _), (r_pos, r_variant, _)| (l_pos, l_variant).cmp(&(r_pos, r_variant)));
+
+            writer.open(line, &format!("{} {{", header));
+            for (pos, variant, projection) in arms {
+                match projection.is_empty() {
+                    true => writer.line(pos.line_number(), variant),
+                    false => write_projection(writer, pos, variant, "{", projection, "}"),
+                }
+            }
+            writer.close(close_line, "}");
+        },
+    }
+}
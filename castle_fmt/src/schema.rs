@@ -0,0 +1,190 @@
+use castle_error::CastleError;
+use castle_input_cursor::Position;
+use castle_schema_parser::{
+    parsers::parse_schema::parse_schema,
+    printer::print_applied_directive,
+    types::{
+        AppliedDirective, DirectiveDefinition, EnumDefinition, InputDefinition, InputDefinitions,
+        InputTypeDefinition, TypeDefinition, VariantDefinition, VariantKindDefinition,
+    },
+};
+use castle_shared_parser::print::print_input;
+
+use crate::{source::Source, writer::Writer};
+
+/// Formats a schema, keeping the order of its definitions and its comments
+pub fn format_schema(src: &str) -> Result<String, CastleError> {
+    let schema = parse_schema(src)?;
+    let mut writer = Writer::new(Source::new(src)?);
+
+    let mut items: Vec<(Position, Item)> = Vec::new();
+    items.extend(schema.types.values().map(|def| (def.span.start, Item::Type(def))));
+    items.extend(schema.enums.values().map(|def| (def.span.start, Item::Enum(def))));
+    items.extend(schema.input_types.values().map(|def| (def.span.start, Item::InputType(def))));
+    items.extend(schema.directives.values().map(|def| (def.span.start, Item::Directive(def))));
+    items.sort_by_key(|(pos, _)| *pos);
+
+    for (_, item) in items {
+        writer.blank_line();
+        match item {
+            Item::Type(def) => write_type(&mut writer, def),
+            Item::Enum(def) => write_enum(&mut writer, def),
+            Item::InputType(def) => write_input_type(&mut writer, def),
+            Item::Directive(def) => write_directive_definition(&mut writer, def),
+        }
+    }
+
+    let formatted = writer.finish();
+    if parse_schema(&formatted)? != schema {
+        return Err(CastleError::Other("Formatting changed the meaning of the schema".into()))
+    }
+    Ok(formatted)
+}
+
+enum Item<'a> {
+    Type(&'a TypeDefinition),
+    Enum(&'a EnumDefinition),
+    InputType(&'a InputTypeDefinition),
+    Directive(&'a DirectiveDefinition),
+}
+
+fn write_type(writer: &mut Writer, def: &TypeDefinition) {
+    write_item_directives(writer, &def.directives);
+    let mut fields: Vec<_> = def.fields.values().collect();
+    fields.sort_by_key(|field| field.span.start);
+    write_block(writer, def.span.start, &format!("type {}", def.ident), fields.is_empty(), |writer| {
+        for field in fields {
+            write_with_input_definitions(writer, field.span.start, &field.ident, &field.input_definitions, &format!(
+                ": {}{}",
+                field.return_kind,
+                inline_directives(&field.directives),
+            ));
+        }
+    }, "}");
+}
+
+fn write_enum(writer: &mut Writer, def: &EnumDefinition) {
+    write_item_directives(writer, &def.directives);
+    let mut variants: Vec<_> = def.variants.values().collect();
+    variants.sort_by_key(|variant| variant.span.start);
+    write_block(writer, def.span.start, &format!("enum {}", def.ident), variants.is_empty(), |writer| {
+        for variant in variants {
+            write_variant(writer, variant);
+        }
+    }, "}");
+}
+
+fn write_variant(writer: &mut Writer, variant: &VariantDefinition) {
+    let line = variant.span.start.line_number();
+    let directives = inline_directives(&variant.directives);
+    match &variant.kind {
+        VariantKindDefinition::Unit => writer.line(line, &format!("{}{}", variant.ident, directives)),
+        VariantKindDefinition::Tuple(kinds) => writer.line(line, &format!(
+            "{}({}){}",
+            variant.ident,
+            kinds.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "),
+            directives,
+        )),
+        VariantKindDefinition::Map(map) => {
+            let mut fields: Vec<_> = map.iter().collect();
+            fields.sort_by_key(|(_, kind)| kind.span.start);
+            write_block(writer, variant.span.start, &variant.ident, fields.is_empty(), |writer| {
+                for (ident, kind) in fields {
+                    writer.line(kind.span.start.line_number(), &format!("{}: {}", ident, kind));
+                }
+            }, &format!("}}{}", directives));
+        },
+    }
+}
+
+fn write_input_type(writer: &mut Writer, def: &InputTypeDefinition) {
+    write_item_directives(writer, &def.directives);
+    let inputs = sorted_input_definitions(&def.input_definitions);
+    write_block(writer, def.span.start, &format!("input {}", def.ident), inputs.is_empty(), |writer| {
+        for input in inputs {
+            writer.line(input.span.start.line_number(), &input_definition(input));
+        }
+    }, "}");
+}
+
+fn write_directive_definition(writer: &mut Writer, def: &DirectiveDefinition) {
+    let mut locations: Vec<_> = def.locations.iter().map(ToString::to_string).collect();
+    locations.sort();
+    write_with_input_definitions(
+        writer,
+        def.span.start,
+        &format!("directive @{}", def.ident),
+        &def.input_definitions,
+        &format!(" on {}", locations.join(" | ")),
+    );
+}
+
+/// Writes `header {`, the block's lines and its closing line, or `header {}` if the block is empty
+/// (and has no comments). `pos` is where the header is in the source.
+fn write_block(writer: &mut Writer, pos: Position, header: &str, empty: bool, lines: impl FnOnce(&mut Writer), close: &str) {
+    let line = pos.line_number();
+    let close_line = writer.source().block_close_line(pos).unwrap_or(line);
+    if empty && !writer.has_comments_before(close_line) {
+        writer.line(line, &format!("{} {{{}", header, close));
+        return
+    }
+    writer.open(line, &format!("{} {{", header));
+    lines(writer);
+    writer.close(close_line, close);
+}
+
+/// Directives applied to a type, enum or input type, each on its own line before it
+fn write_item_directives(writer: &mut Writer, directives: &[AppliedDirective]) {
+    for directive in directives {
+        writer.line(directive.span.start.line_number(), &print_applied_directive(directive));
+    }
+}
+
+fn inline_directives(directives: &[AppliedDirective]) -> String {
+    directives.iter().map(|directive| format!(" {}", print_applied_directive(directive))).collect()
+}
+
+fn sorted_input_definitions(inputs: &InputDefinitions) -> Vec<&InputDefinition> {
+    let mut inputs: Vec<_> = inputs.values().collect();
+    inputs.sort_by_key(|input| input.span.start);
+    inputs
+}
+
+fn input_definition(input: &InputDefinition) -> String {
+    format!(
+        "{}: {}{}{}",
+        input.ident,
+        input.input_kind,
+        input.default.as_ref().map(|default| format!(" = {}", print_input(default))).unwrap_or_default(),
+        inline_directives(&input.directives),
+    )
+}
+
+/// Writes `header`, the inputs of a field or directive definition and `rest` on one line, or
+/// one input per line if there are comments (or docs) between the inputs' parentheses.
+/// `pos` is where the definition is in the source.
+fn write_with_input_definitions(writer: &mut Writer, pos: Position, header: &str, inputs: &InputDefinitions, rest: &str) {
+    let line = pos.line_number();
+    let (open_line, close_line) = match inputs.is_empty() {
+        true => None,
+        false => writer.source().parens_lines_after(pos),
+    }.unwrap_or((line, line));
+    if !writer.has_comments_between(open_line, close_line) {
+        writer.line(line, &format!("{}{}{}", header, inline_input_definitions(inputs), rest));
+        return
+    }
+    writer.open(line, &format!("{}(", header));
+    for input in sorted_input_definitions(inputs) {
+        writer.line(input.span.start.line_number(), &input_definition(input));
+    }
+    writer.close(close_line, &format!("){}", rest));
+}
+
+/// The inputs of a field or directive definition on one line, eg: `(size: number = 48, crop: Crop)`
+fn inline_input_definitions(inputs: &InputDefinitions) -> String {
+    if inputs.is_empty() {
+        return String::new()
+    }
+    let inputs: Vec<_> = sorted_input_definitions(inputs).into_iter().map(input_definition).collect();
+    format!("({})", inputs.join(", "))
+}
@@ -0,0 +1,113 @@
+use castle_error::CastleError;
+use castle_input_cursor::Position;
+use castle_tokenizer::{Punctuator, Token, TokenKind, Tokenizable, Tokenizer};
+
+/// A `# comment` in the source
+#[derive(Debug)]
+pub(crate) struct Comment {
+    /// The text after the `#`
+    pub text: Box<str>,
+    pub line: u32,
+    /// Whether the comment is after some code on the same line
+    pub trailing: bool,
+}
+
+/// What the formatter needs from the source that isn't in the parsed definitions:
+/// the comments, and the tokens to find where blocks are closed
+pub(crate) struct Source {
+    pub comments: Vec<Comment>,
+    tokens: Vec<Token>,
+}
+
+impl Source {
+    pub fn new(src: &str) -> Result<Source, CastleError> {
+        let mut tokenizer = Tokenizer::with_comments(src.as_bytes());
+        let mut comments = Vec::new();
+        let mut tokens: Vec<Token> = Vec::new();
+        let mut last_code_line = 0;
+        while let Some(token) = tokenizer.next(true)? {
+            match token.kind {
+                TokenKind::Comment(text) => comments.push(Comment {
+                    text,
+                    line: token.span.start.line_number(),
+                    trailing: last_code_line == token.span.start.line_number(),
+                }),
+                _ => {
+                    last_code_line = token.span.end.line_number();
+                    tokens.push(token);
+                },
+            }
+        }
+        Ok(Source { comments, tokens })
+    }
+
+    /// The line of the first token, eg: the `message` keyword
+    pub fn first_line(&self) -> u32 {
+        self.tokens.first().map(|token| token.span.start.line_number()).unwrap_or(1)
+    }
+
+    /// Finds the first `{` or `[` after `pos` (skipping anything in parentheses, eg: inputs)
+    /// and returns the index of its token
+    fn block_after(&self, pos: Position) -> Option<usize> {
+        let mut parens = 0;
+        for (index, token) in self.tokens.iter().enumerate().filter(|(_, token)| token.span.start > pos) {
+            match token.kind {
+                TokenKind::Punctuator(Punctuator::OpenParen) => parens += 1,
+                TokenKind::Punctuator(Punctuator::CloseParen) => parens -= 1,
+                TokenKind::Punctuator(Punctuator::OpenBlock | Punctuator::OpenBracket) if parens == 0 => return Some(index),
+                _ => {},
+            }
+        }
+        None
+    }
+
+    /// The index of the token closing the block opened by the token at `open`
+    fn block_close(&self, open: usize) -> Option<usize> {
+        let mut depth = 0;
+        for (index, token) in self.tokens.iter().enumerate().skip(open) {
+            match token.kind {
+                TokenKind::Punctuator(Punctuator::OpenBlock | Punctuator::OpenBracket | Punctuator::OpenParen) => depth += 1,
+                TokenKind::Punctuator(Punctuator::CloseBlock | Punctuator::CloseBracket | Punctuator::CloseParen) => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(index)
+                    }
+                },
+                _ => {},
+            }
+        }
+        None
+    }
+
+    /// The lines of the first `(` after `pos` and of the `)` closing it, eg: the inputs of a field definition
+    pub fn parens_lines_after(&self, pos: Position) -> Option<(u32, u32)> {
+        let open = self.tokens
+            .iter()
+            .position(|token| token.span.start > pos && token.kind == TokenKind::Punctuator(Punctuator::OpenParen))?;
+        let close = self.block_close(open)?;
+        Some((self.tokens[open].span.start.line_number(), self.tokens[close].span.start.line_number()))
+    }
+
+    /// The line of the `}` or `]` closing the first block after `pos`
+    pub fn block_close_line(&self, pos: Position) -> Option<u32> {
+        let close = self.block_close(self.block_after(pos)?)?;
+        Some(self.tokens[close].span.start.line_number())
+    }
+
+    /// Where the identifier `ident` is written directly inside the first block after `pos`,
+    /// eg: the arms of a match projection
+    pub fn ident_in_block_after(&self, pos: Position, ident: &str) -> Option<Position> {
+        let open = self.block_after(pos)?;
+        let close = self.block_close(open)?;
+        let mut depth = 0;
+        for token in &self.tokens[open + 1..close] {
+            match &token.kind {
+                TokenKind::Punctuator(Punctuator::OpenBlock | Punctuator::OpenBracket | Punctuator::OpenParen) => depth += 1,
+                TokenKind::Punctuator(Punctuator::CloseBlock | Punctuator::CloseBracket | Punctuator::CloseParen) => depth -= 1,
+                TokenKind::Identifier(name) if depth == 0 && &**name == ident => return Some(token.span.start),
+                _ => {},
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,131 @@
+use crate::source::Source;
+
+const INDENT: &str = "    ";
+
+/// Writes formatted lines, placing the source's comments between (or after) them.
+///
+/// Each line is written with the line it was on in the source, comments before that line
+/// are written first, and a blank line is kept if there was one in the source.
+pub(crate) struct Writer {
+    out: String,
+    indent: usize,
+    source: Source,
+    next_comment: usize,
+    last_line: u32,
+    blank_line: bool,
+}
+
+impl Writer {
+    pub fn new(source: Source) -> Writer {
+        Writer {
+            out: String::new(),
+            indent: 0,
+            source,
+            next_comment: 0,
+            last_line: 0,
+            blank_line: false,
+        }
+    }
+
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Writes a line that was on `line` in the source
+    pub fn line(&mut self, line: u32, text: &str) {
+        self.start_line(line, text);
+        self.end_line(line);
+    }
+
+    /// Writes a line opening a block (eg: `type User {`) and indents the following lines
+    pub fn open(&mut self, line: u32, text: &str) {
+        self.start_line(line, text);
+        self.end_line(line);
+        self.indent += 1;
+    }
+
+    /// Closes a block opened with [Writer::open], `line` is where the block is closed in the source
+    pub fn close(&mut self, line: u32, text: &str) {
+        self.comments_before(line);
+        self.blank_line = false;
+        self.indent -= 1;
+        self.write_indented(text);
+        self.end_line(line);
+    }
+
+    /// Whether there are comments before `line` that haven't been written
+    pub fn has_comments_before(&self, line: u32) -> bool {
+        self.source.comments.get(self.next_comment).is_some_and(|comment| comment.line < line)
+    }
+
+    /// Whether there are comments from line `start` up to (not including) line `end` that haven't been written
+    pub fn has_comments_between(&self, start: u32, end: u32) -> bool {
+        self.source.comments[self.next_comment..]
+            .iter()
+            .take_while(|comment| comment.line < end)
+            .any(|comment| comment.line >= start)
+    }
+
+    /// The next line written will be preceded by a blank line
+    pub fn blank_line(&mut self) {
+        if !self.out.is_empty() {
+            self.blank_line = true;
+        }
+    }
+
+    /// Writes the remaining comments and returns the formatted source
+    pub fn finish(mut self) -> String {
+        self.comments_before(u32::MAX);
+        self.out
+    }
+
+    fn start_line(&mut self, line: u32, text: &str) {
+        self.comments_before(line);
+        self.blank_line_if_skipped(line);
+        self.write_indented(text);
+    }
+
+    /// Writes the comment after the line if there's one on the same line, and ends the line
+    fn end_line(&mut self, line: u32) {
+        if let Some(comment) = self.source.comments.get(self.next_comment) {
+            if comment.line == line && comment.trailing {
+                self.out.push_str(&format!(" #{}", comment.text));
+                self.next_comment += 1;
+            }
+        }
+        self.out.push('\n');
+        self.last_line = self.last_line.max(line);
+    }
+
+    fn comments_before(&mut self, line: u32) {
+        while let Some(comment) = self.source.comments.get(self.next_comment) {
+            if comment.line >= line {
+                break
+            }
+            let (comment_line, text) = (comment.line, format!("#{}", comment.text));
+            self.next_comment += 1;
+            self.blank_line_if_skipped(comment_line);
+            self.write_indented(&text);
+            self.out.push('\n');
+            self.last_line = self.last_line.max(comment_line);
+        }
+    }
+
+    fn blank_line_if_skipped(&mut self, line: u32) {
+        let after_open = self.out.ends_with("{\n") || self.out.ends_with("[\n") || self.out.ends_with("(\n");
+        if self.last_line > 0 && line > self.last_line + 1 && !after_open {
+            self.blank_line();
+        }
+        if self.blank_line {
+            self.out.push('\n');
+            self.blank_line = false;
+        }
+    }
+
+    fn write_indented(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+    }
+}
@@ -0,0 +1,145 @@
+use castle_fmt::{format, format_message, format_schema};
+
+const SCHEMA: &str = r#"
+# The schema of the app
+
+directive @lowercase on FieldDefinition
+@cached(seconds: 60)
+type User { # users of the app
+    name: String @lowercase, age: number
+
+
+    # in pixels
+    avatar(size: number = 48
+        crop: Option<Crop>): String
+}
+enum Icon {
+    SVGIcon { url: String, size: number }
+    Emoji(String), NoIcon
+}
+input Crop { x: number, y: number = 0 }
+type Empty {}
+"#;
+
+const FORMATTED_SCHEMA: &str = r#"# The schema of the app
+
+directive @lowercase on FieldDefinition
+
+@cached(seconds: 60)
+type User { # users of the app
+    name: String @lowercase
+    age: number
+
+    # in pixels
+    avatar(size: number = 48, crop: Option<Crop>): String
+}
+
+enum Icon {
+    SVGIcon {
+        url: String
+        size: number
+    }
+    Emoji(String)
+    NoIcon
+}
+
+input Crop {
+    x: number
+    y: number = 0
+}
+
+type Empty {}
+"#;
+
+#[test]
+fn formats_schemas_keeping_order_and_comments() {
+    assert_eq!(format_schema(SCHEMA).unwrap(), FORMATTED_SCHEMA);
+}
+
+#[test]
+fn formatting_is_stable() {
+    assert_eq!(format_schema(FORMATTED_SCHEMA).unwrap(), FORMATTED_SCHEMA);
+    assert_eq!(format_message(FORMATTED_MESSAGE).unwrap(), FORMATTED_MESSAGE);
+}
+
+const MESSAGE: &str = r#"
+# get the user
+message GetUser($id: Uuid,$size: number = 48) {
+  user(id: $id) { name, avatar(size: $size) as small
+     icon match { Emoji
+        SVGIcon { url } }
+  friends [ name ]
+    # the last field
+  }
+}
+"#;
+
+const FORMATTED_MESSAGE: &str = r#"# get the user
+message GetUser($id: Uuid, $size: number = 48) {
+    user(id: $id) {
+        name
+        avatar(size: $size) as small
+        icon match {
+            Emoji
+            SVGIcon {
+                url
+            }
+        }
+        friends [
+            name
+        ]
+        # the last field
+    }
+}
+"#;
+
+#[test]
+fn formats_messages_keeping_order_and_comments() {
+    assert_eq!(format_message(MESSAGE).unwrap(), FORMATTED_MESSAGE);
+}
+
+#[test]
+fn format_detects_messages() {
+    assert_eq!(format(MESSAGE).unwrap(), FORMATTED_MESSAGE);
+    assert_eq!(format(SCHEMA).unwrap(), FORMATTED_SCHEMA);
+}
+
+#[test]
+fn comments_at_the_end_of_blocks_stay_in_the_block() {
+    let schema = "type User {\n    name: String\n    # more fields soon\n}\n# the end\n";
+    assert_eq!(format_schema(schema).unwrap(), schema);
+
+    let schema = "type Todo {\n    # nothing yet\n}\n";
+    assert_eq!(format_schema(schema).unwrap(), schema);
+}
+
+#[test]
+fn doc_comments_stay_before_their_definitions() {
+    let schema = "## A person\ntype User {\n    /// shown to others\n    name: String\n}\n";
+    assert_eq!(format_schema(schema).unwrap(), "## A person\ntype User {\n    ## shown to others\n    name: String\n}\n");
+}
+
+#[test]
+fn documented_inputs_stay_on_their_own_lines() {
+    let schema = "type User {\n    avatar(\n        ## the size\n        size: number,\n        crop: String\n    ): String\n}\n";
+    let formatted = "type User {\n    avatar(\n        ## the size\n        size: number\n        crop: String\n    ): String\n}\n";
+    assert_eq!(format_schema(schema).unwrap(), formatted);
+    assert_eq!(format_schema(formatted).unwrap(), formatted);
+}
+
+#[test]
+fn comments_in_input_lists_keep_their_place() {
+    let schema = "directive @resize(\n    size: number, # px\n    # the rest\n    crop: String\n) on FieldDefinition\n";
+    let formatted = "directive @resize(\n    size: number # px\n    # the rest\n    crop: String\n) on FieldDefinition\n";
+    assert_eq!(format_schema(schema).unwrap(), formatted);
+    assert_eq!(format_schema(formatted).unwrap(), formatted);
+
+    let schema = "type User {\n    avatar(size: number): String # in pixels\n}\n";
+    assert_eq!(format_schema(schema).unwrap(), schema);
+}
+
+#[test]
+fn invalid_sources_are_not_formatted() {
+    assert!(format_schema("type User {").is_err());
+    assert!(format_message("message { user(id: }").is_err());
+}
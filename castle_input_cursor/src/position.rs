@@ -40,6 +40,13 @@ impl Position {
     }
 }
 
+impl Default for Position {
+    /// The start of the source (line 1, column 1)
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
 impl fmt::Display for Position {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}:{}", self.line_number, self.column_number)
@@ -49,7 +56,10 @@ impl fmt::Display for Position {
 /// A span in the source code.
 ///
 /// Stores a start position and an end position.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+///
+/// The default span is empty and sits at the start of the source, which is used for nodes
+/// that weren't parsed from source (eg: ones built in code).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 pub struct Span {
     pub start: Position,
     pub end: Position,
@@ -88,6 +98,14 @@ impl Span {
     }
 }
 
+impl Span {
+    /// Creates a span that starts where `self` starts and ends where `other` ends.
+    #[inline]
+    pub fn to(self, other: Self) -> Self {
+        Self::new(self.start, other.end.max(self.start))
+    }
+}
+
 impl From<Position> for Span {
     fn from(pos: Position) -> Self {
         Self {
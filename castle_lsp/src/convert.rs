@@ -0,0 +1,86 @@
+use castle_error::CastleError;
+use castle_input_cursor::{Position, Span};
+use tower_lsp::lsp_types::{self, Diagnostic, DiagnosticSeverity, Range};
+
+/// Castle positions are 1-based and count characters, LSP positions are 0-based and count
+/// UTF-16 code units, so the line's text is needed to convert between them
+pub(crate) fn to_lsp_position(src: &str, pos: Position) -> lsp_types::Position {
+    let line = pos.line_number() - 1;
+    let columns = pos.column_number() as usize - 1;
+    let character = match src.lines().nth(line as usize) {
+        Some(text) => text.chars().take(columns).map(char::len_utf16).sum::<usize>(),
+        None => columns,
+    };
+    lsp_types::Position::new(line, character as u32)
+}
+
+pub(crate) fn from_lsp_position(src: &str, pos: lsp_types::Position) -> Position {
+    let mut units = 0;
+    let columns = match src.lines().nth(pos.line as usize) {
+        Some(text) => text
+            .chars()
+            .take_while(|c| {
+                units += c.len_utf16();
+                units <= pos.character as usize
+            })
+            .count(),
+        None => pos.character as usize,
+    };
+    Position::new(pos.line + 1, columns as u32 + 1)
+}
+
+pub(crate) fn to_range(src: &str, span: Span) -> Range {
+    Range::new(to_lsp_position(src, span.start), to_lsp_position(src, span.end))
+}
+
+/// The position after the last character of the source
+fn end_of(src: &str) -> Position {
+    let line = src.matches('\n').count() + 1;
+    let column = src.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    Position::new(line as u32, column as u32)
+}
+
+/// The span of `ident` when it is written starting at `start`
+pub(crate) fn ident_span(start: Position, ident: &str) -> Span {
+    let end = Position::new(start.line_number(), start.column_number() + ident.chars().count() as u32);
+    Span::new(start, end)
+}
+
+/// The span of `ident` when it is written ending at `end`, eg: a directive name after its `@`.
+/// Starts at the start of the line if `ident` is longer than the span (eg: a span made up while
+/// recovering from an error)
+pub(crate) fn ident_span_ending(end: Position, ident: &str) -> Span {
+    let column = end.column_number().saturating_sub(ident.chars().count() as u32).max(1);
+    let start = Position::new(end.line_number(), column);
+    Span::new(start, end)
+}
+
+/// Every problem in the error as a diagnostic, an unexpected end of the source is put at the
+/// end of the document and other problems without a known location at the start
+pub(crate) fn to_diagnostics(src: &str, error: &CastleError) -> Vec<Diagnostic> {
+    let diagnostic = |span: Option<Span>, message: String| Diagnostic {
+        range: span.map(|span| to_range(src, span)).unwrap_or_default(),
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("castle".into()),
+        message,
+        ..Diagnostic::default()
+    };
+    match error {
+        CastleError::Multiple(diagnostics) => diagnostics
+            .iter()
+            .map(|d| diagnostic(d.span, d.error.to_string()))
+            .collect(),
+        CastleError::Syntax(msg, pos) => {
+            let end = Position::new(pos.line_number(), pos.column_number() + 1);
+            vec![diagnostic(Some(Span::new(*pos, end)), msg.to_string())]
+        },
+        CastleError::Parser(msg, span)
+        | CastleError::Schema(msg, span)
+        | CastleError::Root(msg, span) => vec![diagnostic(Some(*span), msg.to_string())],
+        CastleError::AbruptEOF(_) => {
+            let end = to_lsp_position(src, end_of(src));
+            vec![Diagnostic { range: Range::new(end, end), ..diagnostic(None, error.to_string()) }]
+        },
+        error => vec![diagnostic(None, error.to_string())],
+    }
+}
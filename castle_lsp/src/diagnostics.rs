@@ -0,0 +1,39 @@
+use castle_api::{check_message, check_schema};
+use castle_error::CastleError;
+use castle_query_parser::parse_message_recovering;
+use castle_schema_parser::{parsers::parse_schema::parse_schema_recovering, types::SchemaDefinition};
+use tower_lsp::lsp_types::Diagnostic;
+
+use crate::convert::to_diagnostics;
+
+/// Every problem found parsing and validating the schema, it is only validated if it parses
+/// (otherwise missing definitions would be reported too)
+pub fn schema_diagnostics(src: &str) -> Vec<Diagnostic> {
+    let (schema, errors) = parse_schema_recovering(src);
+    match errors.is_empty() {
+        true => check_schema(&schema).err().into_iter().flat_map(|e| to_diagnostics(src, &e)).collect(),
+        false => all_diagnostics(src, &errors),
+    }
+}
+
+/// Every problem found parsing the message and, if it parses and there is a schema,
+/// validating it
+pub fn message_diagnostics(src: &str, schema: Option<&SchemaDefinition>) -> Vec<Diagnostic> {
+    let (message, errors) = parse_message_recovering(src);
+    match (errors.is_empty(), schema) {
+        (true, Some(schema)) => check_message(schema, &message)
+            .err()
+            .into_iter()
+            .flat_map(|e| to_diagnostics(src, &e))
+            .collect(),
+        (true, None) => vec![],
+        (false, _) => all_diagnostics(src, &errors),
+    }
+}
+
+/// Blocks cut short by the end of the source each report it, so repeats are left out
+fn all_diagnostics(src: &str, errors: &[CastleError]) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<_> = errors.iter().flat_map(|e| to_diagnostics(src, e)).collect();
+    diagnostics.dedup();
+    diagnostics
+}
@@ -0,0 +1,222 @@
+//! A language server for Castle schemas and messages, speaking LSP over stdio.
+//!
+//! Publishes diagnostics, completes kinds, directives and message fields, and supports
+//! go-to-definition and find-references.
+//!
+//! Messages are checked against the schema given by the `schema` initialization option
+//! (relative to the workspace), or against an open schema if there isn't one.
+use std::{collections::BTreeMap, fs, path::Path};
+
+use castle_schema_parser::{parsers::parse_schema::parse_schema, types::SchemaDefinition};
+use castle_tokenizer::{Keyword, TokenKind};
+use tokio::sync::RwLock;
+use tower_lsp::{
+    jsonrpc::Result,
+    lsp_types::{
+        CompletionOptions, CompletionParams, CompletionResponse, DidChangeTextDocumentParams,
+        DidCloseTextDocumentParams, DidOpenTextDocumentParams, GotoDefinitionParams,
+        GotoDefinitionResponse, InitializeParams, InitializeResult, Location, OneOf,
+        ReferenceParams, ServerCapabilities, ServerInfo, TextDocumentSyncCapability,
+        TextDocumentSyncKind, Url,
+    },
+    Client, LanguageServer, LspService, Server,
+};
+
+mod convert;
+mod diagnostics;
+mod message;
+mod schema;
+mod tokens;
+
+pub use diagnostics::{message_diagnostics, schema_diagnostics};
+pub use message::{message_completions, message_definition};
+pub use schema::{schema_completions, schema_definition, schema_references};
+
+/// Runs the language server on stdin and stdout until the client exits
+pub async fn serve_stdio() {
+    let (service, socket) = LspService::new(Backend::new);
+    Server::new(tokio::io::stdin(), tokio::io::stdout(), socket)
+        .serve(service)
+        .await;
+}
+
+/// Whether the source is a message, ie: its first token is the `message` keyword
+pub fn is_message(src: &str) -> bool {
+    matches!(tokens::lex(src).first().map(|token| &token.kind), Some(TokenKind::Keyword(Keyword::Message)))
+}
+
+/// The schema messages are checked against
+struct Schema {
+    uri: Url,
+    src: String,
+    definition: SchemaDefinition,
+}
+
+pub struct Backend {
+    client: Client,
+    /// The source of every open document
+    documents: RwLock<BTreeMap<Url, String>>,
+    /// The schema set by the `schema` initialization option
+    schema_uri: RwLock<Option<Url>>,
+}
+
+impl Backend {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            documents: RwLock::new(BTreeMap::new()),
+            schema_uri: RwLock::new(None),
+        }
+    }
+
+    async fn document(&self, uri: &Url) -> Option<String> {
+        self.documents.read().await.get(uri).cloned()
+    }
+
+    /// The configured schema (open or on disk), otherwise an open schema. `None` if there is no
+    /// schema or it doesn't parse
+    async fn schema(&self) -> Option<Schema> {
+        let (uri, src) = match self.schema_uri.read().await.clone() {
+            Some(uri) => match self.document(&uri).await {
+                Some(src) => (uri, src),
+                None => {
+                    let src = fs::read_to_string(uri.to_file_path().ok()?).ok()?;
+                    (uri, src)
+                },
+            },
+            None => self
+                .documents
+                .read()
+                .await
+                .iter()
+                .find(|(_, src)| !is_message(src))
+                .map(|(uri, src)| (uri.clone(), src.clone()))?,
+        };
+        let definition = parse_schema(&src).ok()?;
+        Some(Schema { uri, src, definition })
+    }
+
+    /// Publishes the diagnostics of every open document, messages depend on the schema so a
+    /// change to any document can change them
+    async fn publish_diagnostics(&self) {
+        let schema = self.schema().await;
+        let documents = self.documents.read().await.clone();
+        for (uri, src) in documents {
+            let diagnostics = match is_message(&src) {
+                true => message_diagnostics(&src, schema.as_ref().map(|schema| &schema.definition)),
+                false => schema_diagnostics(&src),
+            };
+            self.client.publish_diagnostics(uri, diagnostics, None).await;
+        }
+    }
+}
+
+/// The `schema` initialization option, resolved against the workspace
+fn configured_schema(params: &InitializeParams) -> Option<Url> {
+    let schema = params.initialization_options.as_ref()?.get("schema")?.as_str()?;
+    let root = params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .and_then(|folder| folder.uri.to_file_path().ok());
+    let path = match root {
+        Some(root) => root.join(schema),
+        None => Path::new(schema).to_path_buf(),
+    };
+    Url::from_file_path(path).ok()
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        *self.schema_uri.write().await = configured_schema(&params);
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["@".into(), ":".into(), "<".into()]),
+                    ..CompletionOptions::default()
+                }),
+                definition_provider: Some(OneOf::Left(true)),
+                references_provider: Some(OneOf::Left(true)),
+                ..ServerCapabilities::default()
+            },
+            server_info: Some(ServerInfo {
+                name: "castle-lsp".into(),
+                version: Some(env!("CARGO_PKG_VERSION").into()),
+            }),
+        })
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = params.text_document;
+        self.documents.write().await.insert(document.uri, document.text);
+        self.publish_diagnostics().await;
+    }
+
+    async fn did_change(&self, mut params: DidChangeTextDocumentParams) {
+        // documents are synced in full, so the last change is the whole document
+        if let Some(change) = params.content_changes.pop() {
+            self.documents.write().await.insert(params.text_document.uri, change.text);
+        }
+        self.publish_diagnostics().await;
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        let uri = params.text_document.uri;
+        self.documents.write().await.remove(&uri);
+        self.client.publish_diagnostics(uri, vec![], None).await;
+        self.publish_diagnostics().await;
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let position = params.text_document_position;
+        let src = match self.document(&position.text_document.uri).await {
+            Some(src) => src,
+            None => return Ok(None),
+        };
+        let items = match is_message(&src) {
+            true => match self.schema().await {
+                Some(schema) => message_completions(&src, position.position, &schema.definition),
+                None => vec![],
+            },
+            false => schema_completions(&src, position.position),
+        };
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    async fn goto_definition(&self, params: GotoDefinitionParams) -> Result<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params;
+        let uri = position.text_document.uri;
+        let src = match self.document(&uri).await {
+            Some(src) => src,
+            None => return Ok(None),
+        };
+        let location = match is_message(&src) {
+            true => self.schema().await.and_then(|schema| {
+                message_definition(&src, position.position, &schema.definition, &schema.src)
+                    .map(|range| Location::new(schema.uri, range))
+            }),
+            false => schema_definition(&src, position.position).map(|range| Location::new(uri, range)),
+        };
+        Ok(location.map(GotoDefinitionResponse::Scalar))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let position = params.text_document_position;
+        let uri = position.text_document.uri;
+        let src = match self.document(&uri).await {
+            Some(src) if !is_message(&src) => src,
+            _ => return Ok(None),
+        };
+        let references = schema_references(&src, position.position, params.context.include_declaration)
+            .into_iter()
+            .map(|range| Location::new(uri.clone(), range))
+            .collect();
+        Ok(Some(references))
+    }
+}
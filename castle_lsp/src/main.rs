@@ -0,0 +1,4 @@
+#[tokio::main]
+async fn main() {
+    castle_lsp::serve_stdio().await;
+}
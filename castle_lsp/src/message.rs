@@ -0,0 +1,161 @@
+use castle_schema_parser::types::{EnumDefinition, Kind, SchemaDefinition, TypeDefinition, VariantKindDefinition};
+use castle_tokenizer::{Keyword, Punctuator, Token, TokenKind};
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Documentation, Position, Range};
+
+use crate::{
+    convert::{from_lsp_position, ident_span, to_range},
+    tokens::{identifier_at, lex, tokens_before},
+};
+
+/// What the identifiers in a block of a message name
+#[derive(Debug, Clone, Copy)]
+enum Scope<'a> {
+    /// Fields of a type, eg: inside `message { ... }` or `user { ... }`
+    Type(&'a TypeDefinition),
+    /// Variants of an enum, eg: inside `icon match { ... }`
+    Enum(&'a EnumDefinition),
+    /// Fields of a map variant, eg: inside `SVGIcon { ... }` of a match
+    Variant(&'a VariantKindDefinition),
+    /// Somewhere the schema doesn't describe, eg: a field that doesn't exist
+    Unknown,
+}
+
+/// Completes the field (or the variant, in a match) being typed at `pos` from the schema
+///
+/// This only uses the tokens of the message, so it works while the message is being edited
+pub fn message_completions(src: &str, pos: Position, schema: &SchemaDefinition) -> Vec<CompletionItem> {
+    let tokens = lex(src);
+    let (scope, in_inputs) = scope_after(schema, tokens_before(&tokens, from_lsp_position(src, pos)));
+    if in_inputs {
+        return vec![]
+    }
+    match scope {
+        Scope::Type(type_def) => type_def
+            .fields
+            .values()
+            .map(|field| CompletionItem {
+                documentation: documentation(&field.docs),
+                ..completion(&field.ident, CompletionItemKind::FIELD, &field.return_kind)
+            })
+            .collect(),
+        Scope::Variant(VariantKindDefinition::Map(map)) => map
+            .iter()
+            .map(|(ident, kind)| completion(ident, CompletionItemKind::FIELD, kind))
+            .collect(),
+        Scope::Enum(enum_def) => enum_def
+            .variants
+            .values()
+            .map(|variant| CompletionItem {
+                label: variant.ident.to_string(),
+                kind: Some(CompletionItemKind::ENUM_MEMBER),
+                documentation: documentation(&variant.docs),
+                ..CompletionItem::default()
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Where the field (or variant, in a match) at `pos` is defined, as a range in the schema's
+/// source `schema_src`
+pub fn message_definition(src: &str, pos: Position, schema: &SchemaDefinition, schema_src: &str) -> Option<Range> {
+    let tokens = lex(src);
+    let index = identifier_at(&tokens, from_lsp_position(src, pos))?;
+    let name = match &tokens[index].kind {
+        TokenKind::Identifier(name) => &**name,
+        _ => return None,
+    };
+    // a rename isn't defined anywhere
+    if let Some(TokenKind::Keyword(Keyword::As)) = index.checked_sub(1).map(|i| &tokens[i].kind) {
+        return None
+    }
+    let span = match scope_after(schema, &tokens[..index]) {
+        (_, true) => None,
+        (Scope::Type(type_def), _) => type_def.fields.get(name).map(|field| field.span),
+        (Scope::Enum(enum_def), _) => enum_def.variants.get(name).map(|variant| variant.span),
+        // the fields of map variants are only known by their kind
+        _ => None,
+    }?;
+    Some(to_range(schema_src, ident_span(span.start, name)))
+}
+
+fn completion(ident: &str, kind: CompletionItemKind, return_kind: &Kind) -> CompletionItem {
+    CompletionItem {
+        label: ident.to_string(),
+        kind: Some(kind),
+        detail: Some(return_kind.to_string()),
+        ..CompletionItem::default()
+    }
+}
+
+/// The doc comments of a field or variant, shown next to its completion
+fn documentation(docs: &Option<Box<str>>) -> Option<Documentation> {
+    docs.as_ref().map(|docs| Documentation::String(docs.to_string()))
+}
+
+/// Follows the blocks of the message through the schema to find what the block at the end
+/// of `tokens` is projecting, also returns whether the end is inside parentheses (inputs or
+/// variable definitions)
+fn scope_after<'a>(schema: &'a SchemaDefinition, tokens: &[Token]) -> (Scope<'a>, bool) {
+    let mut scopes: Vec<Scope> = Vec::new();
+    // what a block opened next would project, from the last identifier
+    let mut next = Scope::Unknown;
+    let mut parens = 0;
+    let mut renaming = false;
+    for token in tokens {
+        match &token.kind {
+            TokenKind::Punctuator(Punctuator::OpenParen) => parens += 1,
+            TokenKind::Punctuator(Punctuator::CloseParen) => parens -= 1,
+            _ if parens > 0 => {},
+            TokenKind::Keyword(Keyword::As) => renaming = true,
+            TokenKind::Identifier(_) if renaming => renaming = false,
+            TokenKind::Identifier(name) => {
+                next = match scopes.last() {
+                    Some(scope) => child_scope(schema, *scope, name),
+                    None => Scope::Unknown,
+                }
+            },
+            TokenKind::Punctuator(Punctuator::OpenBlock | Punctuator::OpenBracket) => {
+                let scope = match scopes.is_empty() {
+                    true => schema.types.get("Root").map_or(Scope::Unknown, Scope::Type),
+                    false => next,
+                };
+                scopes.push(scope);
+                next = Scope::Unknown;
+            },
+            TokenKind::Punctuator(Punctuator::CloseBlock | Punctuator::CloseBracket) => {
+                scopes.pop();
+            },
+            _ => {},
+        }
+    }
+    (scopes.last().copied().unwrap_or(Scope::Unknown), parens > 0)
+}
+
+/// What a block after the identifier `name` in `scope` projects
+fn child_scope<'a>(schema: &'a SchemaDefinition, scope: Scope<'a>, name: &str) -> Scope<'a> {
+    let kind = match scope {
+        Scope::Type(type_def) => type_def.fields.get(name).map(|field| &field.return_kind),
+        Scope::Variant(VariantKindDefinition::Map(map)) => map.get(name),
+        Scope::Enum(enum_def) => {
+            return enum_def
+                .variants
+                .get(name)
+                .map_or(Scope::Unknown, |variant| Scope::Variant(&variant.kind))
+        },
+        _ => None,
+    };
+    kind.map_or(Scope::Unknown, |kind| kind_scope(schema, kind))
+}
+
+/// Lists and options project the fields of what they contain
+fn kind_scope<'a>(schema: &'a SchemaDefinition, kind: &Kind) -> Scope<'a> {
+    match (&*kind.ident, kind.generics.first()) {
+        ("Vec" | "Option", Some(inner)) => kind_scope(schema, inner),
+        (ident, _) => match (schema.types.get(ident), schema.enums.get(ident)) {
+            (Some(type_def), _) => Scope::Type(type_def),
+            (_, Some(enum_def)) => Scope::Enum(enum_def),
+            _ => Scope::Unknown,
+        },
+    }
+}
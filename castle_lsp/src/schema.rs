@@ -0,0 +1,230 @@
+use castle_input_cursor::Span;
+use castle_schema_parser::{
+    parsers::parse_schema::parse_schema,
+    types::{AppliedDirective, InputDefinitions, Kind, SchemaDefinition, VariantKindDefinition},
+};
+use castle_tokenizer::{Keyword, Punctuator, Token, TokenKind};
+use tower_lsp::lsp_types::{CompletionItem, CompletionItemKind, Position, Range};
+
+use crate::{
+    convert::{from_lsp_position, ident_span, ident_span_ending, to_range},
+    tokens::{identifier_at, lex, tokens_before},
+};
+
+/// The kinds that are always available without being defined in the schema
+const BUILT_IN_KINDS: [&str; 7] = ["String", "number", "bool", "void", "Uuid", "Vec", "Option"];
+
+/// Directives and kinds have separate names, eg: `@cached` and `Cached` can both exist
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Namespace {
+    Kind,
+    Directive,
+}
+
+/// Somewhere a type, enum, input or directive is named in the schema
+#[derive(Debug)]
+struct Occurrence<'a> {
+    name: &'a str,
+    namespace: Namespace,
+    span: Span,
+    declaration: bool,
+}
+
+/// Completes the identifier being typed at `pos`:
+/// - directive names after `@`
+/// - types, enums, inputs and built-in kinds after `:`, or inside the `<>` of generics
+///
+/// This only uses the tokens of the schema, so it works while the schema is being edited
+pub fn schema_completions(src: &str, pos: Position) -> Vec<CompletionItem> {
+    let tokens = lex(src);
+    let before = tokens_before(&tokens, from_lsp_position(src, pos));
+    let generics_depth = before.iter().fold(0i32, |depth, token| match token.kind {
+        TokenKind::Punctuator(Punctuator::GenericOpen) => depth + 1,
+        TokenKind::Punctuator(Punctuator::GenericClose) => depth - 1,
+        _ => depth,
+    });
+    let declared = declared_names(&tokens);
+    match before.last().map(|token| &token.kind) {
+        Some(TokenKind::Punctuator(Punctuator::At)) => declared
+            .into_iter()
+            .filter(|(keyword, _)| *keyword == Keyword::Directive)
+            .map(|(keyword, name)| completion(name, keyword))
+            .collect(),
+        Some(TokenKind::Punctuator(Punctuator::Colon | Punctuator::GenericOpen)) => kind_completions(declared),
+        Some(TokenKind::Punctuator(Punctuator::Comma)) if generics_depth > 0 => kind_completions(declared),
+        _ => vec![],
+    }
+}
+
+/// Where the type, enum, input or directive at `pos` is defined
+pub fn schema_definition(src: &str, pos: Position) -> Option<Range> {
+    let schema = parse_schema(src).ok()?;
+    let occurrences = occurrences(&schema);
+    let target = occurrence_at(src, &occurrences, pos)?;
+    occurrences
+        .iter()
+        .find(|occurrence| occurrence.declaration && same_symbol(occurrence, target))
+        .map(|occurrence| to_range(src, occurrence.span))
+}
+
+/// Everywhere the type, enum, input or directive at `pos` is named in the schema
+pub fn schema_references(src: &str, pos: Position, include_declaration: bool) -> Vec<Range> {
+    let schema = match parse_schema(src) {
+        Ok(schema) => schema,
+        Err(_) => return vec![],
+    };
+    let occurrences = occurrences(&schema);
+    let target = match occurrence_at(src, &occurrences, pos) {
+        Some(target) => target,
+        None => return vec![],
+    };
+    let mut references: Vec<_> = occurrences
+        .iter()
+        .filter(|occurrence| same_symbol(occurrence, target) && (include_declaration || !occurrence.declaration))
+        .map(|occurrence| occurrence.span)
+        .collect();
+    references.sort_by_key(|span| span.start);
+    references.into_iter().map(|span| to_range(src, span)).collect()
+}
+
+fn kind_completions(declared: Vec<(Keyword, &str)>) -> Vec<CompletionItem> {
+    let built_ins = BUILT_IN_KINDS.iter().map(|name| CompletionItem {
+        label: name.to_string(),
+        kind: Some(CompletionItemKind::KEYWORD),
+        detail: Some("built-in".into()),
+        ..CompletionItem::default()
+    });
+    declared
+        .into_iter()
+        .filter(|(keyword, _)| *keyword != Keyword::Directive)
+        .map(|(keyword, name)| completion(name, keyword))
+        .chain(built_ins)
+        .collect()
+}
+
+fn completion(name: &str, keyword: Keyword) -> CompletionItem {
+    let (kind, detail) = match keyword {
+        Keyword::Enum => (CompletionItemKind::ENUM, "enum"),
+        Keyword::Input => (CompletionItemKind::STRUCT, "input"),
+        Keyword::Directive => (CompletionItemKind::FUNCTION, "directive"),
+        _ => (CompletionItemKind::STRUCT, "type"),
+    };
+    CompletionItem {
+        label: name.to_string(),
+        kind: Some(kind),
+        detail: Some(detail.into()),
+        ..CompletionItem::default()
+    }
+}
+
+/// The names declared with `type`, `enum`, `input` and `directive @` in the tokens
+fn declared_names(tokens: &[Token]) -> Vec<(Keyword, &str)> {
+    let mut declared = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let keyword = match token.kind {
+            TokenKind::Keyword(keyword @ (Keyword::Type | Keyword::Enum | Keyword::Input | Keyword::Directive)) => keyword,
+            _ => continue,
+        };
+        let name = match keyword {
+            Keyword::Directive => match tokens.get(index + 1).map(|token| &token.kind) {
+                Some(TokenKind::Punctuator(Punctuator::At)) => tokens.get(index + 2),
+                _ => None,
+            },
+            _ => tokens.get(index + 1),
+        };
+        if let Some(TokenKind::Identifier(name)) = name.map(|token| &token.kind) {
+            declared.push((keyword, &**name));
+        }
+    }
+    declared
+}
+
+fn same_symbol(a: &Occurrence, b: &Occurrence) -> bool {
+    a.name == b.name && a.namespace == b.namespace
+}
+
+fn occurrence_at<'a, 'b>(src: &str, occurrences: &'b [Occurrence<'a>], pos: Position) -> Option<&'b Occurrence<'a>> {
+    let tokens = lex(src);
+    let ident = &tokens[identifier_at(&tokens, from_lsp_position(src, pos))?];
+    occurrences.iter().find(|occurrence| occurrence.span == ident.span)
+}
+
+/// Every place a type, enum, input or directive is named in the schema, kinds that aren't
+/// defined in the schema (eg: built-in kinds) are left out
+fn occurrences(schema: &SchemaDefinition) -> Vec<Occurrence<'_>> {
+    let mut occurrences = Vec::new();
+    let mut declare = |name, namespace, span| occurrences.push(Occurrence { name, namespace, span, declaration: true });
+    for type_def in schema.types.values() {
+        declare(&*type_def.ident, Namespace::Kind, type_def.span);
+    }
+    for enum_def in schema.enums.values() {
+        declare(&*enum_def.ident, Namespace::Kind, enum_def.span);
+    }
+    for input_def in schema.input_types.values() {
+        declare(&*input_def.ident, Namespace::Kind, input_def.span);
+    }
+    for directive_def in schema.directives.values() {
+        declare(&*directive_def.ident, Namespace::Directive, ident_span_ending(directive_def.span.end, &directive_def.ident));
+    }
+
+    for type_def in schema.types.values() {
+        directives(&type_def.directives, &mut occurrences);
+        for field in type_def.fields.values() {
+            kind(schema, &field.return_kind, &mut occurrences);
+            inputs(schema, &field.input_definitions, &mut occurrences);
+            directives(&field.directives, &mut occurrences);
+        }
+    }
+    for enum_def in schema.enums.values() {
+        directives(&enum_def.directives, &mut occurrences);
+        for variant in enum_def.variants.values() {
+            directives(&variant.directives, &mut occurrences);
+            match &variant.kind {
+                VariantKindDefinition::Unit => {},
+                VariantKindDefinition::Tuple(kinds) => kinds.iter().for_each(|k| kind(schema, k, &mut occurrences)),
+                VariantKindDefinition::Map(map) => map.values().for_each(|k| kind(schema, k, &mut occurrences)),
+            }
+        }
+    }
+    for input_def in schema.input_types.values() {
+        directives(&input_def.directives, &mut occurrences);
+        inputs(schema, &input_def.input_definitions, &mut occurrences);
+    }
+    for directive_def in schema.directives.values() {
+        inputs(schema, &directive_def.input_definitions, &mut occurrences);
+    }
+    occurrences
+}
+
+fn kind<'a>(schema: &SchemaDefinition, kind_ref: &'a Kind, occurrences: &mut Vec<Occurrence<'a>>) {
+    let name = &*kind_ref.ident;
+    if schema.types.contains_key(name) || schema.enums.contains_key(name) || schema.input_types.contains_key(name) {
+        occurrences.push(Occurrence {
+            name,
+            namespace: Namespace::Kind,
+            span: ident_span(kind_ref.span.start, name),
+            declaration: false,
+        });
+    }
+    for generic in &kind_ref.generics {
+        kind(schema, generic, occurrences);
+    }
+}
+
+fn inputs<'a>(schema: &SchemaDefinition, input_definitions: &'a InputDefinitions, occurrences: &mut Vec<Occurrence<'a>>) {
+    for input in input_definitions.values() {
+        kind(schema, &input.input_kind, occurrences);
+        directives(&input.directives, occurrences);
+    }
+}
+
+fn directives<'a>(applied: &'a [AppliedDirective], occurrences: &mut Vec<Occurrence<'a>>) {
+    for directive in applied {
+        occurrences.push(Occurrence {
+            name: &directive.ident,
+            namespace: Namespace::Directive,
+            span: ident_span_ending(directive.span.end, &directive.ident),
+            declaration: false,
+        });
+    }
+}
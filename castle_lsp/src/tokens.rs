@@ -0,0 +1,29 @@
+use castle_input_cursor::Position;
+use castle_tokenizer::{Token, TokenKind, Tokenizable, Tokenizer};
+
+/// Tokenizes as much of the source as possible, documents are often invalid while they are
+/// being edited so everything up to the first error is still useful
+pub(crate) fn lex(src: &str) -> Vec<Token> {
+    let mut tokenizer = Tokenizer::new(src.as_bytes());
+    let mut tokens = Vec::new();
+    while let Ok(Some(token)) = tokenizer.next(true) {
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// The tokens before `pos`, leaving out the identifier being typed at `pos` (if there is one)
+pub(crate) fn tokens_before(tokens: &[Token], pos: Position) -> &[Token] {
+    let end = tokens.iter().take_while(|token| token.span.end <= pos).count();
+    match tokens[..end].last() {
+        Some(Token { kind: TokenKind::Identifier(_), span }) if span.end == pos => &tokens[..end - 1],
+        _ => &tokens[..end],
+    }
+}
+
+/// The index of the identifier at `pos`, the cursor can be anywhere in it or right after it
+pub(crate) fn identifier_at(tokens: &[Token], pos: Position) -> Option<usize> {
+    tokens.iter().position(|token| {
+        matches!(token.kind, TokenKind::Identifier(_)) && token.span.start <= pos && pos <= token.span.end
+    })
+}
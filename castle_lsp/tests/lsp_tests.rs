@@ -0,0 +1,134 @@
+use castle_lsp::{
+    is_message, message_completions, message_definition, message_diagnostics, schema_completions,
+    schema_definition, schema_diagnostics, schema_references,
+};
+use castle_schema_parser::parsers::parse_schema::parse_schema;
+use tower_lsp::lsp_types::{CompletionItem, Position, Range};
+
+const SCHEMA: &str = r#"directive @lowercase on FieldDefinition
+
+type Root {
+    me: User
+    users(limit: number): Vec<User>
+}
+
+type User {
+    name: String @lowercase
+    icon: Icon
+}
+
+enum Icon {
+    SVGIcon { url: String }
+    Emoji(String)
+}
+"#;
+
+fn labels(mut items: Vec<CompletionItem>) -> Vec<String> {
+    items.sort_by(|a, b| a.label.cmp(&b.label));
+    items.into_iter().map(|item| item.label).collect()
+}
+
+fn range(start: (u32, u32), end: (u32, u32)) -> Range {
+    Range::new(Position::new(start.0, start.1), Position::new(end.0, end.1))
+}
+
+#[test]
+fn schema_problems_are_diagnosed_where_they_are() {
+    assert!(schema_diagnostics(SCHEMA).is_empty());
+
+    let diagnostics = schema_diagnostics("type Root {\n    me: Usr\n}\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range, range((1, 8), (1, 11)));
+    assert!(diagnostics[0].message.contains("Type Usr not defined"));
+
+    let diagnostics = schema_diagnostics("type Root {\n    me User\n}\n");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start.line, 1);
+}
+
+#[test]
+fn message_problems_are_diagnosed_against_the_schema() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let msg = "message {\n    me {\n        name\n        age\n    }\n}\n";
+    assert!(is_message(msg));
+
+    let diagnostics = message_diagnostics(msg, Some(&schema));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range, range((3, 8), (3, 11)));
+
+    // without a schema only syntax is checked
+    assert!(message_diagnostics(msg, None).is_empty());
+    // the end of the source is where the blocks should have been closed
+    let diagnostics = message_diagnostics("message {\n    me {\n", None);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range, range((2, 0), (2, 0)));
+}
+
+#[test]
+fn schema_completes_kinds_and_directives() {
+    let src = "directive @cached on FieldDefinition\ntype Root {\n    me: U\n    you: Vec<\n    name: String @\n}\ntype User {}\nenum Icon {}\n";
+    let kinds = labels(schema_completions(src, Position::new(2, 9)));
+    assert_eq!(kinds, vec!["Icon", "Option", "Root", "String", "User", "Uuid", "Vec", "bool", "number", "void"]);
+    assert_eq!(labels(schema_completions(src, Position::new(3, 13))), kinds);
+    assert_eq!(labels(schema_completions(src, Position::new(4, 18))), vec!["cached"]);
+    // field names aren't completed
+    assert!(schema_completions(src, Position::new(2, 5)).is_empty());
+}
+
+#[test]
+fn message_completes_fields_and_variants() {
+    let schema = parse_schema(SCHEMA).unwrap();
+    let src = "message {\n    users(limit: 1) [\n        n\n        icon match {\n            SVGIcon {  }\n            \n        }\n    }\n    \n}\n";
+    assert_eq!(labels(message_completions(src, Position::new(2, 9), &schema)), vec!["icon", "name"]);
+    assert_eq!(labels(message_completions(src, Position::new(4, 22), &schema)), vec!["url"]);
+    assert_eq!(labels(message_completions(src, Position::new(5, 12), &schema)), vec!["Emoji", "SVGIcon"]);
+    assert_eq!(labels(message_completions(src, Position::new(8, 4), &schema)), vec!["me", "users"]);
+    // inputs aren't fields
+    assert!(message_completions(src, Position::new(1, 17), &schema).is_empty());
+}
+
+#[test]
+fn goes_to_definitions() {
+    // the kind of `me` goes to `type User`
+    assert_eq!(schema_definition(SCHEMA, Position::new(3, 9)), Some(range((7, 5), (7, 9))));
+    // inside generics
+    assert_eq!(schema_definition(SCHEMA, Position::new(4, 31)), Some(range((7, 5), (7, 9))));
+    // directives go to their definition
+    assert_eq!(schema_definition(SCHEMA, Position::new(8, 20)), Some(range((0, 11), (0, 20))));
+    // built-in kinds aren't defined anywhere
+    assert_eq!(schema_definition(SCHEMA, Position::new(8, 11)), None);
+
+    let schema = parse_schema(SCHEMA).unwrap();
+    let msg = "message {\n    me as user {\n        icon match { Emoji }\n    }\n}\n";
+    assert_eq!(message_definition(msg, Position::new(1, 5), &schema, SCHEMA), Some(range((3, 4), (3, 6))));
+    assert_eq!(message_definition(msg, Position::new(2, 9), &schema, SCHEMA), Some(range((9, 4), (9, 8))));
+    assert_eq!(message_definition(msg, Position::new(2, 23), &schema, SCHEMA), Some(range((14, 4), (14, 9))));
+    // renames aren't defined anywhere
+    assert_eq!(message_definition(msg, Position::new(1, 11), &schema, SCHEMA), None);
+}
+
+#[test]
+fn finds_references() {
+    assert_eq!(
+        schema_references(SCHEMA, Position::new(7, 6), true),
+        vec![range((3, 8), (3, 12)), range((4, 30), (4, 34)), range((7, 5), (7, 9))],
+    );
+    assert_eq!(
+        schema_references(SCHEMA, Position::new(3, 8), false),
+        vec![range((3, 8), (3, 12)), range((4, 30), (4, 34))],
+    );
+    assert_eq!(
+        schema_references(SCHEMA, Position::new(0, 12), true),
+        vec![range((0, 11), (0, 20)), range((8, 18), (8, 27))],
+    );
+}
+
+#[test]
+fn every_syntax_error_is_diagnosed() {
+    let diagnostics = schema_diagnostics("type Root {\n    me User\n    you: User\n    them Vec<User>\n}\n");
+    let lines: Vec<_> = diagnostics.iter().map(|diagnostic| diagnostic.range.start.line).collect();
+    assert_eq!(lines, vec![1, 3]);
+
+    let diagnostics = message_diagnostics("message {\n    me(id: ) { name }\n    you(: 1)\n}\n", None);
+    assert_eq!(diagnostics.len(), 2);
+}
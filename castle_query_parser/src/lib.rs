@@ -6,24 +6,65 @@ pub(crate) mod types;
 use std::collections::HashMap;
 
 use castle_error::CastleError;
-use parsers::parse_projection::{parse_projection};
-use castle_tokenizer::{Tokenizer, Tokenizable, TokenKind, Keyword, Punctuator};
-pub use types::{Field, FieldKind, Projection, Inputs, Input, Message};
+use parsers::{parse_projection::parse_projection, parse_variable_definitions::parse_optional_variable_definitions};
+use castle_tokenizer::{Tokenizer, Tokenizable, TokenKind, Keyword, Punctuator, extensions::ExpectIdentifier};
+pub use types::{Field, FieldKind, Projection, Inputs, Input, Message, VariableDefinition, VariableDefinitions};
+pub use castle_shared_parser::Span;
 
+/// Parses a message, an empty string is parsed as an empty message
+/// ```text
+/// message GetUser($id: Uuid) {
+///     user(id: $id) {
+///         first_name
+///     }
+/// }
+/// ```
 pub fn parse_message(msg: &str) -> Result<Message, CastleError> {
     let bytes = msg.as_bytes();
     let mut tokenizer = Tokenizer::new(bytes);
-    Ok(Message {
-        projection: match tokenizer.next(true)? {
-            Some(token) if let TokenKind::Keyword(Keyword::Message) = token.kind => {
-                parse_projection(&mut tokenizer, Punctuator::OpenBlock, Punctuator::CloseBlock)?
+    parse_message_tokens(&mut tokenizer)
+}
+
+/// Parses as much of the message as possible, returning what could be parsed along with every
+/// error found (in the order they were found).
+///
+/// After an error parsing resynchronizes at the next field (after a `,` or a new line), or
+/// after the block the error was in.
+pub fn parse_message_recovering(msg: &str) -> (Message, Vec<CastleError>) {
+    let bytes = msg.as_bytes();
+    let mut tokenizer = Tokenizer::recovering(bytes);
+    let message = match parse_message_tokens(&mut tokenizer) {
+        Ok(message) => message,
+        Err(e) => {
+            tokenizer.errors.push(e);
+            Message {
+                name: None,
+                variables: HashMap::new(),
+                projection: HashMap::new(),
             }
-            Some(token) => return Err(CastleError::Root(
-                format!("Expected keyword or EOF, got: {:?}", token.kind).into(),
-                token.span
-            )),
-            None => HashMap::new(),
         },
-    })
+    };
+    (message, tokenizer.errors)
 }
 
+fn parse_message_tokens(tokenizer: &mut impl Tokenizable) -> Result<Message, CastleError> {
+    match tokenizer.next(true)? {
+        Some(token) if let TokenKind::Keyword(Keyword::Message) = token.kind => Ok(Message {
+            name: match tokenizer.peek_token_kind(true)? {
+                Some(TokenKind::Identifier(_)) => Some(tokenizer.expect_identifier(true)?),
+                _ => None,
+            },
+            variables: parse_optional_variable_definitions(tokenizer)?,
+            projection: parse_projection(tokenizer, Punctuator::OpenBlock, Punctuator::CloseBlock)?,
+        }),
+        Some(token) => Err(CastleError::Root(
+            format!("Expected keyword or EOF, got: {:?}", token.kind).into(),
+            token.span
+        )),
+        None => Ok(Message {
+            name: None,
+            variables: HashMap::new(),
+            projection: HashMap::new(),
+        }),
+    }
+}
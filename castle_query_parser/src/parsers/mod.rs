@@ -1,2 +1,2 @@
-
-pub(crate) mod parse_projection;
\ No newline at end of file
+pub(crate) mod parse_projection;
+pub(crate) mod parse_variable_definitions;
@@ -1,15 +1,19 @@
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
 use castle_error::CastleError;
 use castle_shared_parser::parse_inputs::{parse_inputs, consume_optional_separator};
 use castle_tokenizer::{
-    extensions::{ExpectIdentifier, ExpectKeyword, ExpectPunctuator, PeekKeyword, IsPunctuator},
+    extensions::{ExpectIdentifier, ExpectKeyword, ExpectPunctuator, PeekKeyword, IsPunctuator, Resynchronize},
     Keyword, Punctuator, TokenKind, Tokenizable,
 };
 
-use crate::{types::Field, FieldKind};
+use crate::{types::Field, FieldKind, Projection};
 
-/// Parses a object projection, except without the {} brackets (so just the fields)
+/// Parses a object projection, except without the {} brackets (so just the fields),
+/// `closing` is the bracket that will close it
+///
+/// Fields are keyed by their response key, which is the rename if there is one,
+/// otherwise the field name. Two fields with the same response key are an error.
 /// ```text
 /// {
 ///     first_name,
@@ -21,15 +25,34 @@ use crate::{types::Field, FieldKind};
 /// ```
 pub fn parse_projection_inner(
     tokenizer: &mut impl Tokenizable,
+    closing: Punctuator,
 ) -> Result<HashMap<Box<str>, Field>, CastleError> {
     let mut projections = HashMap::new();
 
     loop {
         // peek to check if there is an identifier (EOF is allowed since this can be used for top level projections)
         match tokenizer.peek(true)?.map(|t| (&t.kind, &t.span)) {
-            Some((TokenKind::Identifier(_), ..)) => {
-                let field = parse_field(tokenizer)?;
-                projections.insert(field.name.clone(), field);
+            Some((TokenKind::Identifier(_), span)) => {
+                let span = *span;
+                let field = match parse_field(tokenizer) {
+                    Ok(field) => field,
+                    Err(e) => {
+                        tokenizer.recover(e)?;
+                        match tokenizer.skip_past_separator(closing)? {
+                            true => continue,
+                            false => break,
+                        }
+                    },
+                };
+                match projections.entry(field.response_key().into()) {
+                    Entry::Occupied(entry) => tokenizer.recover(CastleError::parse(
+                        format!("Field `{}` is projected more than once, use `as` to rename one of them", entry.key()),
+                        span,
+                    ))?,
+                    Entry::Vacant(entry) => {
+                        entry.insert(field);
+                    },
+                }
                 consume_optional_separator(tokenizer)?;
             },
             _ => break, // EOF or something else
@@ -40,8 +63,9 @@ pub fn parse_projection_inner(
 }
 
 fn parse_field(tokenizer: &mut impl Tokenizable) -> Result<Field, CastleError> {
+    let (name, span) = tokenizer.expect_identifier_with_span(true)?;
     Ok(Field {
-        name: tokenizer.expect_identifier(true)?,
+        name,
         inputs: if tokenizer.peek_is_punctuator(Punctuator::OpenParen, true)? {
             parse_inputs(tokenizer)?
         } else {
@@ -49,6 +73,7 @@ fn parse_field(tokenizer: &mut impl Tokenizable) -> Result<Field, CastleError> {
         },
         rename: parse_rename_optional(tokenizer)?,
         kind: parse_field_kind(tokenizer)?,
+        span,
     })
 }
 
@@ -72,14 +97,15 @@ pub(crate) fn parse_projection(
     closing: Punctuator,
 ) -> Result<HashMap<Box<str>, Field>, CastleError> {
     tokenizer.expect_punctuator(opening, true)?;
-    let projections = parse_projection_inner(tokenizer)?;
-    tokenizer.expect_punctuator(closing, true)?;
+    let projections = parse_projection_inner(tokenizer, closing)?;
+    tokenizer.expect_block_end(closing)?;
     Ok(projections)
 }
 
-/// 3 types of field kinds
+/// 4 types of field kinds
 /// - object (hashmap)
 /// - list (hashmap) - this is like object but it projects the fields of children instead
+/// - match (hashmap of hashmaps) - projects the fields of each enum variant
 /// - scalar/field - no sub projections
 fn parse_field_kind(tokenizer: &mut impl Tokenizable) -> Result<FieldKind, CastleError> {
     match tokenizer.peek_token_kind(false)? {
@@ -89,6 +115,59 @@ fn parse_field_kind(tokenizer: &mut impl Tokenizable) -> Result<FieldKind, Castl
         Some(TokenKind::Punctuator(Punctuator::OpenBracket)) => Ok(FieldKind::List(
             parse_projection(tokenizer, Punctuator::OpenBracket, Punctuator::CloseBracket)?,
         )),
+        Some(TokenKind::Keyword(Keyword::Match)) => Ok(FieldKind::Match(parse_match(tokenizer)?)),
         _ => Ok(FieldKind::Field),
     }
 }
+
+/// Parses the arms of a match projection, each arm is a variant name with an optional
+/// projection of the variant's fields (unit and tuple variants have no projection)
+/// ```text
+/// match {
+///     SVGIcon {
+///         url
+///     }
+///     Emoji { unicode }
+///     NoIcon
+/// }
+/// ```
+fn parse_match(
+    tokenizer: &mut impl Tokenizable,
+) -> Result<HashMap<Box<str>, Projection>, CastleError> {
+    tokenizer.expect_keyword(Keyword::Match, false)?;
+    tokenizer.expect_punctuator(Punctuator::OpenBlock, true)?;
+    let mut arms = HashMap::new();
+    while !tokenizer.at_block_end(Punctuator::CloseBlock)? {
+        let span = tokenizer.peek_expect(true)?.span;
+        match parse_arm(tokenizer) {
+            Ok((variant, _)) if arms.contains_key(&variant) => tokenizer.recover(CastleError::parse(
+                format!("Variant `{}` is matched more than once", variant),
+                span,
+            ))?,
+            Ok((variant, projection)) => {
+                arms.insert(variant, projection);
+            },
+            Err(e) => {
+                tokenizer.recover(e)?;
+                match tokenizer.skip_past_separator(Punctuator::CloseBlock)? {
+                    true => continue,
+                    false => break,
+                }
+            },
+        }
+        consume_optional_separator(tokenizer)?;
+    }
+    tokenizer.expect_block_end(Punctuator::CloseBlock)?;
+    Ok(arms)
+}
+
+/// A variant name with an optional projection of the variant's fields
+fn parse_arm(tokenizer: &mut impl Tokenizable) -> Result<(Box<str>, Projection), CastleError> {
+    let variant = tokenizer.expect_identifier(true)?;
+    let projection = if tokenizer.peek_is_punctuator(Punctuator::OpenBlock, false)? {
+        parse_projection(tokenizer, Punctuator::OpenBlock, Punctuator::CloseBlock)?
+    } else {
+        HashMap::new()
+    };
+    Ok((variant, projection))
+}
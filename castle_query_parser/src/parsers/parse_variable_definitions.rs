@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use castle_error::CastleError;
+use castle_shared_parser::{
+    parse_inputs::{consume_optional_separator, parse_value, parse_variable},
+    parse_kind::parse_kind,
+};
+use castle_tokenizer::{
+    extensions::{ExpectPunctuator, IsPunctuator, Resynchronize},
+    Punctuator, Tokenizable,
+};
+
+use crate::{VariableDefinition, VariableDefinitions};
+
+/// Parses the variables declared by a message, if there are any
+/// ```text
+/// message GetUser($id: Uuid, $size: number = 48) {
+///                ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+/// ```
+pub(crate) fn parse_optional_variable_definitions(
+    tokenizer: &mut impl Tokenizable,
+) -> Result<VariableDefinitions, CastleError> {
+    if !tokenizer.peek_is_punctuator(Punctuator::OpenParen, true)? {
+        return Ok(HashMap::new());
+    }
+    tokenizer.expect_punctuator(Punctuator::OpenParen, true)?;
+    let mut variables = HashMap::new();
+    while !tokenizer.at_block_end(Punctuator::CloseParen)? {
+        let span = tokenizer.peek_expect(true)?.span;
+        match parse_variable_definition(tokenizer) {
+            Ok(variable) if variables.contains_key(&variable.ident) => tokenizer.recover(CastleError::parse(
+                format!("Variable `${}` is declared more than once", variable.ident),
+                span,
+            ))?,
+            Ok(variable) => {
+                variables.insert(variable.ident.clone(), variable);
+            },
+            Err(e) => {
+                tokenizer.recover(e)?;
+                match tokenizer.skip_past_separator(Punctuator::CloseParen)? {
+                    true => continue,
+                    false => break,
+                }
+            },
+        }
+        consume_optional_separator(tokenizer)?;
+    }
+    tokenizer.expect_block_end(Punctuator::CloseParen)?;
+    Ok(variables)
+}
+
+/// `$ident: Kind` with an optional default value `= value`
+fn parse_variable_definition(tokenizer: &mut impl Tokenizable) -> Result<VariableDefinition, CastleError> {
+    let start = tokenizer.peek_expect(true)?.span;
+    let ident = parse_variable(tokenizer)?;
+    tokenizer.expect_punctuator(Punctuator::Colon, true)?;
+    let kind = parse_kind(tokenizer)?;
+    let default = match tokenizer.peek_is_punctuator(Punctuator::Default, true)? {
+        true => {
+            tokenizer.expect_punctuator(Punctuator::Default, true)?;
+            Some(parse_value(tokenizer)?)
+        },
+        false => None,
+    };
+    let span = start.to(kind.span);
+    Ok(VariableDefinition { ident, kind, default, span })
+}
@@ -1,6 +1,35 @@
+use std::collections::HashMap;
+
+use castle_shared_parser::{Input, Kind, Span};
+
 use crate::Projection;
 
+/// A parsed message, eg:
+/// ```text
+/// message GetUser($id: Uuid, $size: number = 48) {
+///     user(id: $id) {
+///         profile_pic(size: $size)
+///     }
+/// }
+/// ```
 #[derive(Debug)]
 pub struct Message {
+    pub name: Option<Box<str>>,
+    pub variables: VariableDefinitions,
     pub projection: Projection
-}
\ No newline at end of file
+}
+
+/// A variable declared by a message, eg: `$size: number = 48`
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
+pub struct VariableDefinition {
+    pub ident: Box<str>,
+    pub kind: Kind,
+    pub default: Option<Input>,
+
+    /// Where the variable is declared in the source, eg: `$size: number`
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
+}
+
+pub type VariableDefinitions = HashMap<Box<str>, VariableDefinition>;
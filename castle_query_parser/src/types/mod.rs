@@ -2,4 +2,4 @@ mod projection;
 mod message;
 
 pub use projection::{Field, FieldKind, Input, Inputs, Projection};
-pub use message::Message;
\ No newline at end of file
+pub use message::{Message, VariableDefinition, VariableDefinitions};
\ No newline at end of file
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
 
+use castle_shared_parser::Span;
 pub use castle_shared_parser::Input;
 
 pub type Inputs = HashMap<Box<str>, Input>;
+/// Projected fields keyed by their response key, see [Field::response_key]
 pub type Projection = HashMap<Box<str>, Field>;
 
 /// The query
@@ -39,7 +41,8 @@ pub type Projection = HashMap<Box<str>, Field>;
 ///     }
 /// }
 /// ```
-#[derive(Debug, PartialEq)]
+#[derive(Debug, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct Field {
     pub name: Box<str>,
     pub inputs: Inputs,
@@ -48,14 +51,39 @@ pub struct Field {
     /// `<original_field> as <renamed_field>`
     pub rename: Option<Box<str>>,
     pub kind: FieldKind,
+
+    /// Where the field's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
 
+impl Field {
+    /// The key this field is returned under, the rename if there is one, otherwise the field name
+    pub fn response_key(&self) -> &str {
+        self.rename.as_deref().unwrap_or(&self.name)
+    }
+}
 
 
 #[derive(Debug, PartialEq)]
 pub enum FieldKind {
     Object(Projection),
     List(Projection),
+
+    /// Projection for each variant of an enum, keyed by variant name
+    ///
+    /// ```text
+    /// icon match {
+    ///     SVGIcon {
+    ///         url
+    ///         size
+    ///     }
+    ///     Emoji {
+    ///         unicode
+    ///     }
+    /// }
+    /// ```
+    Match(HashMap<Box<str>, Projection>),
     Field
 }
 
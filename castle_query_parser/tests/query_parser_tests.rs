@@ -1,8 +1,8 @@
 
 use std::collections::HashMap;
 
-use castle_query_parser::{Field, FieldKind, parse_message, Projection};
-use castle_shared_parser::Input;
+use castle_query_parser::{Field, FieldKind, parse_message, parse_message_recovering, Projection, VariableDefinition, VariableDefinitions};
+use castle_shared_parser::{Input, Kind, Span};
 use castle_tokenizer::Primitive;
 
 #[test]
@@ -25,6 +25,7 @@ fn can_parse_single_field() {
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into_iter().collect::<Root>();
 
@@ -46,18 +47,21 @@ fn can_parse_two_fields_with_empty_args() {
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
         ("last_name".into(), Field {
             name: "last_name".into(),
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
         ("email".into(), Field {
             name: "email".into(),
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into_iter().collect::<Root>();
 
@@ -84,8 +88,10 @@ fn can_parse_object_projection() {
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
             ].into()),
+            span: Span::default(),
         }),
     ].into();
     let actual = &parse_message(query).expect("Failed to parse query").projection;
@@ -112,14 +118,17 @@ fn can_parse_object_projection_with_two_fields() {
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
                 ("last_name".into(), Field {
                     name: "last_name".into(),
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
             ].into_iter().collect::<Root>()),
+            span: Span::default(),
         }),
     ].into_iter().collect::<Root>();
 
@@ -159,14 +168,17 @@ fn query_with_trailing_comma_succeeds() {
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
                 ("last_name".into(), Field {
                     name: "last_name".into(),
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
             ].into()),
+            span: Span::default(),
         }),
     ].into();
 
@@ -194,14 +206,17 @@ fn query_without_trailing_comma_succeeds() {
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
                 ("last_name".into(), Field {
                     name: "last_name".into(),
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
             ].into()),
+            span: Span::default(),
         }),
     ].into();
 
@@ -235,25 +250,29 @@ fn can_parse_object_and_single_field() {
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Object([
-                ("bar".into(), Field {
+                ("sdsd".into(), Field {
                     name: "bar".into(),
                     inputs: HashMap::new(),
                     rename: Some("sdsd".into()),
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
                 ("baz".into(), Field {
                     name: "baz".into(),
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
             ].into()),
+            span: Span::default(),
         }),
         ("xyz".into(), Field {
             name: "xyz".into(),
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -273,6 +292,7 @@ fn can_parse_numeric_argument() {
             inputs: [("size".into(), Input::Primitive(Primitive::Number(48.into())))].into(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -295,6 +315,7 @@ fn can_parse_multiple_numeric_arguments() {
             ].into(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -314,6 +335,7 @@ fn can_parse_string_arguments() {
             inputs: [("size".into(), Input::Primitive(Primitive::String("48".into())))].into(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -336,6 +358,7 @@ fn can_parse_boolean_arguments() {
             ].into(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -366,12 +389,14 @@ fn can_parse_deeply_nested_message() {
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
                 ("last_name".into(), Field {
                     name: "last_name".into(),
                     inputs: HashMap::new(),
                     rename: None,
                     kind: FieldKind::Field,
+                    span: Span::default(),
                 }),
                 ("profile_picture".into(), Field {
                     name: "profile_picture".into(),
@@ -383,10 +408,13 @@ fn can_parse_deeply_nested_message() {
                             inputs: HashMap::new(),
                             rename: None,
                             kind: FieldKind::Field,
+                            span: Span::default(),
                         }),
                     ].into()),
+                    span: Span::default(),
                 }),
             ].into()),
+            span: Span::default(),
         }),
     ].into();
 
@@ -415,6 +443,7 @@ fn can_parse_object_argument() {
             ))].into(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -459,6 +488,7 @@ fn can_parse_array_arguments() {
             ))].into(),
             rename: None,
             kind: FieldKind::Field,
+            span: Span::default(),
         }),
     ].into();
 
@@ -490,6 +520,7 @@ fn can_parse_resolver_with_no_fields () {
             inputs: HashMap::new(),
             rename: None,
             kind: FieldKind::Object(HashMap::new()),
+            span: Span::default(),
         }),
     ].into();
 
@@ -497,6 +528,194 @@ fn can_parse_resolver_with_no_fields () {
     assert_eq!(&expected, actual);
 }
 
+#[test]
+fn aliased_fields_are_keyed_by_alias() {
+    let query = "message {
+        profile_pic(size: 100) as large_pic
+        profile_pic(size: 50) as small_pic
+    }";
+
+    let expected: Root = [
+        ("large_pic".into(), Field {
+            name: "profile_pic".into(),
+            inputs: [("size".into(), Input::Primitive(Primitive::Number(100.into())))].into(),
+            rename: Some("large_pic".into()),
+            kind: FieldKind::Field,
+            span: Span::default(),
+        }),
+        ("small_pic".into(), Field {
+            name: "profile_pic".into(),
+            inputs: [("size".into(), Input::Primitive(Primitive::Number(50.into())))].into(),
+            rename: Some("small_pic".into()),
+            kind: FieldKind::Field,
+            span: Span::default(),
+        }),
+    ].into();
+
+    let actual = &parse_message(query).expect("Expected query to parse").projection;
+    assert_eq!(&expected, actual);
+}
+
+#[test]
+fn duplicate_response_keys_fail() {
+    let query = "message {
+        profile_pic(size: 100)
+        profile_pic(size: 50)
+    }";
+    parse_message(query).unwrap_err();
+
+    let query = "message {
+        first_name
+        last_name as first_name
+    }";
+    parse_message(query).unwrap_err();
+}
+
+#[test]
+fn can_parse_match_projection() {
+    let query = "message {
+        icon match {
+            SVGIcon {
+                url
+                size
+            }
+            Emoji { unicode }
+            NoIcon
+        }
+    }";
+
+    let expected: Root = [
+        ("icon".into(), Field {
+            name: "icon".into(),
+            inputs: HashMap::new(),
+            rename: None,
+            kind: FieldKind::Match([
+                ("SVGIcon".into(), [
+                    ("url".into(), Field {
+                        name: "url".into(),
+                        inputs: HashMap::new(),
+                        rename: None,
+                        kind: FieldKind::Field,
+                        span: Span::default(),
+                    }),
+                    ("size".into(), Field {
+                        name: "size".into(),
+                        inputs: HashMap::new(),
+                        rename: None,
+                        kind: FieldKind::Field,
+                        span: Span::default(),
+                    }),
+                ].into()),
+                ("Emoji".into(), [
+                    ("unicode".into(), Field {
+                        name: "unicode".into(),
+                        inputs: HashMap::new(),
+                        rename: None,
+                        kind: FieldKind::Field,
+                        span: Span::default(),
+                    }),
+                ].into()),
+                ("NoIcon".into(), HashMap::new()),
+            ].into()),
+            span: Span::default(),
+        }),
+    ].into();
+
+    let actual = &parse_message(query).expect("Expected query to parse").projection;
+    assert_eq!(&expected, actual);
+}
+
+#[test]
+fn match_with_duplicate_variant_fails() {
+    let query = "message {
+        icon match {
+            Emoji { unicode }
+            Emoji { unicode }
+        }
+    }";
+
+    parse_message(query).unwrap_err();
+}
+
+#[test]
+fn can_parse_message_with_variables() {
+    let query = "message GetUser($id: Uuid, $size: number = 48) {
+        user(id: $id) {
+            profile_pic(size: $size)
+        }
+    }";
+
+    let expected_variables: VariableDefinitions = [
+        ("id".into(), VariableDefinition {
+            ident: "id".into(),
+            kind: Kind { ident: "Uuid".into(), generics: vec![], span: Span::default() },
+            default: None,
+            span: Span::default(),
+        }),
+        ("size".into(), VariableDefinition {
+            ident: "size".into(),
+            kind: Kind { ident: "number".into(), generics: vec![], span: Span::default() },
+            default: Some(Input::Primitive(Primitive::Number(48.into()))),
+            span: Span::default(),
+        }),
+    ].into();
+
+    let expected_projection: Root = [
+        ("user".into(), Field {
+            name: "user".into(),
+            inputs: [("id".into(), Input::Variable("id".into()))].into(),
+            rename: None,
+            kind: FieldKind::Object([
+                ("profile_pic".into(), Field {
+                    name: "profile_pic".into(),
+                    inputs: [("size".into(), Input::Variable("size".into()))].into(),
+                    rename: None,
+                    kind: FieldKind::Field,
+                    span: Span::default(),
+                }),
+            ].into()),
+            span: Span::default(),
+        }),
+    ].into();
+
+    let message = parse_message(query).expect("Failed to parse query");
+    assert_eq!(message.name.as_deref(), Some("GetUser"));
+    assert_eq!(message.variables, expected_variables);
+    assert_eq!(message.projection, expected_projection);
+}
+
+#[test]
+fn variable_declared_more_than_once_fails() {
+    let query = "message GetUser($id: Uuid, $id: String) {
+        user(id: $id) { first_name }
+    }";
+
+    parse_message(query).unwrap_err();
+}
+
+#[test]
+fn can_parse_none_and_null_inputs() {
+    let query = "message {
+        avatar(size: None, shape: null)
+    }";
+
+    let expected: Root = [
+        ("avatar".into(), Field {
+            name: "avatar".into(),
+            inputs: [
+                ("size".into(), Input::None),
+                ("shape".into(), Input::None),
+            ].into(),
+            rename: None,
+            kind: FieldKind::Field,
+            span: Span::default(),
+        }),
+    ].into();
+
+    let actual = parse_message(query).expect("Failed to parse query").projection;
+    assert_eq!(actual, expected);
+}
+
 // #[test]
 // fn can_parse_object_projection_with_match() {
 //     let query = "
@@ -644,3 +863,53 @@ fn can_parse_resolver_with_no_fields () {
 //     let actual = &parse_message(query).unwrap();
 //     assert_eq!(&expected, actual.wants);
 // }
+
+#[test]
+fn recovering_parser_carries_on_after_errors() {
+    let msg = "
+message GetUser($id: Uuid, $size: = 48) {
+    user(id: $id) {
+        name
+        avatar(size: ) as small
+        name
+        icon match {
+            5
+            Emoji
+        }
+        email
+    }
+    posts [
+        title
+";
+    let (parsed, errors) = parse_message_recovering(msg);
+    assert!(parse_message(msg).is_err());
+
+    assert!(parsed.variables.contains_key("id"));
+    assert!(!parsed.variables.contains_key("size"));
+    let user = match &parsed.projection["user"].kind {
+        FieldKind::Object(projection) => projection,
+        kind => panic!("expected an object projection, got {:?}", kind),
+    };
+    let mut fields: Vec<_> = user.keys().map(|key| &**key).collect();
+    fields.sort();
+    assert_eq!(fields, vec!["email", "icon", "name"]);
+    match &user["icon"].kind {
+        FieldKind::Match(arms) => assert!(arms.contains_key("Emoji")),
+        kind => panic!("expected a match projection, got {:?}", kind),
+    }
+    match &parsed.projection["posts"].kind {
+        FieldKind::List(projection) => assert!(projection.contains_key("title")),
+        kind => panic!("expected a list projection, got {:?}", kind),
+    }
+
+    // the bad variable, the bad input, the repeated field, the bad arm and the two unclosed blocks
+    assert_eq!(errors.len(), 6, "{:#?}", errors);
+}
+
+#[test]
+fn recovering_parser_returns_no_errors_for_valid_messages() {
+    let msg = "message { user(id: 1) { name } }";
+    let (parsed, errors) = parse_message_recovering(msg);
+    assert!(errors.is_empty());
+    assert_eq!(parsed.projection, parse_message(msg).unwrap().projection);
+}
@@ -1,3 +1,4 @@
 
 pub mod types;
-pub mod parsers;
\ No newline at end of file
+pub mod parsers;
+pub mod printer;
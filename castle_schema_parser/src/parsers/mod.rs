@@ -3,5 +3,4 @@ pub mod parse_enum_definition;
 pub mod parse_type_definition;
 pub mod parse_directive_definition;
 pub mod parse_directives;
-pub mod parse_kind;
 pub mod parse_input_type_definition;
\ No newline at end of file
@@ -7,12 +7,16 @@ use crate::types::{DirectiveDefinition, DirectiveLocation};
 
 use super::parse_input_type_definition::parse_optional_input_definitions;
 
-pub fn parse_directive_definition(tokenizer: &mut impl Tokenizable) -> Result<DirectiveDefinition, CastleError> {
+pub fn parse_directive_definition(tokenizer: &mut impl Tokenizable, docs: Option<Box<str>>) -> Result<DirectiveDefinition, CastleError> {
+    let at_span = tokenizer.peek_expect(true)?.span;
     tokenizer.expect_punctuator(Punctuator::At, true)?;
+    let (ident, ident_span) = tokenizer.expect_identifier_with_span(false)?;
     Ok(DirectiveDefinition{
-        ident: tokenizer.expect_identifier(false)?,
+        ident,
         input_definitions: parse_optional_input_definitions(tokenizer, Punctuator::OpenParen, Punctuator::CloseParen)?,
         locations: parse_directive_locations(tokenizer)?,
+        docs,
+        span: at_span.to(ident_span),
     })
 }
 
@@ -20,7 +24,7 @@ pub fn parse_directive_locations(tokenizer: &mut impl Tokenizable) -> Result<Has
     let mut directive_locations = HashSet::new();
     tokenizer.expect_identifier(true)?; // consume on
     loop {
-        let err_location = tokenizer.peek(true)?.unwrap().span;
+        let err_location = tokenizer.peek_expect(true)?.span;
         let token = tokenizer.expect_identifier(true)?;
         match &token[..] {
             "FieldDefinition" => directive_locations.insert(DirectiveLocation::FieldDefinition),
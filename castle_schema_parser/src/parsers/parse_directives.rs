@@ -29,9 +29,12 @@ pub fn parse_directives(tokenizer: &mut impl Tokenizable) -> Result<Vec<AppliedD
 }
 
 pub fn expect_directive(tokenizer: &mut impl Tokenizable) -> Result<AppliedDirective, CastleError> {
+    let at_span = tokenizer.peek_expect(true)?.span;
     tokenizer.expect_punctuator(Punctuator::At, true)?;
+    let (ident, ident_span) = tokenizer.expect_identifier_with_span(false)?;
     Ok(AppliedDirective {
-        ident: tokenizer.expect_identifier(false)?,
+        ident,
         inputs: parse_optional_inputs(tokenizer)?,
+        span: at_span.to(ident_span),
     })
 }
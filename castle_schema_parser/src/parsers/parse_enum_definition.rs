@@ -1,24 +1,28 @@
 use std::collections::HashMap;
 
 use castle_error::CastleError;
-use castle_shared_parser::parse_inputs::consume_optional_separator;
+use castle_shared_parser::{parse_inputs::consume_optional_separator, parse_kind::parse_kind};
 use castle_tokenizer::{
-    extensions::{ExpectIdentifier, ExpectPunctuator, IsPunctuator},
+    extensions::{ExpectIdentifier, ExpectPunctuator, IsPunctuator, Resynchronize},
     Punctuator, TokenKind, Tokenizable,
 };
 
 use crate::types::{AppliedDirective, EnumDefinition, Kind, VariantDefinition, VariantKindDefinition};
 
-use super::{parse_directives::parse_directives, parse_kind::parse_kind};
+use super::parse_directives::parse_directives;
 
 pub(crate) fn parse_enum_definition(
     tokenizer: &mut impl Tokenizable,
     directives: Vec<AppliedDirective>,
+    docs: Option<Box<str>>,
 ) -> Result<EnumDefinition, CastleError> {
+    let (ident, span) = tokenizer.expect_identifier_with_span(true)?;
     Ok(EnumDefinition {
-        ident: tokenizer.expect_identifier(true)?,
+        ident,
         variants: parse_enum_variants(tokenizer)?,
         directives,
+        docs,
+        span,
     })
 }
 
@@ -27,25 +31,34 @@ fn parse_enum_variants(
 ) -> Result<HashMap<Box<str>, VariantDefinition>, CastleError> {
     let mut map = HashMap::new();
     tokenizer.expect_punctuator(Punctuator::OpenBlock, true)?;
-    loop {
-        if tokenizer.peek_is_punctuator(Punctuator::CloseBlock, true)? {
-            break;
+    while !tokenizer.at_block_end(Punctuator::CloseBlock)? {
+        match parse_variant_definition(tokenizer) {
+            Ok(variant_def) => {
+                map.insert(variant_def.ident.clone(), variant_def);
+                consume_optional_separator(tokenizer)?;
+            },
+            Err(e) => {
+                tokenizer.recover(e)?;
+                if !tokenizer.skip_past_separator(Punctuator::CloseBlock)? {
+                    break;
+                }
+            },
         }
-        let variant_def = parse_variant_definition(tokenizer)?;
-        map.insert(variant_def.ident.clone(), variant_def);
-        consume_optional_separator(tokenizer)?;
     }
-    tokenizer.expect_punctuator(Punctuator::CloseBlock, true)?;
+    tokenizer.expect_block_end(Punctuator::CloseBlock)?;
     Ok(map)
 }
 
 fn parse_variant_definition(
     tokenizer: &mut impl Tokenizable,
 ) -> Result<VariantDefinition, CastleError> {
+    let (ident, span) = tokenizer.expect_identifier_with_span(true)?;
     Ok(VariantDefinition {
-        ident: tokenizer.expect_identifier(true)?,
+        ident,
         kind: parse_variant_kind_definition(tokenizer)?,
         directives: parse_directives(tokenizer)?,
+        docs: tokenizer.take_docs(span.start),
+        span,
     })
 }
 
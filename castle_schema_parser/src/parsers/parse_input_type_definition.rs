@@ -1,22 +1,28 @@
 use std::collections::HashMap;
 
 use castle_error::CastleError;
-use castle_tokenizer::{Tokenizable, Punctuator, extensions::{IsPunctuator, ExpectPunctuator, ExpectIdentifier}};
+use castle_tokenizer::{Tokenizable, Punctuator, extensions::{IsPunctuator, ExpectPunctuator, ExpectIdentifier, Resynchronize}};
 
 use crate::types::{InputTypeDefinition, AppliedDirective, InputDefinition};
 
-use super::{parse_kind::parse_kind, parse_directives::parse_directives};
+use castle_shared_parser::{parse_inputs::{consume_optional_separator, parse_value}, parse_kind::parse_kind, Input};
+
+use super::parse_directives::parse_directives;
 
 
 
 pub(crate) fn parse_input_type_definition(
     tokenizer: &mut impl Tokenizable,
     directives: Vec<AppliedDirective>,
+    docs: Option<Box<str>>,
 ) -> Result<InputTypeDefinition, CastleError> {
+    let (ident, span) = tokenizer.expect_identifier_with_span(true)?;
     Ok(InputTypeDefinition {
-        ident: tokenizer.expect_identifier(true)?,
+        ident,
         input_definitions: parse_input_definitions(tokenizer, Punctuator::OpenBlock, Punctuator::CloseBlock)?,
         directives,
+        docs,
+        span,
     })
 }
 
@@ -27,24 +33,47 @@ pub fn parse_input_definitions(
 ) -> Result<HashMap<Box<str>, InputDefinition>, CastleError> {
     let mut inputs = HashMap::new();
     tokenizer.expect_punctuator(opening, true)?;
-    loop {
-        if tokenizer.peek_is_punctuator(closing, true)? {
-            break;
+    while !tokenizer.at_block_end(closing)? {
+        match parse_input_definition(tokenizer) {
+            Ok(input_definition) => {
+                inputs.insert(input_definition.ident.clone(), input_definition);
+                consume_optional_separator(tokenizer)?;
+            },
+            Err(e) => {
+                tokenizer.recover(e)?;
+                if !tokenizer.skip_past_separator(closing)? {
+                    break;
+                }
+            },
         }
-        let name = tokenizer.expect_identifier(true)?;
-        tokenizer.expect_punctuator(Punctuator::Colon, true)?;
-        let input_value = InputDefinition {
-            ident: name.clone(),
-            input_kind: parse_kind(tokenizer)?,
-            default: None,
-            directives: parse_directives(tokenizer)?,
-        };
-        inputs.insert(name, input_value);
     }
-    tokenizer.expect_punctuator(closing, true)?;
+    tokenizer.expect_block_end(closing)?;
     Ok(inputs)
 }
 
+/// `ident: Kind` with an optional default value and directives
+fn parse_input_definition(tokenizer: &mut impl Tokenizable) -> Result<InputDefinition, CastleError> {
+    let (ident, span) = tokenizer.expect_identifier_with_span(true)?;
+    tokenizer.expect_punctuator(Punctuator::Colon, true)?;
+    Ok(InputDefinition {
+        ident,
+        input_kind: parse_kind(tokenizer)?,
+        default: parse_optional_default(tokenizer)?,
+        directives: parse_directives(tokenizer)?,
+        docs: tokenizer.take_docs(span.start),
+        span,
+    })
+}
+
+/// Parses the default value of an input definition if there is one, eg: `= 48`
+fn parse_optional_default(tokenizer: &mut impl Tokenizable) -> Result<Option<Input>, CastleError> {
+    if !tokenizer.peek_is_punctuator(Punctuator::Default, true)? {
+        return Ok(None);
+    }
+    tokenizer.expect_punctuator(Punctuator::Default, true)?;
+    Ok(Some(parse_value(tokenizer)?))
+}
+
 pub(crate) fn parse_optional_input_definitions(
     tokenizer: &mut impl Tokenizable,
     opening: Punctuator,
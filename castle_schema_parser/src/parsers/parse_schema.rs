@@ -1,5 +1,5 @@
 use castle_error::CastleError;
-use castle_tokenizer::{Keyword, TokenKind, Tokenizable, Tokenizer};
+use castle_tokenizer::{extensions::Resynchronize, Keyword, TokenKind, Tokenizable, Tokenizer};
 
 use crate::types::SchemaDefinition;
 
@@ -13,48 +13,89 @@ pub fn parse_schema(schema: &str) -> Result<SchemaDefinition, CastleError> {
     let bytes = schema.as_bytes();
     let mut tokenizer = Tokenizer::new(bytes);
     let mut schema_definition = SchemaDefinition::new();
-    loop {
-        // directive implementations for types and enums come before the type.
-        let directives = parse_directives(&mut tokenizer)?;
-
-        let token = if let Some(token) = tokenizer.next(true)? {
-            token
-        } else {
-            // we're done
-            if directives.len() != 0 {
-                Err(CastleError::Validation(
-                    "Cannot have directives at the end of the schema".into(),
-                ))?
-            }
-            return Ok(schema_definition);
-        };
+    parse_definitions(&mut tokenizer, &mut schema_definition)?;
+    Ok(schema_definition)
+}
 
-        match token.kind {
-            TokenKind::Keyword(Keyword::Type) => {
-                let type_ = parse_type_definition(&mut tokenizer, directives)?;
-                schema_definition.types.insert(type_.ident.clone(), type_);
-            }
-            TokenKind::Keyword(Keyword::Enum) => {
-                let enum_ = parse_enum_definition(&mut tokenizer, directives)?;
-                schema_definition.enums.insert(enum_.ident.clone(), enum_);
-            }
-            TokenKind::Keyword(Keyword::Directive) => {
-                if directives.len() != 0 {
-                    Err(CastleError::Other(
-                        "Directive definitions cannot have directives.".into(),
-                    ))?
-                }
-                let directive_definition = parse_directive_definition(&mut tokenizer)?;
-                schema_definition.directives.insert(directive_definition.ident.clone(), directive_definition);
-            },
-            TokenKind::Keyword(Keyword::Input) => {
-                let input_type_definition = parse_input_type_definition(&mut tokenizer, directives)?;
-                schema_definition.input_types.insert(input_type_definition.ident.clone(), input_type_definition);
+/// Parses as much of the schema as possible, returning what could be parsed along with every
+/// error found (in the order they were found).
+///
+/// After an error parsing resynchronizes at the next field, variant or input (after a `,` or a
+/// new line), or at the next definition (`type`, `enum`, `input` or `directive`).
+pub fn parse_schema_recovering(schema: &str) -> (SchemaDefinition, Vec<CastleError>) {
+    let bytes = schema.as_bytes();
+    let mut tokenizer = Tokenizer::recovering(bytes);
+    let mut schema_definition = SchemaDefinition::new();
+    if let Err(e) = parse_definitions(&mut tokenizer, &mut schema_definition) {
+        tokenizer.errors.push(e);
+    }
+    (schema_definition, tokenizer.errors)
+}
+
+fn parse_definitions(tokenizer: &mut impl Tokenizable, schema_definition: &mut SchemaDefinition) -> Result<(), CastleError> {
+    loop {
+        match parse_definition(tokenizer, schema_definition) {
+            Ok(true) => {},
+            Ok(false) => return Ok(()),
+            Err(e) => {
+                tokenizer.recover(e)?;
+                tokenizer.skip_to_definition()?;
             },
-            _ => Err(CastleError::Schema(
-                format!("Expected item, found: {:?}", token.kind).into(),
-                token.span,
+        }
+    }
+}
+
+/// Parses the next definition into the schema, returns false at the end of the schema
+fn parse_definition(tokenizer: &mut impl Tokenizable, schema_definition: &mut SchemaDefinition) -> Result<bool, CastleError> {
+    // doc comments come before the directives of the item
+    let docs = match tokenizer.peek(true)? {
+        Some(token) => {
+            let start = token.span.start;
+            tokenizer.take_docs(start)
+        },
+        None => None,
+    };
+    // directive implementations for types and enums come before the type.
+    let directives = parse_directives(tokenizer)?;
+
+    let token = if let Some(token) = tokenizer.next(true)? {
+        token
+    } else {
+        // we're done
+        if !directives.is_empty() {
+            Err(CastleError::Validation(
+                "Cannot have directives at the end of the schema".into(),
             ))?
         }
+        return Ok(false);
+    };
+
+    match token.kind {
+        TokenKind::Keyword(Keyword::Type) => {
+            let type_ = parse_type_definition(tokenizer, directives, docs)?;
+            schema_definition.types.insert(type_.ident.clone(), type_);
+        }
+        TokenKind::Keyword(Keyword::Enum) => {
+            let enum_ = parse_enum_definition(tokenizer, directives, docs)?;
+            schema_definition.enums.insert(enum_.ident.clone(), enum_);
+        }
+        TokenKind::Keyword(Keyword::Directive) => {
+            if !directives.is_empty() {
+                Err(CastleError::Other(
+                    "Directive definitions cannot have directives.".into(),
+                ))?
+            }
+            let directive_definition = parse_directive_definition(tokenizer, docs)?;
+            schema_definition.directives.insert(directive_definition.ident.clone(), directive_definition);
+        },
+        TokenKind::Keyword(Keyword::Input) => {
+            let input_type_definition = parse_input_type_definition(tokenizer, directives, docs)?;
+            schema_definition.input_types.insert(input_type_definition.ident.clone(), input_type_definition);
+        },
+        _ => Err(CastleError::Schema(
+            format!("Expected item, found: {:?}", token.kind).into(),
+            token.span,
+        ))?
     }
+    Ok(true)
 }
@@ -1,27 +1,31 @@
 use std::collections::HashMap;
 
 use castle_error::CastleError;
-use castle_shared_parser::parse_inputs::{consume_optional_separator};
+use castle_shared_parser::{parse_inputs::consume_optional_separator, parse_kind::parse_kind};
 use castle_tokenizer::{
-    extensions::{ExpectIdentifier, ExpectPunctuator, IsPunctuator},
+    extensions::{ExpectIdentifier, ExpectPunctuator, Resynchronize},
     Punctuator, Tokenizable,
 };
 
-use crate::types::{AppliedDirective, FieldDefinition, TypeDefinition};
+use crate::types::{AppliedDirective, FieldDefinition, Span, TypeDefinition};
 
 use super::{
     parse_directives::parse_directives,
-    parse_input_type_definition::parse_optional_input_definitions, parse_kind::parse_kind,
+    parse_input_type_definition::parse_optional_input_definitions,
 };
 
 pub(crate) fn parse_type_definition(
     tokenizer: &mut impl Tokenizable,
     directives: Vec<AppliedDirective>,
+    docs: Option<Box<str>>,
 ) -> Result<TypeDefinition, CastleError> {
+    let (ident, span) = tokenizer.expect_identifier_with_span(true)?;
     Ok(TypeDefinition {
-        ident: tokenizer.expect_identifier(true)?,
+        ident,
         fields: parse_fields(tokenizer)?,
         directives,
+        docs,
+        span,
     })
 }
 
@@ -30,21 +34,31 @@ fn parse_fields(
 ) -> Result<HashMap<Box<str>, FieldDefinition>, CastleError> {
     let mut fields = HashMap::new();
     tokenizer.expect_punctuator(Punctuator::OpenBlock, true)?;
-    loop {
-        if tokenizer.peek_is_punctuator(Punctuator::CloseBlock, true)? {
-            break;
+    while !tokenizer.at_block_end(Punctuator::CloseBlock)? {
+        let field = tokenizer
+            .expect_identifier_with_span(true)
+            .and_then(|(name, span)| parse_field_definition(tokenizer, name, span));
+        match field {
+            Ok(field) => {
+                fields.insert(field.ident.clone(), field);
+                consume_optional_separator(tokenizer)?;
+            },
+            Err(e) => {
+                tokenizer.recover(e)?;
+                if !tokenizer.skip_past_separator(Punctuator::CloseBlock)? {
+                    break;
+                }
+            },
         }
-        let name = tokenizer.expect_identifier(true)?;
-        fields.insert(name.clone(), parse_field_definition(tokenizer, name)?);
-        consume_optional_separator(tokenizer)?;
     }
-    tokenizer.expect_punctuator(Punctuator::CloseBlock, true)?;
+    tokenizer.expect_block_end(Punctuator::CloseBlock)?;
     return Ok(fields);
 }
 
 fn parse_field_definition(
     tokenizer: &mut impl Tokenizable,
     ident: Box<str>,
+    span: Span,
 ) -> Result<FieldDefinition, CastleError> {
     Ok(FieldDefinition {
         ident,
@@ -58,5 +72,7 @@ fn parse_field_definition(
             parse_kind(tokenizer)?
         },
         directives: parse_directives(tokenizer)?,
+        docs: tokenizer.take_docs(span.start),
+        span,
     })
 }
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use castle_shared_parser::print::{print_input, print_sorted_inputs};
+
+use crate::types::{
+    AppliedDirective, DirectiveDefinition, EnumDefinition, InputDefinition, InputDefinitions, InputTypeDefinition,
+    SchemaDefinition, TypeDefinition, VariantDefinition, VariantKindDefinition,
+};
+
+const INDENT: &str = "    ";
+
+/// Prints a schema as source text that [parse_schema](crate::parsers::parse_schema::parse_schema) parses
+/// back into the same [SchemaDefinition].
+///
+/// The output is canonical, the same schema is always printed the same way:
+/// - directive definitions come first, then types (`Root` first), enums and input types
+/// - items, fields, variants and inputs are sorted by name
+/// - each field, variant and input type field is on its own line, indented by 4 spaces
+/// - field inputs, directive inputs and values are separated by `, `, unless an input has
+///   docs, then each input is on its own line
+/// - docs are printed as `##` comments on the lines before what they document
+pub fn print_schema(schema: &SchemaDefinition) -> String {
+    let mut items = Vec::new();
+    items.extend(sorted(&schema.directives).into_iter().map(print_directive_definition));
+    let mut types = sorted(&schema.types);
+    types.sort_by_key(|type_def| &*type_def.ident != "Root");
+    items.extend(types.into_iter().map(print_type_definition));
+    items.extend(sorted(&schema.enums).into_iter().map(print_enum_definition));
+    items.extend(sorted(&schema.input_types).into_iter().map(print_input_type_definition));
+
+    let mut printed = items.join("\n\n");
+    if !printed.is_empty() {
+        printed.push('\n');
+    }
+    printed
+}
+
+pub fn print_directive_definition(directive: &DirectiveDefinition) -> String {
+    let mut locations: Vec<_> = directive.locations.iter().map(ToString::to_string).collect();
+    locations.sort();
+    format!(
+        "{}directive @{}{} on {}",
+        print_docs(&directive.docs),
+        directive.ident,
+        print_input_definitions(&directive.input_definitions),
+        locations.join(" | "),
+    )
+}
+
+pub fn print_type_definition(type_def: &TypeDefinition) -> String {
+    let fields = sorted(&type_def.fields).into_iter().map(|field| format!(
+        "{}{}{}: {}{}",
+        print_docs(&field.docs),
+        field.ident,
+        print_input_definitions(&field.input_definitions),
+        field.return_kind,
+        print_applied_directives(&field.directives),
+    ));
+    format!(
+        "{}{}type {} {}",
+        print_docs(&type_def.docs),
+        print_item_directives(&type_def.directives),
+        type_def.ident,
+        print_block(fields),
+    )
+}
+
+pub fn print_enum_definition(enum_def: &EnumDefinition) -> String {
+    let variants = sorted(&enum_def.variants).into_iter().map(print_variant_definition);
+    format!(
+        "{}{}enum {} {}",
+        print_docs(&enum_def.docs),
+        print_item_directives(&enum_def.directives),
+        enum_def.ident,
+        print_block(variants),
+    )
+}
+
+pub fn print_input_type_definition(input_type: &InputTypeDefinition) -> String {
+    let inputs = sorted(&input_type.input_definitions).into_iter().map(print_input_definition);
+    format!(
+        "{}{}input {} {}",
+        print_docs(&input_type.docs),
+        print_item_directives(&input_type.directives),
+        input_type.ident,
+        print_block(inputs),
+    )
+}
+
+fn print_variant_definition(variant: &VariantDefinition) -> String {
+    let kind = match &variant.kind {
+        VariantKindDefinition::Unit => String::new(),
+        VariantKindDefinition::Tuple(kinds) => format!("({})", kinds.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")),
+        VariantKindDefinition::Map(map) => {
+            let mut fields: Vec<_> = map.iter().collect();
+            fields.sort_by_key(|(ident, _)| *ident);
+            format!(" {}", print_block(fields.into_iter().map(|(ident, kind)| format!("{}: {}", ident, kind))))
+        },
+    };
+    format!("{}{}{}{}", print_docs(&variant.docs), variant.ident, kind, print_applied_directives(&variant.directives))
+}
+
+/// Prints the inputs of a field or directive definition, eg: `(size: number = 48, crop: Crop)`
+///
+/// Docs go on their own line, so inputs with docs are printed on their own lines
+fn print_input_definitions(inputs: &InputDefinitions) -> String {
+    if inputs.is_empty() {
+        return String::new()
+    }
+    let inputs = sorted(inputs);
+    match inputs.iter().any(|input| input.docs.is_some()) {
+        true => {
+            let lines: String = inputs
+                .into_iter()
+                .flat_map(|input| print_input_definition(input).lines().map(|line| format!("{}{}\n", INDENT, line)).collect::<Vec<_>>())
+                .collect();
+            format!("(\n{})", lines)
+        },
+        false => format!("({})", inputs.into_iter().map(print_input_definition).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+fn print_input_definition(input: &InputDefinition) -> String {
+    format!(
+        "{}{}: {}{}{}",
+        print_docs(&input.docs),
+        input.ident,
+        input.input_kind,
+        input.default.as_ref().map(|default| format!(" = {}", print_input(default))).unwrap_or_default(),
+        print_applied_directives(&input.directives),
+    )
+}
+
+/// Prints docs as `##` comments, each followed by a new line
+fn print_docs(docs: &Option<Box<str>>) -> String {
+    docs.iter()
+        .flat_map(|docs| docs.split('\n'))
+        .map(|line| match line.is_empty() {
+            true => "##\n".to_string(),
+            false => format!("## {}\n", line),
+        })
+        .collect()
+}
+
+/// Directives applied to a type, enum or input type go on the line before it
+fn print_item_directives(directives: &[AppliedDirective]) -> String {
+    directives.iter().map(|directive| print_applied_directive(directive) + "\n").collect()
+}
+
+/// Directives applied to a field, variant or input go after it, each preceded by a space
+fn print_applied_directives(directives: &[AppliedDirective]) -> String {
+    directives.iter().map(|directive| format!(" {}", print_applied_directive(directive))).collect()
+}
+
+pub fn print_applied_directive(directive: &AppliedDirective) -> String {
+    match directive.inputs.is_empty() {
+        true => format!("@{}", directive.ident),
+        false => format!("@{}({})", directive.ident, print_sorted_inputs(&directive.inputs)),
+    }
+}
+
+/// Prints each line indented inside `{ }`, nested blocks are indented further
+fn print_block(lines: impl Iterator<Item = String>) -> String {
+    let lines: Vec<_> = lines
+        .map(|line| line.lines().map(|line| format!("{}{}\n", INDENT, line)).collect::<String>())
+        .collect();
+    match lines.is_empty() {
+        true => "{}".into(),
+        false => format!("{{\n{}}}", lines.concat()),
+    }
+}
+
+fn sorted<T>(map: &HashMap<Box<str>, T>) -> Vec<&T> {
+    let mut items: Vec<_> = map.iter().collect();
+    items.sort_by_key(|(ident, _)| *ident);
+    items.into_iter().map(|(_, item)| item).collect()
+}
@@ -1,7 +1,7 @@
 
 use std::{collections::{HashMap, HashSet}, fmt::Display};
 
-use castle_shared_parser::Input;
+use castle_shared_parser::{Input, Span};
 
 use super::InputDefinition;
 
@@ -22,11 +22,18 @@ use super::InputDefinition;
 ///     reason: String = "No longer supported"
 /// )
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct DirectiveDefinition {
     pub ident: Box<str>,
     pub input_definitions: HashMap<Box<str>, InputDefinition>,
     pub locations: HashSet<DirectiveLocation>,
+    /// The doc comments (`##` or `///`) before the directive definition
+    pub docs: Option<Box<str>>,
+
+    /// Where the directive's name is in the source, eg: `@lowercase`
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
@@ -73,8 +80,13 @@ impl Display for DirectiveLocation {
 ///     this_is_a_field(arg: String): String @FieldDefinition(an_arg: 123)
 /// }
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct AppliedDirective {
     pub ident: Box<str>,
     pub inputs: HashMap<Box<str>, Input>,
+
+    /// Where the directive is applied in the source, eg: `@lowercase`
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
\ No newline at end of file
@@ -1,21 +1,37 @@
 use std::collections::HashMap;
 
-use super::{AppliedDirective, Kind};
+use castle_shared_parser::Span;
 
+use super::{AppliedDirective, FieldDefinition, Kind, TypeDefinition};
 
 
-#[derive(Debug, PartialEq, Clone)]
+
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct EnumDefinition {
     pub ident: Box<str>,
     pub variants: HashMap<Box<str>, VariantDefinition>,
     pub directives: Vec<AppliedDirective>,
+    /// The doc comments (`##` or `///`) before the enum
+    pub docs: Option<Box<str>>,
+
+    /// Where the enum's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct VariantDefinition {
     pub ident: Box<str>,
     pub kind: VariantKindDefinition,
     pub directives: Vec<AppliedDirective>,
+    /// The doc comments (`##` or `///`) before the variant
+    pub docs: Option<Box<str>>,
+
+    /// Where the variant's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -23,4 +39,30 @@ pub enum VariantKindDefinition {
     Unit,
     Tuple(Vec<Kind>),
     Map(HashMap<Box<str>, Kind>),
-}
\ No newline at end of file
+}
+
+impl VariantDefinition {
+    /// The fields of a map variant as a [TypeDefinition] named `Enum.Variant`,
+    /// so they can be projected the same way as the fields of a type.
+    ///
+    /// Returns `None` for unit and tuple variants.
+    pub fn map_type_definition(&self, enum_ident: &str) -> Option<TypeDefinition> {
+        match &self.kind {
+            VariantKindDefinition::Map(map) => Some(TypeDefinition {
+                ident: format!("{}.{}", enum_ident, self.ident).into(),
+                fields: map.iter().map(|(ident, kind)| (ident.clone(), FieldDefinition {
+                    ident: ident.clone(),
+                    input_definitions: HashMap::new(),
+                    return_kind: kind.clone(),
+                    directives: vec![],
+                    docs: None,
+                    span: kind.span,
+                })).collect(),
+                directives: vec![],
+                docs: self.docs.clone(),
+                span: self.span,
+            }),
+            _ => None,
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use castle_shared_parser::Span;
 use super::{Kind, AppliedDirective, InputDefinitions};
 
 /// Definition of a field resolver, used in field types
@@ -7,10 +8,17 @@ use super::{Kind, AppliedDirective, InputDefinitions};
 ///     also_a_field: String @lowercase
 /// }
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct FieldDefinition {
     pub ident: Box<str>,
     pub input_definitions: InputDefinitions,
     pub return_kind: Kind,
     pub directives: Vec<AppliedDirective>,
+    /// The doc comments (`##` or `///`) before the field
+    pub docs: Option<Box<str>>,
+
+    /// Where the field's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
\ No newline at end of file
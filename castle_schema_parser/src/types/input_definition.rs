@@ -3,7 +3,7 @@
 
 use std::collections::HashMap;
 
-use castle_shared_parser::Input;
+use castle_shared_parser::{Input, Span};
 
 use super::{Kind, AppliedDirective};
 /// Argument Definition
@@ -23,20 +23,34 @@ use super::{Kind, AppliedDirective};
 ///     height(unit: Unit = METER): Float
 /// }
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct InputDefinition {
     pub ident: Box<str>,
     pub input_kind: Kind,
     pub default: Option<Input>,
     pub directives: Vec<AppliedDirective>,
+    /// The doc comments (`##` or `///`) before the input
+    pub docs: Option<Box<str>>,
+
+    /// Where the input's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
 
 pub type InputDefinitions = HashMap<Box<str>, InputDefinition>;
 
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct InputTypeDefinition {
     pub ident: Box<str>,
     pub input_definitions: InputDefinitions,
     pub directives: Vec<AppliedDirective>,
+    /// The doc comments (`##` or `///`) before the input type
+    pub docs: Option<Box<str>>,
+
+    /// Where the input type's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
\ No newline at end of file
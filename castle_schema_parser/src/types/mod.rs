@@ -2,7 +2,6 @@ mod directive_definitions;
 mod enum_definition;
 mod field_definition;
 mod input_definition;
-mod kind;
 mod schema_definition;
 mod type_definition;
 
@@ -12,6 +11,6 @@ pub use enum_definition::VariantDefinition;
 pub use enum_definition::VariantKindDefinition;
 pub use field_definition::FieldDefinition;
 pub use input_definition::{InputDefinition, InputTypeDefinition, InputDefinitions};
-pub use kind::Kind;
+pub use castle_shared_parser::{Kind, Span};
 pub use schema_definition::SchemaDefinition;
 pub use type_definition::TypeDefinition;
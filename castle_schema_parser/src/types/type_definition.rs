@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use castle_shared_parser::Span;
+
 use super::{field_definition::FieldDefinition, directive_definitions::AppliedDirective};
 
 /// Definition of a type in the schema.
@@ -11,9 +13,16 @@ use super::{field_definition::FieldDefinition, directive_definitions::AppliedDir
 ///     email: String,
 /// }
 /// ```
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct TypeDefinition {
     pub ident: Box<str>,
     pub fields: HashMap<Box<str>, FieldDefinition>,
     pub directives: Vec<AppliedDirective>,
+    /// The doc comments (`##` or `///`) before the type
+    pub docs: Option<Box<str>>,
+
+    /// Where the type's name is in the source
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
\ No newline at end of file
@@ -2,21 +2,32 @@
 use std::collections::HashMap;
 
 use castle_schema_parser::{
-    parsers::parse_schema::parse_schema,
+    parsers::parse_schema::{parse_schema, parse_schema_recovering},
+    printer::print_schema,
     types::{
         AppliedDirective, DirectiveDefinition, DirectiveLocation, EnumDefinition, FieldDefinition,
         InputDefinition, Kind, SchemaDefinition, TypeDefinition, VariantDefinition,
-        VariantKindDefinition, InputTypeDefinition,
+        VariantKindDefinition, InputTypeDefinition, Span,
     },
 };
-use castle_shared_parser::Input;
+use castle_error::CastleError;
+use castle_shared_parser::{Input, VariantType};
 use castle_tokenizer::{Primitive, Number};
 
+/// Parses the schema, checking that printing it and parsing the printed schema gives the same definitions
+fn parse_and_round_trip(schema: &str) -> SchemaDefinition {
+    let parsed = parse_schema(schema).unwrap();
+    let printed = print_schema(&parsed);
+    let reparsed = parse_schema(&printed).unwrap_or_else(|e| panic!("failed to parse printed schema:\n{}\n{}", printed, e));
+    assert_eq!(parsed, reparsed, "printed schema:\n{}", printed);
+    parsed
+}
+
 #[test]
 fn can_parse_empty_message() {
     let query = "";
     let expected = SchemaDefinition::new();
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -44,17 +55,22 @@ fn can_parse_simple_type() {
                         return_kind: Kind {
                             ident: "Uuid".into(),
                             generics: vec![],
+                            span: Span::default(),
                         },
                         ident: "id".into(),
+                        docs: None,
+                        span: Span::default(),
                     },
                 )]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
 
     assert_eq!(expected, actual);
 }
@@ -86,8 +102,11 @@ fn can_parse_simple_type_more_fields_and_no_commas() {
                             return_kind: Kind {
                                 ident: "Uuid".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
                             ident: "id".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -98,8 +117,11 @@ fn can_parse_simple_type_more_fields_and_no_commas() {
                             return_kind: Kind {
                                 ident: "String".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
                             ident: "name".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -110,18 +132,23 @@ fn can_parse_simple_type_more_fields_and_no_commas() {
                             return_kind: Kind {
                                 ident: "number".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
                             ident: "age".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -161,8 +188,11 @@ fn can_parse_two_types() {
                                 return_kind: Kind {
                                     ident: "Uuid".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 ident: "id".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                         (
@@ -173,8 +203,11 @@ fn can_parse_two_types() {
                                 return_kind: Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 ident: "name".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                         (
@@ -185,11 +218,16 @@ fn can_parse_two_types() {
                                 return_kind: Kind {
                                     ident: "number".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 ident: "age".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                     ].into(),
+                    docs: None,
+                    span: Span::default(),
                 },
             ),
             (
@@ -206,8 +244,11 @@ fn can_parse_two_types() {
                                 return_kind: Kind {
                                     ident: "Uuid".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 ident: "id".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                         (
@@ -218,8 +259,11 @@ fn can_parse_two_types() {
                                 return_kind: Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 ident: "name".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                         (
@@ -230,19 +274,24 @@ fn can_parse_two_types() {
                                 return_kind: Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 ident: "industry".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                     ]
                     .into(),
+                    docs: None,
+                    span: Span::default(),
                 },
             ),
         ]
         .into(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -278,10 +327,14 @@ fn can_parse_types_with_two_vecs() {
                                         Kind {
                                             ident: "String".into(),
                                             generics: vec![],
+                                            span: Span::default(),
                                         },
                                     ],
+                                    span: Span::default(),
                                 },
                                 ident: "industries".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                         (
@@ -295,21 +348,27 @@ fn can_parse_types_with_two_vecs() {
                                         Kind {
                                             ident: "Organisation".into(),
                                             generics: vec![],
+                                            span: Span::default(),
                                         },
                                     ],
+                                    span: Span::default(),
                                 },
                                 ident: "related_orgs".into(),
+                                docs: None,
+                                span: Span::default(),
                             },
                         ),
                     ]
                     .into(),
+                    docs: None,
+                    span: Span::default(),
                 },
             ),
         ]
         .into(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -337,6 +396,8 @@ fn can_parse_enum_schema() {
                             ident: "Red".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -345,6 +406,8 @@ fn can_parse_enum_schema() {
                             ident: "Green".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -353,18 +416,22 @@ fn can_parse_enum_schema() {
                             ident: "Blue".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ]
                 .into(),
                 directives: vec![],
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
         types: HashMap::new(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -396,6 +463,8 @@ fn can_parse_enum_and_type() {
                             ident: "Red".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -404,6 +473,8 @@ fn can_parse_enum_and_type() {
                             ident: "Green".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -412,11 +483,15 @@ fn can_parse_enum_and_type() {
                             ident: "Blue".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ]
                 .into(),
                 directives: vec![],
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
@@ -434,17 +509,22 @@ fn can_parse_enum_and_type() {
                             return_kind: Kind {
                                 ident: "String".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
                             ident: "first_name".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ].into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -475,6 +555,8 @@ fn can_parse_enum_with_tuple_variant() {
                             ident: "Unit1".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -484,8 +566,11 @@ fn can_parse_enum_with_tuple_variant() {
                             kind: VariantKindDefinition::Tuple(vec![Kind {
                                 ident: "String".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             }]),
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -496,13 +581,17 @@ fn can_parse_enum_with_tuple_variant() {
                                 Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                                 Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 },
                             ]),
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -511,18 +600,22 @@ fn can_parse_enum_with_tuple_variant() {
                             ident: "Unit2".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ]
                 .into(),
                 directives: vec![],
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
         types: HashMap::new(),
     };
 
-    let actual = parse_schema(query).unwrap();
+    let actual = parse_and_round_trip(query);
     assert_eq!(expected, actual);
 }
 
@@ -558,6 +651,7 @@ fn can_parse_enum_with_fields() {
                                         Kind {
                                             ident: "Uuid".into(),
                                             generics: vec![],
+                                            span: Span::default(),
                                         },
                                     ),
                                     (
@@ -565,6 +659,7 @@ fn can_parse_enum_with_fields() {
                                         Kind {
                                             ident: "String".into(),
                                             generics: vec![],
+                                            span: Span::default(),
                                         },
                                     ),
                                     (
@@ -572,12 +667,15 @@ fn can_parse_enum_with_fields() {
                                         Kind {
                                             ident: "number".into(),
                                             generics: vec![],
+                                            span: Span::default(),
                                         },
                                     ),
                                 ]
                                 .into(),
                             ),
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -586,18 +684,22 @@ fn can_parse_enum_with_fields() {
                             ident: "Unit".into(),
                             kind: VariantKindDefinition::Unit,
                             directives: vec![],
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ]
                 .into(),
                 directives: vec![],
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
         types: HashMap::new(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
 }
 
@@ -630,9 +732,13 @@ fn can_parse_generic_field_type() {
                                 generics: vec![Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 }],
+                                span: Span::default(),
                             },
                             ident: "first_name".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                     (
@@ -645,18 +751,24 @@ fn can_parse_generic_field_type() {
                                 generics: vec![Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 }],
+                                span: Span::default(),
                             },
                             ident: "last_name".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     ),
                 ]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
-    assert_eq!(expected, parse_schema(schema).unwrap());
+    assert_eq!(expected, parse_and_round_trip(schema));
 }
 
 #[test]
@@ -684,27 +796,34 @@ fn can_parse_directives_on_fields() {
                                 ident: "bar".into(),
                                 inputs: [("baz".into(), Input::Primitive(Primitive::Number(Number::from(123))))]
                                     .into(),
+                                span: Span::default(),
                             },
                             AppliedDirective {
                                 ident: "foo".into(),
                                 inputs: HashMap::new(),
+                                span: Span::default(),
                             },
                         ],
                         input_definitions: HashMap::new(),
                         return_kind: Kind {
                             ident: "bool".into(),
                             generics: vec![],
+                            span: Span::default(),
                         },
                         ident: "is_admin".into(),
+                        docs: None,
+                        span: Span::default(),
                     },
                 )]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    assert_eq!(expected, parse_schema(schema).unwrap());
+    assert_eq!(expected, parse_and_round_trip(schema));
 }
 
 #[test]
@@ -729,12 +848,17 @@ fn can_parse_directive_definitions() {
                             input_kind: Kind {
                                 ident: "String".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
                             ident: "token".into(),
+                            docs: None,
+                            span: Span::default(),
                         },
                     )]
                     .into(),
                     locations: [DirectiveLocation::EnumDefinition].into(),
+                    docs: None,
+                    span: Span::default(),
                 },
             ),
             (
@@ -743,6 +867,8 @@ fn can_parse_directive_definitions() {
                     ident: "is_admin".into(),
                     input_definitions: HashMap::new(),
                     locations: [DirectiveLocation::InputDefinition].into(),
+                    docs: None,
+                    span: Span::default(),
                 },
             ),
         ]
@@ -751,7 +877,7 @@ fn can_parse_directive_definitions() {
         types: HashMap::new(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
 }
 
@@ -782,17 +908,22 @@ fn can_parse_comments() {
                         return_kind: Kind {
                             ident: "String".into(),
                             generics: vec![],
+                            span: Span::default(),
                         },
                         ident: "bar".into(),
+                        docs: None,
+                        span: Span::default(),
                     },
                 )]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
 }
 
@@ -825,19 +956,26 @@ fn generic_inside_generic_works() {
                                 generics: vec![Kind {
                                     ident: "String".into(),
                                     generics: vec![],
+                                    span: Span::default(),
                                 }],
+                                span: Span::default(),
                             }],
+                            span: Span::default(),
                         },
                         ident: "pets".into(),
+                        docs: None,
+                        span: Span::default(),
                     },
                 )]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
 }
 
@@ -865,14 +1003,19 @@ fn can_parse_input_type() {
                         input_kind: Kind {
                             ident: "String".into(),
                             generics: vec![],
+                            span: Span::default(),
                         },
+                        docs: None,
+                        span: Span::default(),
                     }),
                 ].into(),
+                docs: None,
+                span: Span::default(),
             }),
         ].into(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
 }
 
@@ -904,23 +1047,31 @@ fn can_parse_type_with_field_args() {
                             input_kind: Kind {
                                 ident: "String".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
+                            docs: None,
+                            span: Span::default(),
                         })]
                         .into(),
                         return_kind: Kind {
                             ident: "String".into(),
                             generics: vec![],
+                            span: Span::default(),
                         },
                         ident: "foo".into(),
+                        docs: None,
+                        span: Span::default(),
                     },
                 )]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
 }
 
@@ -952,22 +1103,300 @@ fn can_parse_type_with_custom_input_type() {
                             input_kind: Kind {
                                 ident: "Xyz".into(),
                                 generics: vec![],
+                                span: Span::default(),
                             },
+                            docs: None,
+                            span: Span::default(),
                         })]
                         .into(),
                         return_kind: Kind {
                             ident: "String".into(),
                             generics: vec![],
+                            span: Span::default(),
                         },
                         ident: "foo".into(),
+                        docs: None,
+                        span: Span::default(),
                     },
                 )]
                 .into(),
+                docs: None,
+                span: Span::default(),
             },
         )]
         .into(),
     };
 
-    let actual = parse_schema(schema).unwrap();
+    let actual = parse_and_round_trip(schema);
     assert_eq!(expected, actual);
-}
\ No newline at end of file
+}
+#[test]
+fn can_parse_input_definitions_with_defaults() {
+    let schema = "
+        type Root {
+            height(unit: String = \"m\" precision: number = 2 @rounded): number
+        }
+    ";
+
+    let expected = SchemaDefinition {
+        input_types: HashMap::new(),
+        directives: HashMap::new(),
+        enums: HashMap::new(),
+        types: [(
+            "Root".into(),
+            TypeDefinition {
+                ident: "Root".into(),
+                directives: vec![],
+                fields: [(
+                    "height".into(),
+                    FieldDefinition {
+                        directives: vec![],
+                        input_definitions: [
+                            ("unit".into(), InputDefinition {
+                                ident: "unit".into(),
+                                default: Some(Input::Primitive(Primitive::String("m".into()))),
+                                directives: vec![],
+                                input_kind: Kind {
+                                    ident: "String".into(),
+                                    generics: vec![],
+                                    span: Span::default(),
+                                },
+                                docs: None,
+                                span: Span::default(),
+                            }),
+                            ("precision".into(), InputDefinition {
+                                ident: "precision".into(),
+                                default: Some(Input::Primitive(Primitive::Number(Number::from(2)))),
+                                directives: vec![AppliedDirective {
+                                    ident: "rounded".into(),
+                                    inputs: HashMap::new(),
+                                    span: Span::default(),
+                                }],
+                                input_kind: Kind {
+                                    ident: "number".into(),
+                                    generics: vec![],
+                                    span: Span::default(),
+                                },
+                                docs: None,
+                                span: Span::default(),
+                            }),
+                        ]
+                        .into(),
+                        return_kind: Kind {
+                            ident: "number".into(),
+                            generics: vec![],
+                            span: Span::default(),
+                        },
+                        ident: "height".into(),
+                        docs: None,
+                        span: Span::default(),
+                    },
+                )]
+                .into(),
+                docs: None,
+                span: Span::default(),
+            },
+        )]
+        .into(),
+    };
+
+    let actual = parse_and_round_trip(schema);
+    assert_eq!(expected, actual);
+}
+
+#[test]
+fn records_where_definitions_are_in_the_source() {
+    let schema = "
+type User {
+    friends: Vec<User> @lowercase
+}
+";
+    let parsed = parse_and_round_trip(schema);
+    let user = &parsed.types["User"];
+    let friends = &user.fields["friends"];
+    let position = |span: Span| (span.start.line_number(), span.start.column_number(), span.end.column_number());
+
+    assert_eq!(position(user.span), (2, 6, 10));
+    assert_eq!(position(friends.span), (3, 5, 12));
+    assert_eq!(position(friends.return_kind.span), (3, 14, 23));
+    assert_eq!(position(friends.return_kind.generics[0].span), (3, 18, 22));
+    assert_eq!(position(friends.directives[0].span), (3, 24, 34));
+}
+
+#[test]
+fn recovering_parser_carries_on_after_errors() {
+    let schema = "
+type User {
+    name: String
+    age number
+    email: String
+    avatar(size: number = ): String
+}
+
+enum Icon {
+    Emoji(String
+    NoIcon
+}
+
+type 5 {}
+
+input Crop {
+    x: number, y: 5, z: number
+}
+";
+    let (parsed, errors) = parse_schema_recovering(schema);
+    assert!(parse_schema(schema).is_err());
+
+    let mut fields: Vec<_> = parsed.types["User"].fields.keys().map(|key| &**key).collect();
+    fields.sort();
+    assert_eq!(fields, vec!["avatar", "email", "name"]);
+    // the malformed input is left out of the field
+    assert!(parsed.types["User"].fields["avatar"].input_definitions.is_empty());
+    let mut inputs: Vec<_> = parsed.input_types["Crop"].input_definitions.keys().map(|key| &**key).collect();
+    inputs.sort();
+    assert_eq!(inputs, vec!["x", "z"]);
+    assert!(parsed.enums.contains_key("Icon"));
+    assert_eq!(parsed.types.len(), 1);
+
+    let lines: Vec<_> = errors
+        .iter()
+        .map(|error| match error {
+            CastleError::Parser(_, span) | CastleError::Schema(_, span) => span.start.line_number(),
+            error => panic!("expected an error with a span, got {:?}", error),
+        })
+        .collect();
+    // the unclosed tuple takes the `}` of the enum, so the enum isn't closed before `type 5`
+    assert_eq!(lines, vec![4, 6, 12, 14, 14, 17]);
+}
+
+#[test]
+fn recovering_parser_keeps_unclosed_definitions() {
+    let (parsed, errors) = parse_schema_recovering("type User {\n    name: String\n\ntype Post {\n    title: String\n");
+    assert!(parsed.types["User"].fields.contains_key("name"));
+    assert!(parsed.types["Post"].fields.contains_key("title"));
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn recovering_parser_skips_untokenizable_characters() {
+    let (parsed, errors) = parse_schema_recovering("type User {\n    name: String %\n    age: number\n}\n");
+    assert_eq!(parsed.types["User"].fields.len(), 2);
+    assert!(matches!(&errors[..], [CastleError::Syntax(_, pos)] if pos.line_number() == 2));
+
+    let (_, errors) = parse_schema_recovering(SCHEMA_WITHOUT_ERRORS);
+    assert!(errors.is_empty());
+}
+
+const SCHEMA_WITHOUT_ERRORS: &str = "
+directive @lowercase on FieldDefinition
+
+type Root {
+    me: String @lowercase
+}
+";
+
+#[test]
+fn doc_comments_are_kept_on_definitions() {
+    let schema = r#"
+        ## A person
+        ##
+        ## who uses the app
+        @cached
+        type User {
+            ## shown to others
+            name: String
+            # not a doc comment
+            age: number
+            email: String /// trailing
+            nickname: String
+            /// in pixels
+            avatar(
+                ## between 16 and 512
+                size: number
+            ): String
+        }
+        /// How a user looks
+        enum Icon {
+            ## no icon at all
+            NoIcon
+            Emoji(String)
+        }
+        ## A crop of an image
+        input Crop {
+            /// from the left
+            x: number
+        }
+        ## Lowercases a string
+        directive @lowercase on FieldDefinition
+    "#;
+
+    let parsed = parse_schema(schema).unwrap();
+    let user = &parsed.types["User"];
+    assert_eq!(user.docs.as_deref(), Some("A person\n\nwho uses the app"));
+    assert_eq!(user.fields["name"].docs.as_deref(), Some("shown to others"));
+    assert_eq!(user.fields["age"].docs, None);
+    assert_eq!(user.fields["nickname"].docs, None);
+    assert_eq!(user.fields["avatar"].docs.as_deref(), Some("in pixels"));
+    assert_eq!(user.fields["avatar"].input_definitions["size"].docs.as_deref(), Some("between 16 and 512"));
+    let icon = &parsed.enums["Icon"];
+    assert_eq!(icon.docs.as_deref(), Some("How a user looks"));
+    assert_eq!(icon.variants["NoIcon"].docs.as_deref(), Some("no icon at all"));
+    assert_eq!(icon.variants["Emoji"].docs, None);
+    let crop = &parsed.input_types["Crop"];
+    assert_eq!(crop.docs.as_deref(), Some("A crop of an image"));
+    assert_eq!(crop.input_definitions["x"].docs.as_deref(), Some("from the left"));
+    assert_eq!(parsed.directives["lowercase"].docs.as_deref(), Some("Lowercases a string"));
+}
+
+#[test]
+fn none_and_null_are_only_literals_in_values() {
+    let schema = "
+        enum Shape {
+            None
+            Circle(number)
+        }
+        type Root {
+            null: String
+            avatar(size: Option<number> = None, shape: Shape = Circle(null)): String
+        }
+    ";
+
+    let parsed = parse_schema(schema).unwrap();
+    assert!(parsed.enums["Shape"].variants.contains_key("None"));
+    let root = &parsed.types["Root"];
+    assert!(root.fields.contains_key("null"));
+    let inputs = &root.fields["avatar"].input_definitions;
+    assert_eq!(inputs["size"].default, Some(Input::None));
+    let shape = match &inputs["shape"].default {
+        Some(Input::Variant(variant)) => variant,
+        default => panic!("expected a variant, got {:?}", default),
+    };
+    assert_eq!(shape.ident(), "Circle");
+    assert_eq!(shape.value(), &VariantType::Tuple(vec![Input::None]));
+}
+
+#[test]
+fn input_definitions_can_be_separated_by_commas_or_newlines() {
+    let schema = "
+        input Crop { x: number, y: number = 0,
+            width: number
+            , height: number, }
+        type Root {
+            avatar(size: number, crop: Option<Crop>,): String
+            banner(
+                width: number,
+                height: number
+            ): String
+        }
+        directive @cached(seconds: number, scope: String) on FieldDefinition
+    ";
+
+    let parsed = parse_schema(schema).unwrap();
+    let mut crop: Vec<_> = parsed.input_types["Crop"].input_definitions.keys().map(|key| &**key).collect();
+    crop.sort();
+    assert_eq!(crop, vec!["height", "width", "x", "y"]);
+    let root = &parsed.types["Root"];
+    assert_eq!(root.fields["avatar"].input_definitions.len(), 2);
+    assert_eq!(root.fields["banner"].input_definitions.len(), 2);
+    assert_eq!(parsed.directives["cached"].input_definitions.len(), 2);
+}
@@ -0,0 +1,123 @@
+use castle_schema_parser::{parsers::parse_schema::parse_schema, printer::print_schema};
+
+#[test]
+fn prints_schemas_in_canonical_form() {
+    let schema = r#"
+        input Crop { y: number = 0.0, x: number }
+        enum Icon { NoIcon @deprecated, SVGIcon { url: String, size: number }, Emoji(String) }
+        @cached(seconds: 60)
+        type User {
+            name: String @lowercase
+            avatar(size: number = 48 crop: Option<Crop>): String
+        }
+        type Root { me: User, users(ids: Vec<Uuid> = []): Vec<User> }
+        directive @lowercase on InputFieldDefinition | FieldDefinition
+        directive @cached(seconds: number, scope: Scope = Private { user: "me" }) on TypeDefinition
+    "#;
+
+    let expected = r#"directive @cached(scope: Scope = Private { user: "me" }, seconds: number) on TypeDefinition
+
+directive @lowercase on FieldDefinition | InputFieldDefinition
+
+type Root {
+    me: User
+    users(ids: Vec<Uuid> = []): Vec<User>
+}
+
+@cached(seconds: 60)
+type User {
+    avatar(crop: Option<Crop>, size: number = 48): String
+    name: String @lowercase
+}
+
+enum Icon {
+    Emoji(String)
+    NoIcon @deprecated
+    SVGIcon {
+        size: number
+        url: String
+    }
+}
+
+input Crop {
+    x: number
+    y: number = 0
+}
+"#;
+
+    let printed = print_schema(&parse_schema(schema).unwrap());
+    assert_eq!(printed, expected);
+    assert_eq!(parse_schema(&printed).unwrap(), parse_schema(schema).unwrap());
+    assert_eq!(print_schema(&parse_schema(&printed).unwrap()), expected);
+}
+
+#[test]
+fn prints_values_that_parse_to_the_same_value() {
+    let schema = r#"
+        type Root {
+            search(
+                query: String = "say \"hi\"\n\tback\\slash"
+                limit: number = -5
+                ratio: number = 2.5
+                tags: Vec<String> = ["a", "b"]
+                filter: Option<Filter> = None
+            ): String
+        }
+    "#;
+
+    let parsed = parse_schema(schema).unwrap();
+    let printed = print_schema(&parsed);
+    assert!(printed.contains(r#"query: String = "say \"hi\"\n\tback\\slash""#), "{}", printed);
+    assert!(printed.contains("ratio: number = 2.5"), "{}", printed);
+    assert!(printed.contains("limit: number = -5"), "{}", printed);
+    assert_eq!(parse_schema(&printed).unwrap(), parsed);
+}
+
+#[test]
+fn prints_doc_comments_before_definitions() {
+    let schema = r#"
+        /// A person
+        ///
+        /// who uses the app
+        type User {
+            ## shown to others
+            name: String
+            avatar(
+                ## in pixels
+                size: number, crop: Option<Crop>): String
+        }
+        ## How a user looks
+        enum Icon {
+            ## no icon at all
+            NoIcon }
+    "#;
+
+    let expected = r#"## A person
+##
+## who uses the app
+type User {
+    avatar(
+        crop: Option<Crop>
+        ## in pixels
+        size: number
+    ): String
+    ## shown to others
+    name: String
+}
+
+## How a user looks
+enum Icon {
+    ## no icon at all
+    NoIcon
+}
+"#;
+
+    let printed = print_schema(&parse_schema(schema).unwrap());
+    assert_eq!(printed, expected);
+    assert_eq!(parse_schema(&printed).unwrap(), parse_schema(schema).unwrap());
+}
+
+#[test]
+fn empty_schemas_print_nothing() {
+    assert_eq!(print_schema(&parse_schema("").unwrap()), "");
+}
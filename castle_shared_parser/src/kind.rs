@@ -1,12 +1,19 @@
 use std::fmt::Display;
 
+use castle_input_cursor::Span;
+
 
 /// Definition of a type as a return value;
 /// e.g. `Vec<User>`, `User` or `String`
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(PartialEq)]
 pub struct Kind {
     pub ident: Box<str>,
     pub generics: Vec<Kind>,
+
+    /// Where the kind is written in the source, including any generics
+    #[derivative(PartialEq = "ignore")]
+    pub span: Span,
 }
 
 impl Display for Kind {
@@ -1,8 +1,14 @@
 use std::{collections::HashMap, fmt::Display};
 
 pub use castle_tokenizer::Primitive;
+pub use castle_input_cursor::Span;
 
 pub mod parse_inputs;
+pub mod parse_kind;
+pub mod print;
+mod kind;
+
+pub use kind::Kind;
 
 
 // (ident: primitive, ident2: primitive)
@@ -13,6 +19,8 @@ pub mod parse_inputs;
 // })
 // (ident: Variant (value, value), ident_2: Primitive) // this is a tuple variant
 // (ident: Variant, ident_2: primitive) // is a unit variant
+// (ident: $variable) // a variable declared by the message
+// (ident: None) // no value, for Option<T> inputs (null is also accepted)
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Input {
@@ -20,6 +28,10 @@ pub enum Input {
     Variant(Variant),
     Map(HashMap<Box<str>, Input>),
     List(Vec<Input>),
+    /// A reference to a message variable, eg: `$id`
+    Variable(Box<str>),
+    /// No value, written as `None` or `null`
+    None,
 }
 
 impl Input {
@@ -47,14 +59,65 @@ impl Input {
             _ => None,
         }
     }
+    pub fn is_none(&self) -> bool {
+        matches!(self, Input::None)
+    }
+    /// Parses a string input as a [Uuid](uuid::Uuid)
+    pub fn as_uuid(&self) -> Option<uuid::Uuid> {
+        self.as_str().and_then(|str| uuid::Uuid::parse_str(str).ok())
+    }
+    /// Parses each item of a list input as a [Uuid](uuid::Uuid), `None` if any item isn't a valid uuid
+    pub fn as_uuid_list(&self) -> Option<Vec<uuid::Uuid>> {
+        self.as_list()?.iter().map(Input::as_uuid).collect()
+    }
+    pub fn as_variable(&self) -> Option<&str> {
+        match self {
+            Input::Variable(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Replaces each [Input::Variable] (including ones nested in lists, maps and variants)
+    /// with the value `lookup` returns for the variable's name.
+    /// Variables `lookup` returns `None` for are left as they are.
+    pub fn substitute_variables<'a>(&mut self, lookup: &impl Fn(&str) -> Option<&'a Input>) {
+        match self {
+            Input::Variable(name) => if let Some(value) = lookup(name) {
+                *self = value.clone();
+            },
+            Input::List(list) => for item in list {
+                item.substitute_variables(lookup);
+            },
+            Input::Map(map) => for item in map.values_mut() {
+                item.substitute_variables(lookup);
+            },
+            Input::Variant(Variant { value: VariantType::Tuple(items), .. }) => for item in items {
+                item.substitute_variables(lookup);
+            },
+            Input::Variant(Variant { value: VariantType::Map(map), .. }) => for item in map.values_mut() {
+                item.substitute_variables(lookup);
+            },
+            Input::Variant(_) | Input::Primitive(_) | Input::None => {},
+        }
+    }
 }
 
+/// An enum variant provided as an input, eg: `Meter`, `Rgb(255, 0, 0)` or `Email { address: "..." }`
 #[derive(Debug, PartialEq, Clone)]
 pub struct Variant {
     ident: Box<str>,
     value: VariantType,
 }
 
+impl Variant {
+    pub fn ident(&self) -> &str {
+        &self.ident
+    }
+    pub fn value(&self) -> &VariantType {
+        &self.value
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum VariantType {
     Unit,
@@ -69,6 +132,8 @@ impl Display for Input {
             Input::Variant(variant) => write!(f, "{:#?}", variant),
             Input::Map(map) => write!(f, "{:#?}", map),
             Input::List(list) => write!(f, "{}", list.iter().map(|item| format!("{}", item)).collect::<Vec<String>>().join(", ")),
+            Input::Variable(name) => write!(f, "${}", name),
+            Input::None => write!(f, "None"),
         }
     }
 }
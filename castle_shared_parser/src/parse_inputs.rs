@@ -22,10 +22,15 @@ pub fn parse_inputs(
     parse_map(tokenizer, Punctuator::OpenParen, Punctuator::CloseParen)
 }
 
-fn parse_value(tokenizer: &mut impl Tokenizable) -> Result<Input, CastleError> {
+pub fn parse_value(tokenizer: &mut impl Tokenizable) -> Result<Input, CastleError> {
     let value = tokenizer.peek_expect(true)?;
     Ok(match &value.kind {
-        TokenKind::Identifier(_) => Input::Variant(parse_variant(tokenizer)?), // ident // this is for tuples
+        TokenKind::Identifier(_) => match parse_variant(tokenizer)? {
+            // `None` and `null` are only literals where a value is expected, elsewhere they can
+            // still name fields (schema validation rejects variants named after them)
+            Variant { ident, value: VariantType::Unit } if matches!(&*ident, "None" | "null") => Input::None,
+            variant => Input::Variant(variant), // ident // this is for tuples
+        },
         TokenKind::Primitive(_) => Input::Primitive(tokenizer.expect_primitive(true)?),
         TokenKind::Punctuator(Punctuator::OpenBlock) => Input::Map(parse_map(
             tokenizer,
@@ -37,13 +42,20 @@ fn parse_value(tokenizer: &mut impl Tokenizable) -> Result<Input, CastleError> {
             Punctuator::OpenBracket,
             Punctuator::CloseBracket,
         )?),
+        TokenKind::Punctuator(Punctuator::Dollar) => Input::Variable(parse_variable(tokenizer)?),
         _ => Err(CastleError::Schema(
-            "Expected primitive, map, list, or variant".into(),
+            "Expected primitive, map, list, variant, variable or None".into(),
             value.span,
         ))?
     })
 }
 
+/// Parses a variable name, eg: `$id`
+pub fn parse_variable(tokenizer: &mut impl Tokenizable) -> Result<Box<str>, CastleError> {
+    tokenizer.expect_punctuator(Punctuator::Dollar, true)?;
+    tokenizer.expect_identifier(false)
+}
+
 fn expect_colon_and_value(tokenizer: &mut impl Tokenizable) -> Result<Input, CastleError> {
     tokenizer.expect_punctuator(Punctuator::Colon, true)?;
     parse_value(tokenizer)
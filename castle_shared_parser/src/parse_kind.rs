@@ -0,0 +1,48 @@
+use castle_error::CastleError;
+use crate::parse_inputs::consume_optional_separator;
+use castle_tokenizer::{Tokenizable, TokenKind, extensions::{ExpectIdentifier, ExpectPunctuator, IsPunctuator}, Punctuator, Token};
+
+use crate::{Kind, Span};
+
+
+pub fn parse_kind(tokenizer: &mut impl Tokenizable) -> Result<Kind, CastleError> {
+    let (ident, ident_span) = tokenizer.expect_identifier_with_span(true)?;
+    let (generics, generics_span) = parse_generics(tokenizer)?;
+    Ok(Kind {
+        ident,
+        generics,
+        span: generics_span.map_or(ident_span, |generics_span| ident_span.to(generics_span)),
+    })
+}
+
+/// if we see a `<` then we have a generic type
+/// otherwise we have a non-generic type
+/// we have to check if it is a punctuator
+///
+/// also returns the span of the generics (`<...>`) if there were any
+pub fn parse_generics(tokenizer: &mut impl Tokenizable) -> Result<(Vec<Kind>, Option<Span>), CastleError> {
+    let mut generics = Vec::new();
+    match tokenizer.peek(true)? {
+        Some(Token { kind: TokenKind::Punctuator(Punctuator::GenericOpen), span }) => {
+            let span = *span;
+            tokenizer.expect_punctuator(Punctuator::GenericOpen, true)?;
+            loop {
+                if tokenizer.peek_is_punctuator(Punctuator::GenericClose, true)? {
+                    break
+                }
+                generics.push(parse_kind(tokenizer)?);
+                consume_optional_separator(tokenizer)?;
+            }
+
+            if generics.is_empty() {
+                return Err(CastleError::Schema("Generics <T> need at least one type argument".into(), span));
+            }
+
+            let close_span = tokenizer.peek_expect(true)?.span;
+            tokenizer.expect_punctuator(Punctuator::GenericClose, true)?;
+            Ok((generics, Some(span.to(close_span))))
+        },
+        // no `<` (or the end of the source) means there are no generics
+        _ => Ok((generics, None)),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,70 @@
+use crate::{Input, Variant, VariantType};
+use castle_tokenizer::Primitive;
+
+/// Prints an input the way it's written in a schema or message, maps are sorted by key
+/// so the same input is always printed the same way
+pub fn print_input(input: &Input) -> String {
+    match input {
+        Input::Primitive(primitive) => print_primitive(primitive),
+        Input::Variant(variant) => print_variant(variant),
+        Input::Map(map) => print_map(map),
+        Input::List(list) => format!("[{}]", list.iter().map(print_input).collect::<Vec<_>>().join(", ")),
+        Input::Variable(name) => format!("${}", name),
+        Input::None => "None".into(),
+    }
+}
+
+pub fn print_primitive(primitive: &Primitive) -> String {
+    match primitive {
+        Primitive::String(string) => print_string(string),
+        Primitive::Number(number) => number.to_string(),
+        Primitive::Boolean(b) => b.to_string(),
+    }
+}
+
+/// Quotes a string, escaping the characters the tokenizer unescapes
+pub fn print_string(string: &str) -> String {
+    let mut printed = String::with_capacity(string.len() + 2);
+    printed.push('"');
+    for ch in string.chars() {
+        match ch {
+            '"' => printed.push_str("\\\""),
+            '\\' => printed.push_str("\\\\"),
+            '\n' => printed.push_str("\\n"),
+            '\r' => printed.push_str("\\r"),
+            '\t' => printed.push_str("\\t"),
+            '\u{0008}' => printed.push_str("\\b"),
+            '\u{000C}' => printed.push_str("\\f"),
+            ch if ch.is_control() => printed.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => printed.push(ch),
+        }
+    }
+    printed.push('"');
+    printed
+}
+
+fn print_variant(variant: &Variant) -> String {
+    match &variant.value {
+        VariantType::Unit => variant.ident.to_string(),
+        VariantType::Tuple(items) => format!("{}({})", variant.ident, items.iter().map(print_input).collect::<Vec<_>>().join(", ")),
+        VariantType::Map(map) => format!("{} {}", variant.ident, print_map(map)),
+    }
+}
+
+fn print_map(map: &std::collections::HashMap<Box<str>, Input>) -> String {
+    if map.is_empty() {
+        return "{}".into()
+    }
+    format!("{{ {} }}", print_sorted_inputs(map))
+}
+
+/// Prints `key: value` pairs sorted by key and separated by commas, eg: the inputs of a field or directive
+pub fn print_sorted_inputs(inputs: &std::collections::HashMap<Box<str>, Input>) -> String {
+    let mut inputs: Vec<_> = inputs.iter().collect();
+    inputs.sort_by_key(|(key, _)| *key);
+    inputs
+        .into_iter()
+        .map(|(key, value)| format!("{}: {}", key, print_input(value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,6 +1,6 @@
 
-
 use castle_error::CastleError;
+use castle_input_cursor::Span;
 
 use crate::{Keyword, Tokenizable, TokenKind, Punctuator, Token, Primitive};
 
@@ -80,6 +80,14 @@ pub trait ExpectIdentifier: Tokenizable + Sized {
         &mut self,
         skip_line_terminators: bool,
     ) -> Result<Box<str>, CastleError> {
+        Ok(self.expect_identifier_with_span(skip_line_terminators)?.0)
+    }
+
+    /// Same as [ExpectIdentifier::expect_identifier], but also returns where the identifier is in the source
+    fn expect_identifier_with_span(
+        &mut self,
+        skip_line_terminators: bool,
+    ) -> Result<(Box<str>, Span), CastleError> {
         let identifier = self
             .next(skip_line_terminators)?
             .ok_or(CastleError::AbruptEOF(
@@ -87,7 +95,7 @@ pub trait ExpectIdentifier: Tokenizable + Sized {
             ))?;
 
         match identifier.kind {
-            TokenKind::Identifier(str) => Ok(str),
+            TokenKind::Identifier(str) => Ok((str, identifier.span)),
             _ => Err(CastleError::parse(
                 format!("Expected identifier, got '{:?}'", identifier.kind),
                 identifier.span,
@@ -123,10 +131,101 @@ pub trait IsPunctuator: Tokenizable + Sized {
     }
 }
 
+/// Skipping past malformed parts of the source, so parsing can carry on after an error has
+/// been recovered from (see [Tokenizable::recover])
+pub trait Resynchronize: Tokenizable + Sized {
+    /// Skips the rest of a malformed item of a block (eg: a field), up to and including the
+    /// next separator (`,` or a new line) that isn't nested in brackets.
+    ///
+    /// Stops before the block's `closing` punctuator (or the `}` or `]` of an outer block), or
+    /// the start of the next definition, and returns whether there are more items in the block.
+    /// Stray `)`s (eg: from malformed inputs) are skipped
+    fn skip_past_separator(&mut self, closing: Punctuator) -> Result<bool, CastleError> {
+        let mut depth = 0;
+        loop {
+            match self.peek_token_kind(false)? {
+                None => return Ok(false),
+                Some(kind) if starts_definition(kind) => return Ok(false),
+                Some(TokenKind::Punctuator(punctuator)) if is_closing(*punctuator) && depth > 0 => depth -= 1,
+                Some(TokenKind::Punctuator(punctuator)) if *punctuator == closing || is_closing_block(*punctuator) => {
+                    return Ok(false)
+                },
+                Some(TokenKind::Punctuator(punctuator)) if is_opening(*punctuator) => depth += 1,
+                Some(TokenKind::Punctuator(Punctuator::Comma) | TokenKind::LineTerminator) if depth == 0 => {
+                    self.next(false)?;
+                    return Ok(true)
+                },
+                _ => {},
+            }
+            self.next(false)?;
+        }
+    }
+
+    /// Skips to the start of the next definition (`type`, `enum`, `input` or `directive`)
+    fn skip_to_definition(&mut self) -> Result<(), CastleError> {
+        loop {
+            match self.peek_token_kind(true)? {
+                Some(kind) if !starts_definition(kind) => self.next(true)?,
+                _ => return Ok(()),
+            };
+        }
+    }
+
+    /// Whether the items of a block are over: its `closing` punctuator is next, or the block
+    /// isn't closed before the next definition or the end of the source
+    fn at_block_end(&mut self, closing: Punctuator) -> Result<bool, CastleError> {
+        Ok(match self.peek_token_kind(true)? {
+            Some(TokenKind::Punctuator(actual)) => *actual == closing,
+            Some(kind) => starts_definition(kind),
+            None => true,
+        })
+    }
+
+    /// Expects the `closing` punctuator of a block, after its items have been parsed.
+    ///
+    /// When recovering, a block cut short by the next definition or the end of the source is
+    /// left unclosed, and anything else before the `closing` punctuator is skipped
+    fn expect_block_end(&mut self, closing: Punctuator) -> Result<(), CastleError> {
+        let error = match self.peek(true)? {
+            Some(Token { kind: TokenKind::Punctuator(actual), .. }) if *actual == closing => {
+                return self.expect_punctuator(closing, true)
+            },
+            Some(Token { kind, span }) => CastleError::parse(
+                format!("Expected punctuator '{:?}', but got '{:?}'", closing, kind),
+                *span,
+            ),
+            None => CastleError::AbruptEOF("Error found in 'expect_punctuator'".into()),
+        };
+        self.recover(error)?;
+        while self.skip_past_separator(closing)? {}
+        match self.peek_is_punctuator(closing, true)? {
+            true => self.expect_punctuator(closing, true),
+            false => Ok(()),
+        }
+    }
+}
+
+fn starts_definition(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::Keyword(Keyword::Type | Keyword::Enum | Keyword::Input | Keyword::Directive))
+}
+
+fn is_opening(punctuator: Punctuator) -> bool {
+    matches!(punctuator, Punctuator::OpenBlock | Punctuator::OpenParen | Punctuator::OpenBracket)
+}
+
+fn is_closing(punctuator: Punctuator) -> bool {
+    matches!(punctuator, Punctuator::CloseParen) || is_closing_block(punctuator)
+}
+
+fn is_closing_block(punctuator: Punctuator) -> bool {
+    matches!(punctuator, Punctuator::CloseBlock | Punctuator::CloseBracket)
+}
+
 impl<T: Tokenizable> PeekKeyword for T {}
 impl<T: Tokenizable> ExpectNext for T {}
 impl<T: Tokenizable> ExpectPunctuator for T {}
 impl<T: Tokenizable> ExpectKeyword for T {}
 impl<T: Tokenizable> ExpectIdentifier for T {}
 impl<T: Tokenizable> ExpectPrimitive for T {}
-impl<T: Tokenizable> IsPunctuator for T {}
\ No newline at end of file
+impl<T: Tokenizable> IsPunctuator for T {}
+impl<T: Tokenizable> Resynchronize for T {}
\ No newline at end of file
@@ -4,7 +4,7 @@
 
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Keyword {
     As, // as
     True, // true
@@ -13,17 +13,45 @@ pub struct Number {
     pub(crate) n: NumberKind,
 }
 
+/// Numbers are serialized as plain numbers (eg: `3`, `-3` or `3.5` in JSON), keeping whether
+/// they are unsigned, signed or floating point for formats that can tell them apart
 impl Serialize for Number {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where S: serde::Serializer {
-        self.n.serialize(serializer)
+        match self.n {
+            NumberKind::Float(f) => serializer.serialize_f64(f),
+            NumberKind::Int(i) => serializer.serialize_i64(i),
+            NumberKind::UInt(u) => serializer.serialize_u64(u),
+        }
     }
 }
 
 impl<'de> Deserialize<'de> for Number {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where D: serde::Deserializer<'de> {
-        Ok(Number { n: NumberKind::deserialize(deserializer)? })
+        deserializer.deserialize_any(NumberVisitor)
+    }
+}
+
+struct NumberVisitor;
+
+impl<'de> serde::de::Visitor<'de> for NumberVisitor {
+    type Value = Number;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a number")
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, i: i64) -> Result<Number, E> {
+        Ok(i.into())
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, u: u64) -> Result<Number, E> {
+        Ok(u.into())
+    }
+
+    fn visit_f64<E: serde::de::Error>(self, f: f64) -> Result<Number, E> {
+        Ok(f.into())
     }
 }
 
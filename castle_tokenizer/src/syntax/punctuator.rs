@@ -15,6 +15,7 @@ pub enum Punctuator {
     Comma, // ,
     Spread, // ...
     At, // @ - Used for directives
+    Dollar, // $ - Used for message variables
     DoubleColon, // ::
 
     // Brackets, Parenthesis, Blocks
@@ -42,6 +43,7 @@ impl Punctuator {
             "," => Some(Punctuator::Comma),
             "..." => Some(Punctuator::Spread),
             "@" => Some(Punctuator::At),
+            "$" => Some(Punctuator::Dollar),
             "::" => Some(Punctuator::DoubleColon),
 
             "{" => Some(Punctuator::OpenBlock),
@@ -17,6 +17,8 @@ pub enum TokenKind {
     Punctuator(Punctuator),
     LineTerminator,
     Keyword(Keyword),
+    /// The text of a `# comment` after the `#`, only produced by [Tokenizer::with_comments](crate::Tokenizer::with_comments)
+    Comment(Box<str>),
 }
 
 impl Token {
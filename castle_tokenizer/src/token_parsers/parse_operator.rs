@@ -30,6 +30,7 @@ pub fn parse_operator(cursor: &mut Cursor<impl Read>, start_pos: Position ) -> R
         b']' => Punctuator::CloseBracket,
         b',' => Punctuator::Comma,
         b'@' => Punctuator::At,
+        b'$' => Punctuator::Dollar,
         b';' => Punctuator::SemiColon,
         b'.' => Punctuator::Dot,
         b':' => {
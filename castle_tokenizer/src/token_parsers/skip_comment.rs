@@ -1,7 +1,9 @@
 use std::io::Read;
 
 use castle_error::CastleError;
-use castle_input_cursor::Cursor;
+use castle_input_cursor::{Cursor, Position, Span};
+
+use crate::{Token, TokenKind};
 
 pub(crate) fn skip_comment(cursor: &mut Cursor<impl Read>) -> Result<(), CastleError> {
     // while the next byte is not a newline or EOF
@@ -11,4 +13,59 @@ pub(crate) fn skip_comment(cursor: &mut Cursor<impl Read>) -> Result<(), CastleE
             _ => continue
         }
     }
-}
\ No newline at end of file
+}
+
+/// Parses a comment into a [TokenKind::Comment] holding the text after the `#`,
+/// leaving the line terminator after it to be parsed as its own token
+pub(crate) fn parse_comment(cursor: &mut Cursor<impl Read>, start: Position) -> Result<Token, CastleError> {
+    // consume the #
+    cursor.next_byte()?;
+    let text = rest_of_line(cursor)?;
+    Ok(Token::new(TokenKind::Comment(text.trim_end().into()), Span::new(start, cursor.pos())))
+}
+
+/// Parses a `/// docs` comment into a [TokenKind::Comment] as if it were written `## docs`
+pub(crate) fn parse_slash_comment(cursor: &mut Cursor<impl Read>, start: Position) -> Result<Token, CastleError> {
+    // consume the ///
+    for _ in 0..3 {
+        cursor.next_byte()?;
+    }
+    let text = format!("#{}", rest_of_line(cursor)?.trim_end());
+    Ok(Token::new(TokenKind::Comment(text.into()), Span::new(start, cursor.pos())))
+}
+
+/// Whether the next bytes start a doc comment, `##` or `///` followed by a space or the end of
+/// the line (so `#####` banners aren't docs)
+pub(crate) fn is_doc_comment(cursor: &mut Cursor<impl Read>) -> Result<bool, CastleError> {
+    Ok(match cursor.peek_n(4)?.to_le_bytes() {
+        [b'#', b'#', after, _] | [b'/', b'/', b'/', after] => matches!(after, b' ' | b'\t' | b'\r' | b'\n' | 0),
+        _ => false,
+    })
+}
+
+/// Parses a `## docs` or `/// docs` comment into the text of the docs, without the `##` or
+/// `///` and the space after it
+pub(crate) fn parse_doc_comment(cursor: &mut Cursor<impl Read>) -> Result<String, CastleError> {
+    let marker = match cursor.peek()? {
+        Some(b'#') => 2,
+        _ => 3,
+    };
+    for _ in 0..marker {
+        cursor.next_byte()?;
+    }
+    let text = rest_of_line(cursor)?;
+    let text = text.strip_prefix(' ').unwrap_or(&text);
+    Ok(text.trim_end().into())
+}
+
+/// Reads up to the end of the line, leaving the line terminator
+fn rest_of_line(cursor: &mut Cursor<impl Read>) -> Result<String, CastleError> {
+    let mut bytes = Vec::new();
+    loop {
+        match cursor.peek()? {
+            Some(b'\n') | Some(b'\r') | None => break,
+            Some(_) => bytes.extend(cursor.next_byte()?),
+        }
+    }
+    Ok(String::from_utf8_lossy(&bytes).into())
+}
@@ -1,6 +1,7 @@
 
 
 use castle_error::CastleError;
+use castle_input_cursor::Position;
 
 use crate::{Token, TokenKind};
 pub trait Tokenizable {
@@ -11,6 +12,19 @@ pub trait Tokenizable {
         skip_line_terminators: bool,
     ) -> Result<Option<&Token>, CastleError>;
 
+    /// Called with an error the parser can resynchronize after (eg: a malformed field).
+    /// Returns the error, unless the tokenizer is recovering (see [Tokenizer::recovering](crate::Tokenizer::recovering))
+    /// in which case it is kept so parsing can carry on
+    fn recover(&mut self, error: CastleError) -> Result<(), CastleError> {
+        Err(error)
+    }
+
+    /// Takes the doc comments (`##` or `///`) before the token starting at `pos`, the lines
+    /// of the comments are joined with `\n`
+    fn take_docs(&mut self, _pos: Position) -> Option<Box<str>> {
+        None
+    }
+
     fn peek(&mut self, skip_line_terminators: bool) -> Result<Option<&Token>, CastleError> {
         self.peek_n(0, skip_line_terminators)
     }
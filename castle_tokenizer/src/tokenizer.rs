@@ -1,13 +1,16 @@
-use std::{collections::VecDeque, io::Read};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::Read,
+};
 
 use castle_error::CastleError;
-use castle_input_cursor::Cursor;
+use castle_input_cursor::{Cursor, Position};
 
 use crate::{
     token_parsers::{
         parse_ident_or_keyword::parse_ident_or_keyword, parse_newline::parse_newline,
         parse_numbers::parse_number, parse_operator::parse_operator, parse_string::parse_string,
-        skip_comment::skip_comment,
+        skip_comment::{is_doc_comment, parse_comment, parse_doc_comment, parse_slash_comment, skip_comment},
     },
     Token, TokenKind, Tokenizable,
 };
@@ -15,6 +18,21 @@ use crate::{
 pub struct Tokenizer<R> {
     pub cursor: Cursor<R>,
     pub peeked: VecDeque<Token>,
+    /// Whether comments are returned as [TokenKind::Comment] tokens instead of being skipped
+    pub retain_comments: bool,
+    /// Whether errors are kept in [Tokenizer::errors] so parsing can carry on after them
+    pub recovering: bool,
+    /// The errors found while recovering, in the order they were found
+    pub errors: Vec<CastleError>,
+    /// The doc comments (`##` or `///`) before a token, keyed by where the token starts
+    pub docs: BTreeMap<Position, Box<str>>,
+    /// The lines of the doc comments since the last token
+    pending_docs: Vec<String>,
+    /// The line of the last doc comment in [Tokenizer::pending_docs]
+    docs_line: u32,
+    /// The line the last token (other than a line terminator) ended on, doc comments after a
+    /// token on the same line are plain comments
+    token_line: u32,
 }
 
 impl<R: Read> Tokenizable for Tokenizer<R> {
@@ -63,6 +81,20 @@ impl<R: Read> Tokenizable for Tokenizer<R> {
 
         Ok(self.peeked.get(skip_n))
     }
+
+    fn recover(&mut self, error: CastleError) -> Result<(), CastleError> {
+        match self.recovering {
+            true => {
+                self.errors.push(error);
+                Ok(())
+            },
+            false => Err(error),
+        }
+    }
+
+    fn take_docs(&mut self, pos: Position) -> Option<Box<str>> {
+        self.docs.remove(&pos)
+    }
 }
 
 impl<R: Read> Tokenizer<R> {
@@ -70,12 +102,44 @@ impl<R: Read> Tokenizer<R> {
         Self {
             cursor: Cursor::new(reader),
             peeked: VecDeque::new(),
+            retain_comments: false,
+            recovering: false,
+            errors: Vec::new(),
+            docs: BTreeMap::new(),
+            pending_docs: Vec::new(),
+            docs_line: 0,
+            token_line: 0,
+        }
+    }
+
+    /// A tokenizer that returns comments as [TokenKind::Comment] tokens, for tools that
+    /// need to keep them (eg: formatters). Parsers don't expect comment tokens.
+    ///
+    /// Doc comments are returned as comments too, `/// docs` is returned as `## docs`.
+    pub fn with_comments(reader: R) -> Self {
+        Self {
+            retain_comments: true,
+            ..Self::new(reader)
+        }
+    }
+
+    /// A tokenizer that keeps the errors it (and the parser using it) can carry on after in
+    /// [Tokenizer::errors], for tools that want every error in the source (eg: editors).
+    /// Characters that can't be tokenized are skipped.
+    pub fn recovering(reader: R) -> Self {
+        Self {
+            recovering: true,
+            ..Self::new(reader)
         }
     }
 
     /// Advances the cursor and returns the next token
-    /// Skips comments and whitespace (not including line terminators)
+    /// Skips comments (unless [Tokenizer::retain_comments] is set) and whitespace (not including line terminators)
     /// Coalesces consecutive line terminators (\n and \r)
+    ///
+    /// Doc comments are kept in [Tokenizer::docs] for the token on the line after them, docs
+    /// separated from the token by a blank line or another comment are dropped. Doc comments
+    /// after a token on the same line are skipped like other comments.
     pub fn advance(&mut self) -> Result<Option<Token>, CastleError> {
         loop {
             // skip whitespaces
@@ -92,39 +156,79 @@ impl<R: Read> Tokenizer<R> {
                 }
             };
 
-            if let Ok(c) = char::try_from(next_ch) {
-                let token = match c {
-                    '#' => {
-                        skip_comment(&mut self.cursor)?;
-                        continue;
+            let c = match char::try_from(next_ch) {
+                Ok(c) => c,
+                Err(_) => return Ok(None), // EOF
+            };
+            match self.parse_token(c, start) {
+                Ok(Some(token)) => {
+                    if token.kind != TokenKind::LineTerminator {
+                        if !self.pending_docs.is_empty() && token.span.start.line_number() == self.docs_line + 1 {
+                            self.docs.insert(token.span.start, self.pending_docs.join("\n").into());
+                        }
+                        self.pending_docs.clear();
+                        self.token_line = token.span.end.line_number();
                     }
-                    '\r' | '\n' => parse_newline(&mut self.cursor, start)?,
-                    '"' => parse_string(&mut self.cursor, start)?,
-                    // Operator & Punctuator
-                    '=' | '<' | '>' | '*' | '/' | '%' | '&' | '|' | '^' | ':' | '{' | '}' | '['
-                    | ']' | ',' | ';' | '@' | '(' | ')' => parse_operator(&mut self.cursor, start)?,
-                    '-' => parse_number(&mut self.cursor, start)?,
-                    _ if c.is_digit(10) => parse_number(&mut self.cursor, start)?,
-                    _ if c.is_ascii_alphabetic() => {
-                        parse_ident_or_keyword(&mut self.cursor, start)?
+                    return Ok(Some(token))
+                },
+                Ok(None) => continue, // a skipped comment
+                Err(e) if self.recovering => {
+                    self.errors.push(e);
+                    // skip what couldn't be tokenized so tokenizing can carry on after it
+                    if self.cursor.pos() == start {
+                        self.cursor.next_char()?;
                     }
-                    _ => Err(CastleError::syntax(
-                        format!(
-                            "Unexpected '{}' at line {}, column {}",
-                            c,
-                            start.line_number(),
-                            start.column_number()
-                        ),
-                        start,
-                    ))?,
-                };
-
-                return Ok(Some(token));
-            } else {
-                return Ok(None); // EOF
+                },
+                Err(e) => return Err(e),
             }
         }
     }
+
+    /// Parses the token starting with `c`, returns `None` for skipped comments
+    fn parse_token(&mut self, c: char, start: Position) -> Result<Option<Token>, CastleError> {
+        let token = match c {
+            '#' if self.retain_comments => parse_comment(&mut self.cursor, start)?,
+            '/' if self.retain_comments && is_doc_comment(&mut self.cursor)? => parse_slash_comment(&mut self.cursor, start)?,
+            '#' | '/' if is_doc_comment(&mut self.cursor)? && start.line_number() != self.token_line => {
+                if start.line_number() != self.docs_line + 1 {
+                    self.pending_docs.clear();
+                }
+                let docs = parse_doc_comment(&mut self.cursor)?;
+                self.pending_docs.push(docs);
+                self.docs_line = start.line_number();
+                return Ok(None);
+            }
+            '#' | '/' if is_doc_comment(&mut self.cursor)? => {
+                parse_doc_comment(&mut self.cursor)?;
+                return Ok(None);
+            }
+            '#' => {
+                skip_comment(&mut self.cursor)?;
+                self.pending_docs.clear();
+                return Ok(None);
+            }
+            '\r' | '\n' => parse_newline(&mut self.cursor, start)?,
+            '"' => parse_string(&mut self.cursor, start)?,
+            // Operator & Punctuator
+            '=' | '<' | '>' | '*' | '/' | '%' | '&' | '|' | '^' | ':' | '{' | '}' | '['
+            | ']' | ',' | ';' | '@' | '$' | '(' | ')' => parse_operator(&mut self.cursor, start)?,
+            '-' => parse_number(&mut self.cursor, start)?,
+            _ if c.is_ascii_digit() => parse_number(&mut self.cursor, start)?,
+            _ if c.is_ascii_alphabetic() => {
+                parse_ident_or_keyword(&mut self.cursor, start)?
+            }
+            _ => Err(CastleError::syntax(
+                format!(
+                    "Unexpected '{}' at line {}, column {}",
+                    c,
+                    start.line_number(),
+                    start.column_number()
+                ),
+                start,
+            ))?,
+        };
+        Ok(Some(token))
+    }
 }
 
 fn is_whitespace(ch: u32) -> bool {
@@ -141,4 +245,56 @@ fn is_whitespace(ch: u32) -> bool {
 fn skips_newlines_with_eof() {
     let mut tokenizer = Tokenizer::new("\n\n".as_bytes());
     assert_eq!(tokenizer.next(true).unwrap(), None)
-}
\ No newline at end of file
+}
+
+#[test]
+fn retains_comments_when_asked_to() {
+    let mut tokenizer = Tokenizer::with_comments("name # the user's name \nage".as_bytes());
+    let kinds: Vec<_> = std::iter::from_fn(|| tokenizer.next(false).unwrap()).map(|token| token.kind).collect();
+    assert_eq!(kinds, vec![
+        TokenKind::Identifier("name".into()),
+        TokenKind::Comment(" the user's name".into()),
+        TokenKind::LineTerminator,
+        TokenKind::Identifier("age".into()),
+    ]);
+}
+#[test]
+fn keeps_doc_comments_for_the_next_token() {
+    let mut tokenizer = Tokenizer::new("# not docs\n## A user\n##\n/// of the app\ntype User".as_bytes());
+    let token = tokenizer.next(true).unwrap().unwrap();
+    assert_eq!(token.kind, TokenKind::Keyword(crate::Keyword::Type));
+    assert_eq!(tokenizer.take_docs(token.span.start), Some("A user\n\nof the app".into()));
+    assert_eq!(tokenizer.take_docs(token.span.start), None);
+}
+
+#[test]
+fn banner_comments_are_not_doc_comments() {
+    let mut tokenizer = Tokenizer::new("########\n## Users\n########\ntype User".as_bytes());
+    let token = tokenizer.next(true).unwrap().unwrap();
+    assert_eq!(tokenizer.take_docs(token.span.start), None);
+
+    let mut tokenizer = Tokenizer::new("##### Users #####\n##Users\ntype User".as_bytes());
+    let token = tokenizer.next(true).unwrap().unwrap();
+    assert_eq!(token.kind, TokenKind::Keyword(crate::Keyword::Type));
+    assert_eq!(tokenizer.take_docs(token.span.start), None);
+}
+
+#[test]
+fn doc_comments_before_a_blank_line_are_dropped() {
+    let mut tokenizer = Tokenizer::new("## Users\n\n## A user\ntype User\n## The end\n  \n".as_bytes());
+    let token = tokenizer.next(true).unwrap().unwrap();
+    assert_eq!(tokenizer.take_docs(token.span.start), Some("A user".into()));
+
+    let mut tokenizer = Tokenizer::new("## A user\n\ntype User".as_bytes());
+    let token = tokenizer.next(true).unwrap().unwrap();
+    assert_eq!(tokenizer.take_docs(token.span.start), None);
+}
+
+#[test]
+fn doc_comments_after_a_token_are_not_docs() {
+    let mut tokenizer = Tokenizer::new("name ## trailing\nage /// trailing\nemail".as_bytes());
+    for _ in 0..3 {
+        let token = tokenizer.next(true).unwrap().unwrap();
+        assert_eq!(tokenizer.take_docs(token.span.start), None);
+    }
+}